@@ -0,0 +1,119 @@
+//! Latency of one `browser` invocation against a running daemon
+//!
+//! Starts a stub daemon in this process and runs the `browser` binary against
+//! it, reporting wall-clock time per invocation and how many connections and
+//! messages each one needed. Run with `cargo bench --bench dispatch`; pass a
+//! number to change the iterations per case.
+
+use std::env;
+#[cfg(unix)]
+use std::time::Duration;
+
+/// Invocations per case unless a count is given
+const DEFAULT_ITERATIONS: usize = 50;
+
+/// Invocations run before timing starts
+const WARMUP: usize = 3;
+
+/// Commands timed, as `browser` arguments
+const CASES: &[&[&str]] = &[&["ping"], &["back"]];
+
+fn main() {
+    let iterations = env::args()
+        .skip(1)
+        .find_map(|arg| arg.parse().ok())
+        .unwrap_or(DEFAULT_ITERATIONS);
+
+    #[cfg(unix)]
+    run(iterations);
+    #[cfg(not(unix))]
+    let _ = iterations;
+}
+
+#[cfg(unix)]
+fn run(iterations: usize) {
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use std::process::Command;
+    use std::thread;
+    use std::time::Instant;
+
+    let root = env::temp_dir().join(format!("browser-dispatch-bench-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    let socket_dir = root.join("run/browser");
+    fs::create_dir_all(&socket_dir).unwrap();
+    fs::set_permissions(&socket_dir, fs::Permissions::from_mode(0o700)).unwrap();
+    fs::create_dir_all(root.join("config")).unwrap();
+
+    let socket = socket_dir.join("daemon.sock");
+    {
+        let socket = socket.clone();
+        thread::spawn(move || stub::serve(&socket, Duration::ZERO));
+    }
+    while !socket.exists() {
+        thread::sleep(Duration::from_millis(1));
+    }
+
+    let browser = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_browser"))
+            .args(args)
+            .env("XDG_CONFIG_HOME", root.join("config"))
+            .env("XDG_RUNTIME_DIR", root.join("run"))
+            .env("XDG_STATE_HOME", root.join("state"))
+            .env_remove("BROWSER_SOCKET_PATH")
+            .env_remove("BROWSER_CONTEXT")
+            .current_dir(&root)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "browser {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr)
+        );
+    };
+
+    for args in CASES {
+        for _ in 0..WARMUP {
+            browser(args);
+        }
+
+        let connections = stub::connections();
+        let messages = stub::messages();
+        let mut samples: Vec<Duration> = (0..iterations)
+            .map(|_| {
+                let started = Instant::now();
+                browser(args);
+                started.elapsed()
+            })
+            .collect();
+        samples.sort();
+
+        let runs = iterations.max(1) as f64;
+        let mean = samples.iter().sum::<Duration>() / iterations.max(1) as u32;
+        println!(
+            "dispatch/{:<8} mean {:>8.2?}  p50 {:>8.2?}  p95 {:>8.2?}  connections/run {:.1}  messages/run {:.1}",
+            args.join(" "),
+            mean,
+            percentile(&samples, 50),
+            percentile(&samples, 95),
+            (stub::connections() - connections) as f64 / runs,
+            (stub::messages() - messages) as f64 / runs,
+        );
+    }
+
+    let _ = fs::remove_dir_all(&root);
+}
+
+/// The `p`th percentile of sorted `samples`
+#[cfg(unix)]
+fn percentile(samples: &[Duration], p: usize) -> Duration {
+    if samples.is_empty() {
+        return Duration::ZERO;
+    }
+    samples[(samples.len() - 1) * p / 100]
+}
+
+#[cfg(unix)]
+#[path = "../tests/support/stub_daemon.rs"]
+mod stub;
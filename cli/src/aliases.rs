@@ -0,0 +1,547 @@
+//! Ref aliases and page objects
+//!
+//! `@name` is accepted anywhere a ref is taken and resolves, in order, to:
+//!
+//! 1. an alias set for the session and the current page's domain
+//! 2. an alias set for the session on any domain
+//! 3. an alias from the project's `.browser.toml`
+//! 4. a locator from a `pages.toml` page whose URL pattern matches the
+//!    current page
+//!
+//! `@page.name` names a page-object locator directly, regardless of URL.
+//! Aliases live in `aliases.toml` and page objects in `pages.toml`, both in
+//! the config directory. The current page is taken from the session's last
+//! snapshot.
+
+use crate::error::{CliError, Result};
+use crate::utils::dirs::get_config_dir;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::path::{Path, PathBuf};
+
+// =============================================================================
+// Constants
+// =============================================================================
+
+/// Prefix marking an alias where a ref is expected
+pub const ALIAS_PREFIX: char = '@';
+
+/// Alias store file name in the config directory
+const ALIASES_FILE: &str = "aliases.toml";
+
+/// Page-object file name in the config directory
+const PAGES_FILE: &str = "pages.toml";
+
+// =============================================================================
+// Alias Store
+// =============================================================================
+
+/// Aliases for one session
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct SessionAliases {
+    /// Aliases that apply on every domain
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub aliases: BTreeMap<String, String>,
+
+    /// Aliases scoped to a domain (and its subdomains)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub domains: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+/// Contents of `aliases.toml`
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct AliasStore {
+    #[serde(default)]
+    pub sessions: BTreeMap<String, SessionAliases>,
+}
+
+impl AliasStore {
+    /// Load the alias store, returning an empty one if the file is missing
+    pub fn load() -> Result<Self> {
+        Self::load_from(&aliases_path()?)
+    }
+
+    fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = fs::read_to_string(path)?;
+        toml::from_str(&contents).map_err(|e| {
+            CliError::ConfigError(format!(
+                "invalid alias file {}: {}",
+                path.display(),
+                e.to_string().trim_end()
+            ))
+        })
+    }
+
+    /// Apply `change` to the alias store and write it back if it succeeds
+    ///
+    /// Holds a lock on `aliases.toml.lock` from load to save, so concurrent
+    /// `alias set` and `alias rm` calls do not lose each other's changes,
+    /// and replaces the file by renaming, so a crash never truncates it.
+    pub fn update<T>(change: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        Self::update_at(&aliases_path()?, change)
+    }
+
+    fn update_at<T>(path: &Path, change: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let _lock = lock(path)?;
+
+        let mut store = Self::load_from(path)?;
+        let result = change(&mut store)?;
+
+        let contents = toml::to_string_pretty(&store)
+            .map_err(|e| CliError::CommandFailed(format!("failed to encode aliases: {}", e)))?;
+        let tmp = path.with_extension("toml.tmp");
+        fs::write(&tmp, contents)?;
+        fs::rename(&tmp, path)?;
+        Ok(result)
+    }
+
+    /// Set an alias, optionally scoped to a domain
+    pub fn set(&mut self, session: &str, name: &str, locator: &str, domain: Option<&str>) {
+        let session = self.sessions.entry(session.to_string()).or_default();
+        let aliases = match domain {
+            Some(domain) => session.domains.entry(domain.to_lowercase()).or_default(),
+            None => &mut session.aliases,
+        };
+        aliases.insert(name.to_string(), locator.to_string());
+    }
+
+    /// Remove an alias, returning whether it existed
+    pub fn remove(&mut self, session: &str, name: &str, domain: Option<&str>) -> bool {
+        let Some(session_aliases) = self.sessions.get_mut(session) else {
+            return false;
+        };
+
+        let removed = match domain {
+            Some(domain) => {
+                let domain = domain.to_lowercase();
+                let removed = session_aliases
+                    .domains
+                    .get_mut(&domain)
+                    .is_some_and(|aliases| aliases.remove(name).is_some());
+                if session_aliases
+                    .domains
+                    .get(&domain)
+                    .is_some_and(BTreeMap::is_empty)
+                {
+                    session_aliases.domains.remove(&domain);
+                }
+                removed
+            }
+            None => session_aliases.aliases.remove(name).is_some(),
+        };
+
+        if session_aliases.aliases.is_empty() && session_aliases.domains.is_empty() {
+            self.sessions.remove(session);
+        }
+        removed
+    }
+
+    /// Look up an alias for a session, preferring one scoped to the URL's domain
+    pub fn lookup(&self, session: &str, name: &str, url: Option<&str>) -> Option<&str> {
+        let session = self.sessions.get(session)?;
+
+        if let Some(host) = url.and_then(url_host) {
+            let scoped = session
+                .domains
+                .iter()
+                .filter(|(domain, _)| host_matches_domain(&host, domain))
+                // Most specific domain wins
+                .max_by_key(|(domain, _)| domain.len())
+                .and_then(|(_, aliases)| aliases.get(name));
+            if let Some(locator) = scoped {
+                return Some(locator);
+            }
+        }
+
+        session.aliases.get(name).map(String::as_str)
+    }
+}
+
+// =============================================================================
+// Page Objects
+// =============================================================================
+
+/// A page object: a URL pattern and the locators that apply on it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageObject {
+    /// URL glob; `*` matches any run of characters
+    pub url: String,
+
+    #[serde(default)]
+    pub locators: BTreeMap<String, String>,
+}
+
+/// Contents of `pages.toml`
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct PageObjects {
+    #[serde(default)]
+    pub pages: BTreeMap<String, PageObject>,
+}
+
+impl PageObjects {
+    /// Load page objects, returning none if the file is missing
+    pub fn load() -> Result<Self> {
+        let path = pages_path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = fs::read_to_string(&path)?;
+        Self::parse(&contents).map_err(|e| {
+            CliError::ConfigError(format!(
+                "invalid page file {}: {}",
+                path.display(),
+                e.to_string().trim_end()
+            ))
+        })
+    }
+
+    fn parse(contents: &str) -> std::result::Result<Self, toml::de::Error> {
+        toml::from_str(contents)
+    }
+
+    /// Look up `page.name`, or `name` on the first page matching the URL
+    pub fn lookup(&self, name: &str, url: Option<&str>) -> Option<&str> {
+        if let Some((page, locator)) = name.split_once('.') {
+            return self
+                .pages
+                .get(page)?
+                .locators
+                .get(locator)
+                .map(String::as_str);
+        }
+
+        let url = url?;
+        self.pages
+            .values()
+            .filter(|page| glob_matches(&page.url, url))
+            .find_map(|page| page.locators.get(name))
+            .map(String::as_str)
+    }
+}
+
+// =============================================================================
+// Resolution
+// =============================================================================
+
+/// Resolve `@name` to the locator it stands for
+///
+/// `url` is the current page, used to pick domain-scoped aliases and
+/// matching page objects. `project` holds the project file's aliases.
+pub fn resolve(
+    session: &str,
+    alias: &str,
+    url: Option<&str>,
+    project: &BTreeMap<String, String>,
+) -> Result<String> {
+    let name = alias.strip_prefix(ALIAS_PREFIX).unwrap_or(alias);
+
+    if !name.contains('.') {
+        if let Some(locator) = AliasStore::load()?.lookup(session, name, url) {
+            return Ok(locator.to_string());
+        }
+        if let Some(locator) = project.get(name) {
+            return Ok(locator.clone());
+        }
+    }
+
+    if let Some(locator) = PageObjects::load()?.lookup(name, url) {
+        return Ok(locator.to_string());
+    }
+
+    Err(CliError::InvalidArguments(format!(
+        "unknown alias {}{} for session \"{}\"{}",
+        ALIAS_PREFIX,
+        name,
+        session,
+        if url.is_none() {
+            " (take a snapshot so domain and page aliases can match the current page)"
+        } else {
+            ""
+        }
+    )))
+}
+
+/// Validate an alias name: letters, digits, `-` and `_`
+pub fn validate_alias_name(name: &str) -> Result<()> {
+    let name = name.strip_prefix(ALIAS_PREFIX).unwrap_or(name);
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(CliError::InvalidArguments(format!(
+            "invalid alias name \"{}\": use letters, digits, '-' and '_'",
+            name
+        )));
+    }
+    Ok(())
+}
+
+fn aliases_path() -> Result<PathBuf> {
+    Ok(get_config_dir()
+        .map_err(CliError::ConfigError)?
+        .join(ALIASES_FILE))
+}
+
+fn pages_path() -> Result<PathBuf> {
+    Ok(get_config_dir()
+        .map_err(CliError::ConfigError)?
+        .join(PAGES_FILE))
+}
+
+/// Take an exclusive advisory lock on `<path>.lock`, released when dropped
+fn lock(path: &Path) -> Result<File> {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".lock");
+    let file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(false)
+        .open(PathBuf::from(name))?;
+    file.lock()?;
+    Ok(file)
+}
+
+// =============================================================================
+// URL Helpers
+// =============================================================================
+
+/// Extract the lowercase host from a URL
+pub(crate) fn url_host(url: &str) -> Option<String> {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host_port = authority.rsplit('@').next()?;
+    let host = host_port.split(':').next()?;
+    (!host.is_empty()).then(|| host.to_lowercase())
+}
+
+/// Whether a host is the domain or one of its subdomains
+pub(crate) fn host_matches_domain(host: &str, domain: &str) -> bool {
+    host == domain || host.ends_with(&format!(".{}", domain))
+}
+
+/// Match a URL against a glob where `*` matches any run of characters
+fn glob_matches(pattern: &str, url: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let Some(first) = parts.next() else {
+        return url.is_empty();
+    };
+    let Some(mut rest) = url.strip_prefix(first) else {
+        return false;
+    };
+
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No wildcard: the prefix must be the whole URL
+        return rest.is_empty();
+    };
+
+    for part in middle {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup_prefers_domain_scoped_alias() {
+        let mut store = AliasStore::default();
+        store.set("default", "submit", "css=#global", None);
+        store.set("default", "submit", "css=#scoped", Some("Example.com"));
+
+        assert_eq!(
+            store.lookup("default", "submit", Some("https://app.example.com/login")),
+            Some("css=#scoped")
+        );
+        assert_eq!(
+            store.lookup("default", "submit", Some("https://other.org/")),
+            Some("css=#global")
+        );
+        assert_eq!(store.lookup("default", "submit", None), Some("css=#global"));
+    }
+
+    #[test]
+    fn lookup_is_per_session() {
+        let mut store = AliasStore::default();
+        store.set("work", "login", "e3", None);
+
+        assert_eq!(store.lookup("work", "login", None), Some("e3"));
+        assert_eq!(store.lookup("default", "login", None), None);
+    }
+
+    #[test]
+    fn remove_prunes_empty_sessions() {
+        let mut store = AliasStore::default();
+        store.set("default", "submit", "e5", Some("example.com"));
+
+        assert!(!store.remove("default", "submit", None));
+        assert!(store.remove("default", "submit", Some("example.com")));
+        assert!(store.sessions.is_empty());
+    }
+
+    #[test]
+    fn alias_store_round_trips_toml() {
+        let mut store = AliasStore::default();
+        store.set("default", "submit", "role=button[name=\"Save\"]", None);
+        store.set("default", "login", "css=#login", Some("example.com"));
+
+        let encoded = toml::to_string_pretty(&store).expect("encode");
+        let decoded: AliasStore = toml::from_str(&encoded).expect("decode");
+        assert_eq!(
+            decoded.lookup("default", "submit", None),
+            Some("role=button[name=\"Save\"]")
+        );
+        assert_eq!(
+            decoded.lookup("default", "login", Some("https://example.com/")),
+            Some("css=#login")
+        );
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "browser-aliases-test-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir.join(ALIASES_FILE)
+    }
+
+    #[test]
+    fn concurrent_updates_keep_every_alias() {
+        let path = temp_path("concurrent");
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    AliasStore::update_at(&path, |store| {
+                        store.set("default", &format!("a{}", i), "e1", None);
+                        Ok(())
+                    })
+                    .unwrap();
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let store = AliasStore::load_from(&path).unwrap();
+        assert_eq!(store.sessions["default"].aliases.len(), 8);
+
+        // A failed change leaves the file as it was
+        let err = AliasStore::update_at(&path, |store| {
+            store.sessions.clear();
+            Err::<(), _>(CliError::InvalidArguments("no".to_string()))
+        })
+        .unwrap_err();
+        assert!(matches!(err, CliError::InvalidArguments(_)));
+        assert_eq!(AliasStore::load_from(&path).unwrap().sessions.len(), 1);
+
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn invalid_alias_file_is_a_config_error() {
+        let path = temp_path("invalid");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "sessions = 3\n").unwrap();
+
+        let err = AliasStore::load_from(&path).unwrap_err();
+        assert!(matches!(err, CliError::ConfigError(_)), "{:?}", err);
+        let err = AliasStore::update_at(&path, |_| Ok(())).unwrap_err();
+        assert!(matches!(err, CliError::ConfigError(_)), "{:?}", err);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "sessions = 3\n");
+
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn page_objects_match_url_pattern() {
+        let pages = PageObjects::parse(
+            r#"
+[pages.login]
+url = "https://example.com/login*"
+
+[pages.login.locators]
+email = 'label="Email"'
+submit = 'role=button[name="Sign in"]'
+
+[pages.search]
+url = "*://*.example.com/search?*"
+
+[pages.search.locators]
+submit = "css=#search-button"
+"#,
+        )
+        .expect("parse pages");
+
+        assert_eq!(
+            pages.lookup("submit", Some("https://example.com/login?next=/")),
+            Some("role=button[name=\"Sign in\"]")
+        );
+        assert_eq!(
+            pages.lookup("submit", Some("https://www.example.com/search?q=x")),
+            Some("css=#search-button")
+        );
+        assert_eq!(pages.lookup("submit", Some("https://example.com/")), None);
+        assert_eq!(pages.lookup("submit", None), None);
+        assert_eq!(pages.lookup("login.email", None), Some("label=\"Email\""));
+    }
+
+    #[test]
+    fn glob_matches_wildcards() {
+        assert!(glob_matches(
+            "https://example.com/*",
+            "https://example.com/a/b"
+        ));
+        assert!(glob_matches("*example.com*", "https://www.example.com/"));
+        assert!(glob_matches("https://example.com/", "https://example.com/"));
+        assert!(!glob_matches(
+            "https://example.com/",
+            "https://example.com/a"
+        ));
+        assert!(!glob_matches("https://*.org/*", "https://example.com/"));
+    }
+
+    #[test]
+    fn url_host_strips_scheme_port_and_userinfo() {
+        assert_eq!(
+            url_host("https://user@App.Example.com:8443/path?q=1"),
+            Some("app.example.com".to_string())
+        );
+        assert_eq!(
+            url_host("example.com/login"),
+            Some("example.com".to_string())
+        );
+        assert_eq!(url_host("https:///"), None);
+    }
+
+    #[test]
+    fn validate_alias_name_rejects_dots_and_spaces() {
+        assert!(validate_alias_name("submit-btn").is_ok());
+        assert!(validate_alias_name("@submit_btn").is_ok());
+        assert!(validate_alias_name("login.email").is_err());
+        assert!(validate_alias_name("two words").is_err());
+        assert!(validate_alias_name("@").is_err());
+    }
+}
@@ -2,8 +2,12 @@
 //!
 //! Defines all commands and their arguments.
 
-use crate::types::OutputFormat;
+use crate::snapshot::SnapshotFormat;
+use crate::types::{ColorChoice, OutputFormat};
+use crate::utils::duration::parse_duration;
 use clap::{Args, Parser, Subcommand};
+use std::path::PathBuf;
+use std::time::Duration;
 
 /// Browser CLI - Browser Automation for AI Agents
 #[derive(Debug, Parser)]
@@ -14,7 +18,7 @@ use clap::{Args, Parser, Subcommand};
     about = "Browser CLI - Browser Automation for AI Agents",
     long_about = None,
     help_template = "{about}\n\nUsage: {usage}\n\nOptions:\n{options}\n\n{after-help}",
-    after_help = "COMMANDS:\n  navigate <URL>            Navigate the active tab to a URL\n  snapshot                  Take a snapshot of the current page\n  click <REF>               Click on an element\n  type <REF> <TEXT>         Type text into an element\n  scroll <DIRECTION>        Scroll the page or an element\n  tab                       Tab management commands\n  back                      Go back in browser history\n  forward                   Go forward in browser history\n  ping                      Check if daemon is running\n  version                   Show version information\n\nTAB SUBCOMMANDS:\n  browser tab new [URL]             Create a new tab, optionally with a starting URL\n  browser tab close                 Close the active tab\n  browser tab switch <TAB_ID>       Switch to a tab by its ID\n  browser tab list                  List all open tabs with their IDs\n\nQUICK START:\n  browser navigate example.com\n  browser tab new google.com\n  browser snapshot\n  browser click e2\n  browser type e3 \"testexample.com\"\n  browser tab close\n\nTYPICAL WORKFLOW:\n  navigate > snapshot > interact (click/type/scroll) > snapshot (optional)\n\nOUTPUT FORMATS:\n  - human (default)  Plain text output for humans\n  - json             Pretty-printed JSON output for scripting\n  - quiet            No output except for errors\n\nENVIRONMENT VARIABLES:\n  BROWSER_SESSION    Default session name to use\n\nSESSION MANAGEMENT:\n  Sessions allow multiple independent browser windows belonging to the same instance.\n  Each session has its own browser context, cookies, and state.\n  Example:\n    browser -s work navigate https://work.example.com\n    browser -s personal navigate https://personal.example.com\n\nPROFILE MANAGEMENT:\n  Use --profile to specify a custom browser profile directory:\n    browser --profile /path/to/custom/profile navigate example.com\n  This allows using existing browser profiles with saved cookies, bookmarks, etc.\n\nHELP:\n  browser --help\n  browser navigate --help\n  browser tab --help\n  browser tab new --help"
+    after_help = "COMMANDS:\n  navigate <URL>            Navigate the active tab to a URL\n  snapshot                  Take a snapshot of the current page\n  click <REF>               Click on an element\n  type <REF> <TEXT>         Type text into an element\n  scroll <DIRECTION>        Scroll the page or an element\n  tab                       Tab management commands\n  alias                     Manage @name ref aliases\n  history                   Search archived snapshots\n  config                    Read and change settings in config.toml\n  context                   Manage named contexts\n  back                      Go back in browser history\n  forward                   Go forward in browser history\n  ping                      Check if daemon is running\n  daemonctl                 Manage the background daemon\n  daemon --foreground       Run the daemon attached, for services and containers\n  doctor                    Check the environment for problems\n  version [--all]           Show version information\n\nTAB SUBCOMMANDS:\n  browser tab new [URL]             Create a new tab, optionally with a starting URL\n  browser tab close                 Close the active tab\n  browser tab switch <TAB_ID>       Switch to a tab by its ID\n  browser tab list                  List all open tabs with their IDs\n\nALIAS SUBCOMMANDS:\n  browser alias set <NAME> <LOCATOR>  Save a locator as @NAME for the session\n  browser alias list                  List aliases and page-object locators\n  browser alias rm <NAME>             Remove an alias\n\nHISTORY SUBCOMMANDS:\n  browser history search <QUERY>    Find archived snapshots containing text\n  browser history show <ID>         Print an archived snapshot\n\nCONFIG SUBCOMMANDS:\n  browser config get <KEY>          Print the effective value of a key\n  browser config set <KEY> <VALUE>  Save a value in config.toml\n  browser config list               List every key that has a value\n  browser config show [--origin]    Print every key and where its value came from\n  browser config path               Print the config file path\n\nCONTEXT SUBCOMMANDS:\n  browser context create <NAME>     Save --session, --profile and --socket as a context\n  browser context use <NAME>        Make a context the default\n  browser context list              List contexts, marking the active one\n  browser context rm <NAME>         Remove a context\n\nDAEMONCTL SUBCOMMANDS:\n  browser daemonctl status          Print the daemon's PID, uptime, version and sessions\n  browser daemonctl stop            Shut the daemon down\n  browser daemonctl restart         Stop the daemon and start a new one\n  browser daemonctl logs [-f]       Print the daemon log, optionally following it\n\nQUICK START:\n  browser navigate example.com\n  browser tab new google.com\n  browser snapshot\n  browser click e2\n  browser type e3 \"testexample.com\"\n  browser tab close\n\nTYPICAL WORKFLOW:\n  navigate > snapshot > interact (click/type/scroll) > snapshot (optional)\n  Add --then-snapshot or --then-diff to an action to get the snapshot in the same call:\n    browser click e2 --then-diff --settle 300\n\nOUTPUT FORMATS:\n  - human (default)  Plain text output for humans\n  - json             Pretty-printed JSON output for scripting\n  - ndjson           One compact JSON object per line\n  - yaml             YAML output\n  - table            Aligned columns (tab lists, aliases, search results)\n  - template         Render with --template '{{title}} {{url}}'\n  - quiet            No output except for errors\n  Human output is colored on terminals; use --color never or NO_COLOR=1 to disable.\n\nQUERIES:\n  --query extracts part of the response before it is formatted:\n    browser tab list --query 'tabs[?active].url'\n    browser snapshot -q title\n\nTRACING:\n  --timing adds the command ID and connect/daemon/total timings to the output;\n  --request-id sets the command IDs sent to the daemon:\n    browser --request-id trace-7f3a click e2 --timing\n\nTIMEOUTS:\n  --timeout bounds each command and is passed to the daemon as a deadline;\n  --connect-timeout bounds connecting to the daemon. Durations take a unit:\n    browser click e2 --timeout 5s\n    browser snapshot --timeout 2m --connect-timeout 500ms\n\nENVIRONMENT VARIABLES:\n  BROWSER_SESSION    Default session name to use\n  BROWSER_CONTEXT    Named context to use\n  BROWSER_ARCHIVE    Set to 1 to archive every snapshot for `browser history`\n  NO_COLOR           Disable colored output unless --color always is given\n\nSESSION MANAGEMENT:\n  Sessions allow multiple independent browser windows belonging to the same instance.\n  Each session has its own browser context, cookies, and state.\n  Example:\n    browser -s work navigate https://work.example.com\n    browser -s personal navigate https://personal.example.com\n\nPROFILE MANAGEMENT:\n  Use --profile to specify a custom browser profile directory:\n    browser --profile /path/to/custom/profile navigate example.com\n  This allows using existing browser profiles with saved cookies, bookmarks, etc.\n\nHELP:\n  browser --help\n  browser navigate --help\n  browser tab --help\n  browser tab new --help"
 )]
 pub struct Cli {
     /// Session name to use (overrides BROWSER_SESSION env var)
@@ -25,9 +29,57 @@ pub struct Cli {
     #[arg(long, global = true)]
     pub profile: Option<String>,
 
-    /// Output format: human (plain text), json (pretty JSON), quiet (errors only)
-    #[arg(short, long, global = true, default_value = "human")]
-    pub output: OutputFormat,
+    /// Named context to use (overrides BROWSER_CONTEXT env var and `context use`)
+    #[arg(long, global = true, value_name = "NAME")]
+    pub context: Option<String>,
+
+    /// Output format: human, json, ndjson, yaml, table, template, quiet (errors only) [default: human]
+    #[arg(short, long, global = true)]
+    pub output: Option<OutputFormat>,
+
+    /// Extract part of the response data, e.g. 'tabs[?active].url'
+    #[arg(short, long, global = true, value_name = "PATH")]
+    pub query: Option<String>,
+
+    /// Render response data with a template, e.g. '{{title}} {{url}}' (implies -o template)
+    #[arg(long, global = true, value_name = "TEMPLATE")]
+    pub template: Option<String>,
+
+    /// Color human output: auto (terminals only, honours NO_COLOR), always, never
+    #[arg(long, global = true, value_name = "WHEN", default_value = "auto")]
+    pub color: ColorChoice,
+
+    /// Add the command ID, session and timings (connect, daemon, total) to the output
+    #[arg(long, global = true, visible_alias = "meta")]
+    pub timing: bool,
+
+    /// How long to wait for each command, e.g. 500ms, 10s, 2m (overrides command_timeout_ms)
+    #[arg(long, global = true, value_name = "DURATION", value_parser = parse_duration)]
+    pub timeout: Option<Duration>,
+
+    /// How long to wait to connect to the daemon (overrides connection_timeout_ms)
+    #[arg(long, global = true, value_name = "DURATION", value_parser = parse_duration)]
+    pub connect_timeout: Option<Duration>,
+
+    /// Fail with "daemon not running" instead of starting one (overrides autostart)
+    #[arg(long, global = true)]
+    pub no_autostart: bool,
+
+    /// ID to derive daemon command IDs from (ID, ID.1, ...) instead of generating them
+    #[arg(long, global = true, value_name = "ID")]
+    pub request_id: Option<String>,
+
+    /// Take a snapshot after navigate/click/type/scroll and include it in the response
+    #[arg(long, global = true)]
+    pub then_snapshot: bool,
+
+    /// Like --then-snapshot, and also report what changed since the previous snapshot
+    #[arg(long, global = true)]
+    pub then_diff: bool,
+
+    /// Milliseconds to wait after the action before the follow-up snapshot
+    #[arg(long, global = true, value_name = "MS", default_value_t = 0)]
+    pub settle: u64,
 
     #[command(subcommand)]
     pub command: Commands,
@@ -38,35 +90,35 @@ pub enum Commands {
     /// Navigate the active tab to a URL
     #[command(
         about = "Navigate the active tab to a URL",
-        long_about = "Navigate the active tab to a URL.\n\nUSAGE:\n  browser navigate [OPTIONS] <URL>\n\nARGUMENTS:\n  <URL>  URL to navigate to\n\nEXAMPLES:\n  browser navigate https://example.com\n  browser navigate example.com\n  browser navigate -o json example.com"
+        long_about = "Navigate the active tab to a URL.\n\nUSAGE:\n  browser navigate [OPTIONS] <URL>\n\nARGUMENTS:\n  <URL>  URL to navigate to, or a /path on base_url from .browser.toml\n\nEXAMPLES:\n  browser navigate https://example.com\n  browser navigate example.com\n  browser navigate /checkout\n  browser navigate -o json example.com"
     )]
     Navigate(NavigateArgs),
 
     /// Take a snapshot of the current page
     #[command(
         about = "Take a snapshot of the current page",
-        long_about = "Take a snapshot of the current page, returning the accessibility tree with element references.\n\nUSAGE:\n  browser snapshot [OPTIONS]\n\nEXAMPLES:\n  browser snapshot\n  browser snapshot -o json"
+        long_about = "Take a snapshot of the current page, returning the accessibility tree with element references.\n\nUSAGE:\n  browser snapshot [OPTIONS]\n\nOPTIONS:\n  -f, --format <FORMAT>  Export format: text, markdown, yaml, html [default: text]\n\nEXAMPLES:\n  browser snapshot\n  browser snapshot -o json\n  browser snapshot --format markdown\n  browser snapshot --format html > snapshot.html"
     )]
-    Snapshot,
+    Snapshot(SnapshotArgs),
 
     /// Click on an element
     #[command(
         about = "Click on an element",
-        long_about = "Click on an element using its reference from a snapshot.\n\nUSAGE:\n  browser click [OPTIONS] <REF>\n\nARGUMENTS:\n  <REF>  Element ref to click (from snapshot, e.g., e2)\n\nEXAMPLES:\n  browser click e2\n  browser click -s mysession submit-btn"
+        long_about = "Click on an element using its reference from a snapshot.\n\nUSAGE:\n  browser click [OPTIONS] <REF>\n\nARGUMENTS:\n  <REF>  Element ref (from snapshot, e.g., e2) or locator\n\nLOCATORS:\n  e12                       Ref from a snapshot\n  role=button[name=\"Save\"]  Role with accessible name\n  text=\"Sign in\"            Accessible name of any element\n  label=\"Email\"             Form control by label\n  css=#login                CSS selector\n  testid=submit             data-testid attribute\n  <locator> >> nth=2        Chain selectors; nth is zero-based\n\nEXAMPLES:\n  browser click e2\n  browser click -s mysession submit-btn\n  browser click 'role=button[name=\"Save\"]'\n  browser click 'css=ul.results li >> nth=0'"
     )]
     Click(ClickArgs),
 
     /// Type text into an element
     #[command(
         about = "Type text into an element",
-        long_about = "Type text into an input element.\n\nUSAGE:\n  browser type [OPTIONS] <REF> <TEXT>\n\nARGUMENTS:\n  <REF>   Element ref to type into (from snapshot)\n  <TEXT>  Text to type\n\nEXAMPLES:\n  browser type e3 \"testexample.com\"\n  browser type password \"mysecretpassword\""
+        long_about = "Type text into an input element.\n\nUSAGE:\n  browser type [OPTIONS] <REF> <TEXT>\n\nARGUMENTS:\n  <REF>   Element ref (from snapshot) or locator to type into\n  <TEXT>  Text to type\n\nEXAMPLES:\n  browser type e3 \"testexample.com\"\n  browser type password \"mysecretpassword\"\n  browser type 'label=\"Email\"' \"testexample.com\""
     )]
     Type(TypeArgs),
 
     /// Scroll the page or an element
     #[command(
         about = "Scroll the page or an element",
-        long_about = "Scroll the page or an element.\n\nUSAGE:\n  browser scroll [OPTIONS] <DIRECTION>\n\nARGUMENTS:\n  <DIRECTION>  Direction to scroll: up, down, left, right\n\nOPTIONS:\n  -r, --ref <REF>        Element ref to scroll within (optional)\n  -a, --amount <AMOUNT>  Amount to scroll in pixels (optional)\n\nEXAMPLES:\n  browser scroll down\n  browser scroll down -a 500\n  browser scroll right -r e5 -a 200"
+        long_about = "Scroll the page or an element.\n\nUSAGE:\n  browser scroll [OPTIONS] <DIRECTION>\n\nARGUMENTS:\n  <DIRECTION>  Direction to scroll: up, down, left, right\n\nOPTIONS:\n  -r, --ref <REF>        Element ref or locator to scroll within (optional)\n  -a, --amount <AMOUNT>  Amount to scroll in pixels (optional)\n\nEXAMPLES:\n  browser scroll down\n  browser scroll down -a 500\n  browser scroll right -r e5 -a 200"
     )]
     Scroll(ScrollArgs),
 
@@ -78,6 +130,38 @@ pub enum Commands {
     )]
     Tab(TabCommands),
 
+    /// Manage ref aliases
+    #[command(subcommand)]
+    #[command(
+        about = "Manage @name ref aliases",
+        long_about = "Manage @name ref aliases.\n\nAliases name a locator so scripts can use @NAME anywhere a ref is accepted. They are stored per session, optionally scoped to a domain, in aliases.toml in the config directory. Locators declared per URL pattern in pages.toml resolve the same way, and as @PAGE.NAME.\n\nUSAGE:\n  browser alias <SUBCOMMAND>\n\nSUBCOMMANDS:\n  set <NAME> <LOCATOR>  Save a locator as @NAME\n  list                  List aliases and page-object locators\n  rm <NAME>             Remove an alias\n\nEXAMPLES:\n  browser alias set submit-btn 'role=button[name=\"Save\"]'\n  browser alias set login 'css=#login' --domain example.com\n  browser click @submit-btn\n  browser alias list\n  browser alias rm submit-btn"
+    )]
+    Alias(AliasCommands),
+
+    /// Search archived snapshots
+    #[command(subcommand)]
+    #[command(
+        about = "Search archived snapshots",
+        long_about = "Search archived snapshots.\n\nWhen BROWSER_ARCHIVE=1, every snapshot is appended with its title, URL, session and timestamp to an archive in the data directory ($XDG_DATA_HOME/browser/archive). Retention is limited by BROWSER_ARCHIVE_MAX_ENTRIES (default 1000), BROWSER_ARCHIVE_MAX_AGE_DAYS and BROWSER_ARCHIVE_MAX_BYTES (default 100 MiB); 0 disables a limit.\n\nUSAGE:\n  browser history <SUBCOMMAND>\n\nSUBCOMMANDS:\n  search <QUERY>  Find archived snapshots containing text\n  show <ID>       Print an archived snapshot\n\nEXAMPLES:\n  BROWSER_ARCHIVE=1 browser snapshot\n  browser history search \"Payment declined\"\n  browser history show 42"
+    )]
+    History(HistoryCommands),
+
+    /// Read and change settings in config.toml
+    #[command(subcommand)]
+    #[command(
+        about = "Read and change settings in config.toml",
+        long_about = "Read and change settings in config.toml.\n\nSettings are layered: built-in defaults, then the config file ($XDG_CONFIG_HOME/browser/config.toml), then the nearest .browser.toml above the current directory, then environment variables, then flags. `get`, `list` and `show` show the effective values; `set` writes to the config file.\n\nA project .browser.toml may set session, profile (relative to the file), base_url, allowed_domains and an [aliases] table. With base_url set, `navigate /path` and `tab new /path` open paths on that site; with allowed_domains set, other hosts are refused.\n\nUSAGE:\n  browser config <SUBCOMMAND>\n\nSUBCOMMANDS:\n  get <KEY>          Print the effective value of a key\n  set <KEY> <VALUE>  Save a value in the config file\n  list               List every key that has a value\n  show [--origin]    Print every key and where its value came from\n  path               Print the config file path\n\nKEYS:\n  socket_path, session, profile, output, connection_timeout_ms, command_timeout_ms,\n  startup_timeout_ms, startup_backoff_ms, autostart, version_mismatch,\n  base_url, allowed_domains, archive.enabled, archive.max_entries,\n  archive.max_age_days, archive.max_bytes, plugins.daemon.version,\n  plugins.daemon.path\n\nEXAMPLES:\n  browser config set output json\n  browser config set archive.enabled true\n  browser config get command_timeout_ms\n  browser config list\n  browser config show --origin"
+    )]
+    Config(ConfigCommands),
+
+    /// Manage named contexts
+    #[command(subcommand)]
+    #[command(
+        about = "Manage named contexts",
+        long_about = "Manage named contexts.\n\nA context bundles a session, profile and socket path under one name, stored in config.toml. The active context is chosen by --context, then BROWSER_CONTEXT, then `context use`. Its settings override config.toml and .browser.toml; environment variables and flags still override it.\n\nUSAGE:\n  browser context <SUBCOMMAND>\n\nSUBCOMMANDS:\n  create <NAME>  Save a context from --session, --profile and --socket\n  use <NAME>     Make a context the default\n  list           List contexts, marking the active one\n  rm <NAME>      Remove a context\n\nEXAMPLES:\n  browser context create staging --session stg --profile ~/profiles/stg --socket /run/user/1000/browser-stg.sock\n  browser context use staging\n  browser --context local snapshot\n  browser context list"
+    )]
+    Context(ContextCommands),
+
     /// Go back in browser history
     #[command(
         about = "Go back in browser history",
@@ -99,24 +183,77 @@ pub enum Commands {
     )]
     Ping,
 
+    /// Manage the background daemon
+    #[command(subcommand)]
+    #[command(
+        about = "Manage the background daemon",
+        long_about = "Manage the background daemon.\n\nThe CLI starts a daemon on the configured socket when a command cannot connect to one, unless autostart is off (`--no-autostart`, or `autostart = false` when systemd or a container runs it). Its PID is kept in a pidfile beside the socket (daemon.pid for daemon.sock) and its output in the state directory ($XDG_STATE_HOME/browser/daemon.log, rotated at 1 MiB). These commands never start a daemon, except `restart`.\n\nUSAGE:\n  browser daemonctl <SUBCOMMAND>\n\nSUBCOMMANDS:\n  status     Print the daemon's PID, uptime, version, socket and session count\n  stop       Shut the daemon down, signalling its PID if it does not respond\n  restart    Stop the daemon and start a new one\n  logs [-f]  Print the daemon log, optionally following it\n\nEXAMPLES:\n  browser daemonctl status\n  browser daemonctl stop\n  browser --context staging daemonctl restart\n  browser daemonctl logs -f"
+    )]
+    Daemonctl(DaemonctlCommands),
+
+    /// Check the environment for problems
+    #[command(
+        about = "Check the environment for problems",
+        long_about = "Check the environment the CLI and daemon run in, printing a pass, warn or fail line for each check with what to do about problems.\n\nChecks that the config resolves, the socket is private, the daemon answers and speaks the CLI's protocol, the daemon binary is installed and executable, a daemon release exists for this platform, each connected extension speaks the CLI's protocol, and the temp and state directories are writable. The daemon is not started.\n\nExits with code 1 if any check fails. Use -o json to attach the results to a support ticket.\n\nUSAGE:\n  browser doctor\n\nEXAMPLES:\n  browser doctor\n  browser doctor -o json"
+    )]
+    Doctor,
+
     /// Show version information
     #[command(
         about = "Show version information",
-        long_about = "Show version information.\n\nUSAGE:\n  browser version"
+        long_about = "Show version information.\n\nWith --all, also print the version of the running daemon and of the extension in each connected browser window, with the protocol version each speaks. Components whose protocol major version differs from the CLI's cannot work together; `version_mismatch` in the config decides whether commands warn or refuse when that happens. The daemon is not started.\n\nUSAGE:\n  browser version [--all]\n\nEXAMPLES:\n  browser version\n  browser version --all\n  browser version --all -o json"
     )]
-    Version,
+    Version(VersionArgs),
 
     /// Start Deamon
     #[command(
         disable_help_flag = true,
+        args_conflicts_with_subcommands = true,
         about = "Start the browser daemon",
-        long_about = "Start the browser daemon."
+        long_about = "Start the browser daemon.\n\nArguments are passed to the daemon binary, which runs in place of the CLI. Commands start a daemon like this on their own, detached and logging to the state directory, unless autostart is off.\n\nWith --foreground, the daemon runs on the configured socket attached to the terminal or service manager: its output is not redirected, it stays in the CLI's session, and its PID goes in the pidfile so daemonctl can manage it. If systemd passed in a listening socket (LISTEN_FDS and LISTEN_PID, as for socket activation), the daemon listens on that instead of creating the socket.\n\nUSAGE:\n  browser daemon [--foreground] [DAEMON ARGS]...\n  browser daemon install-service --user [--force]\n\nOPTIONS:\n      --foreground  Run attached, on the configured socket\n\nSUBCOMMANDS:\n  install-service   Write systemd user units that run the daemon as a service\n\nEXAMPLES:\n  browser daemon --foreground\n  browser daemon --foreground --port 9333\n  browser daemon install-service --user"
     )]
-    Daemon {
-        /// Arguments to pass to the daemon
-        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
-        args: Vec<String>,
-    },
+    Daemon(DaemonArgs),
+}
+
+/// Arguments for `browser daemon`
+#[derive(Debug, Args)]
+pub struct DaemonArgs {
+    #[command(subcommand)]
+    pub command: Option<DaemonCommands>,
+
+    /// Run attached to the terminal or service manager, on the configured socket
+    #[arg(long)]
+    pub foreground: bool,
+
+    /// Arguments to pass to the daemon
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    pub args: Vec<String>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum DaemonCommands {
+    /// Write systemd units that run the daemon as a service
+    #[command(
+        about = "Write systemd units that run the daemon as a service",
+        long_about = "Write systemd user units that run the daemon as a service: browser-daemon.socket listens on the configured socket, and systemd starts browser-daemon.service (`browser daemon --foreground`) on the first connection, passing it the socket. The units go in $XDG_CONFIG_HOME/systemd/user.\n\nCombine with `autostart = false` so commands never start a daemon of their own. Stop and restart the daemon with systemctl rather than daemonctl.\n\nUSAGE:\n  browser daemon install-service --user [--force]\n\nOPTIONS:\n      --user   Install units for the current user (required)\n      --force  Overwrite units that already exist\n\nEXAMPLES:\n  browser daemon install-service --user\n  systemctl --user daemon-reload\n  systemctl --user enable --now browser-daemon.socket"
+    )]
+    InstallService(InstallServiceArgs),
+}
+
+/// Arguments for `browser daemon install-service`
+#[derive(Debug, Args)]
+pub struct InstallServiceArgs {
+    /// Install units for the current user
+    #[arg(long)]
+    pub user: bool,
+
+    /// Overwrite units that already exist
+    #[arg(long)]
+    pub force: bool,
+
+    /// Print help (`browser daemon` passes its own --help to the daemon)
+    #[arg(short, long, action = clap::ArgAction::Help)]
+    help: Option<bool>,
 }
 
 #[derive(Debug, Subcommand)]
@@ -150,21 +287,69 @@ pub enum TabCommands {
     List,
 }
 
+#[derive(Debug, Subcommand)]
+pub enum AliasCommands {
+    /// Save a locator as an alias
+    #[command(
+        about = "Save a locator as an alias",
+        long_about = "Save a locator as @NAME for the current session.\n\nUSAGE:\n  browser alias set [OPTIONS] <NAME> <LOCATOR>\n\nARGUMENTS:\n  <NAME>     Alias name (letters, digits, '-' and '_')\n  <LOCATOR>  Ref or locator the alias stands for\n\nOPTIONS:\n  -d, --domain <DOMAIN>  Only use the alias on this domain and its subdomains\n\nEXAMPLES:\n  browser alias set submit-btn 'role=button[name=\"Save\"]'\n  browser -s work alias set login 'css=#login' --domain example.com"
+    )]
+    Set(AliasSetArgs),
+
+    /// List aliases
+    #[command(
+        about = "List aliases",
+        long_about = "List the session's aliases and the page-object locators from pages.toml.\n\nUSAGE:\n  browser alias list [OPTIONS]\n\nEXAMPLES:\n  browser alias list\n  browser alias list -o json"
+    )]
+    List,
+
+    /// Remove an alias
+    #[command(
+        about = "Remove an alias",
+        long_about = "Remove an alias from the current session.\n\nUSAGE:\n  browser alias rm [OPTIONS] <NAME>\n\nARGUMENTS:\n  <NAME>  Alias name\n\nOPTIONS:\n  -d, --domain <DOMAIN>  Remove the alias scoped to this domain\n\nEXAMPLES:\n  browser alias rm submit-btn\n  browser alias rm login --domain example.com"
+    )]
+    Rm(AliasRmArgs),
+}
+
+#[derive(Debug, Subcommand)]
+pub enum HistoryCommands {
+    /// Find archived snapshots containing text
+    #[command(
+        about = "Find archived snapshots containing text",
+        long_about = "Find archived snapshots whose title, URL or tree contains the query, newest first. Matching is case-insensitive.\n\nUSAGE:\n  browser history search [OPTIONS] <QUERY>\n\nARGUMENTS:\n  <QUERY>  Text to search for\n\nOPTIONS:\n  -n, --limit <LIMIT>  Maximum number of results [default: 20]\n\nBy default all sessions are searched; pass -s to search one session.\n\nEXAMPLES:\n  browser history search checkout\n  browser -s work history search \"Payment declined\" -n 5\n  browser history search error -o json"
+    )]
+    Search(HistorySearchArgs),
+
+    /// Print an archived snapshot
+    #[command(
+        about = "Print an archived snapshot",
+        long_about = "Print an archived snapshot by the ID shown in search results.\n\nUSAGE:\n  browser history show [OPTIONS] <ID>\n\nARGUMENTS:\n  <ID>  Archive ID\n\nOPTIONS:\n  -f, --format <FORMAT>  Export format: text, markdown, yaml, html [default: text]\n\nEXAMPLES:\n  browser history show 42\n  browser history show 42 --format markdown"
+    )]
+    Show(HistoryShowArgs),
+}
+
 #[derive(Debug, Args)]
 pub struct NavigateArgs {
     /// URL to navigate to
     pub url: String,
 }
 
+#[derive(Debug, Args)]
+pub struct SnapshotArgs {
+    /// Export format: text, markdown, yaml, html
+    #[arg(short, long, default_value = "text")]
+    pub format: SnapshotFormat,
+}
+
 #[derive(Debug, Args)]
 pub struct ClickArgs {
-    /// Element ref to click (from snapshot)
+    /// Element ref (from snapshot) or locator to click
     pub r#ref: String,
 }
 
 #[derive(Debug, Args)]
 pub struct TypeArgs {
-    /// Element ref to type into (from snapshot)
+    /// Element ref (from snapshot) or locator to type into
     pub r#ref: String,
 
     /// Text to type
@@ -176,7 +361,7 @@ pub struct ScrollArgs {
     /// Direction to scroll: up, down, left, right
     pub direction: String,
 
-    /// Element ref to scroll within (optional)
+    /// Element ref or locator to scroll within (optional)
     #[arg(short, long)]
     pub r#ref: Option<String>,
 
@@ -197,6 +382,206 @@ pub struct TabSwitchArgs {
     pub tab_id: i32,
 }
 
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommands {
+    /// Print the effective value of a key
+    #[command(
+        about = "Print the effective value of a key",
+        long_about = "Print the effective value of a key, after the config file, environment variables and flags are applied.\n\nUSAGE:\n  browser config get <KEY>\n\nEXAMPLES:\n  browser config get output\n  browser -s work config get session"
+    )]
+    Get(ConfigGetArgs),
+
+    /// Save a value in the config file
+    #[command(
+        about = "Save a value in the config file",
+        long_about = "Save a value in config.toml, creating the file if needed. Comments and other settings in the file are kept.\n\nUSAGE:\n  browser config set <KEY> <VALUE>\n\nEXAMPLES:\n  browser config set output json\n  browser config set command_timeout_ms 60000\n  browser config set archive.max_age_days 30"
+    )]
+    Set(ConfigSetArgs),
+
+    /// List every key that has a value
+    #[command(
+        about = "List every key that has a value",
+        long_about = "List every key that has a value, with its effective value.\n\nUSAGE:\n  browser config list\n\nEXAMPLES:\n  browser config list\n  browser config list -o json"
+    )]
+    List,
+
+    /// Print every key with its effective value
+    #[command(
+        about = "Print every key with its effective value",
+        long_about = "Print every key with its effective value, including unset ones. With --origin, also print where each value came from: the default, config.toml, a project .browser.toml, an environment variable or a flag.\n\nUSAGE:\n  browser config show [--origin]\n\nEXAMPLES:\n  browser config show\n  browser config show --origin\n  browser config show --origin -o json"
+    )]
+    Show(ConfigShowArgs),
+
+    /// Print the config file path
+    #[command(
+        about = "Print the config file path",
+        long_about = "Print the path of config.toml, whether or not it exists yet.\n\nUSAGE:\n  browser config path\n\nEXAMPLES:\n  $EDITOR \"$(browser config path)\""
+    )]
+    Path,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ContextCommands {
+    /// Save a named context
+    #[command(
+        about = "Save a named context",
+        long_about = "Save the given --session, --profile and --socket under a name in config.toml. Settings left out are not changed when the context is used.\n\nUSAGE:\n  browser context create [OPTIONS] <NAME>\n\nARGUMENTS:\n  <NAME>  Context name (letters, digits, '-' and '_')\n\nOPTIONS:\n  -s, --session <SESSION>  Session the context uses\n      --profile <PROFILE>  Browser profile directory the context uses\n      --socket <PATH>      Daemon socket the context connects to\n\nEXAMPLES:\n  browser context create staging --session stg --profile ~/profiles/stg --socket /run/user/1000/browser-stg.sock\n  browser context create local --session dev"
+    )]
+    Create(ContextCreateArgs),
+
+    /// Make a context the default
+    #[command(
+        about = "Make a context the default",
+        long_about = "Make a context the default by saving its name in config.toml. --context and BROWSER_CONTEXT still take precedence.\n\nUSAGE:\n  browser context use <NAME>\n\nEXAMPLES:\n  browser context use staging"
+    )]
+    Use(ContextNameArgs),
+
+    /// List contexts
+    #[command(
+        about = "List contexts",
+        long_about = "List contexts with their settings, marking the active one with *.\n\nUSAGE:\n  browser context list\n\nEXAMPLES:\n  browser context list\n  browser context list -o json"
+    )]
+    List,
+
+    /// Remove a context
+    #[command(
+        about = "Remove a context",
+        long_about = "Remove a context from config.toml. If it was the default, no context is used afterwards.\n\nUSAGE:\n  browser context rm <NAME>\n\nEXAMPLES:\n  browser context rm staging"
+    )]
+    Rm(ContextNameArgs),
+}
+
+#[derive(Debug, Subcommand)]
+pub enum DaemonctlCommands {
+    /// Show daemon status
+    #[command(
+        about = "Show daemon status",
+        long_about = "Print the PID, uptime, version, socket and number of sessions of the daemon on the configured socket. Exits with code 2 if no daemon is running.\n\nUSAGE:\n  browser daemonctl status\n\nEXAMPLES:\n  browser daemonctl status\n  browser daemonctl status -o json"
+    )]
+    Status,
+
+    /// Stop the daemon
+    #[command(
+        about = "Stop the daemon",
+        long_about = "Ask the daemon to shut down, letting in-flight commands finish and closing its browsers. If it does not respond or exit within 15 seconds, send SIGTERM to the PID it reported or the one in its pidfile, then SIGKILL. Stopping a daemon that is not running succeeds.\n\nA socket-activated daemon is left to systemd, which would start it again; run `systemctl --user stop browser-daemon.socket browser-daemon.service` instead.\n\nUSAGE:\n  browser daemonctl stop\n\nEXAMPLES:\n  browser daemonctl stop"
+    )]
+    Stop,
+
+    /// Restart the daemon
+    #[command(
+        about = "Restart the daemon",
+        long_about = "Stop the daemon, start a new one and print its status. Sessions and their browsers are closed. A socket-activated daemon is left to systemd; run `systemctl --user restart browser-daemon.service` instead.\n\nUSAGE:\n  browser daemonctl restart\n\nEXAMPLES:\n  browser daemonctl restart"
+    )]
+    Restart,
+
+    /// Print the daemon log
+    #[command(
+        about = "Print the daemon log",
+        long_about = "Print the last lines of the daemon log, written when the CLI starts the daemon. A daemon run with `daemon --foreground` writes no log; under systemd, use `journalctl --user -u browser-daemon`.\n\nWith -f and -o json, ndjson or yaml, each line is printed as a {\"line\": ...} record.\n\nUSAGE:\n  browser daemonctl logs [OPTIONS]\n\nOPTIONS:\n  -n, --lines <N>  Number of lines to print (default: 50)\n  -f, --follow     Keep printing new output until interrupted\n\nEXAMPLES:\n  browser daemonctl logs\n  browser daemonctl logs -n 200\n  browser daemonctl logs -f -o ndjson"
+    )]
+    Logs(DaemonLogsArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct VersionArgs {
+    /// Also print the daemon's and connected extensions' versions
+    #[arg(long)]
+    pub all: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct DaemonLogsArgs {
+    /// Number of lines to print
+    #[arg(short = 'n', long, default_value_t = 50)]
+    pub lines: usize,
+
+    /// Keep printing new output until interrupted
+    #[arg(short, long)]
+    pub follow: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct ContextCreateArgs {
+    /// Context name
+    pub name: String,
+
+    /// Daemon socket path the context connects to
+    #[arg(long, value_name = "PATH")]
+    pub socket: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct ContextNameArgs {
+    /// Context name
+    pub name: String,
+}
+
+#[derive(Debug, Args)]
+pub struct ConfigShowArgs {
+    /// Print where each value came from
+    #[arg(long)]
+    pub origin: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct ConfigGetArgs {
+    /// Config key, e.g. output or archive.enabled
+    pub key: String,
+}
+
+#[derive(Debug, Args)]
+pub struct ConfigSetArgs {
+    /// Config key, e.g. output or archive.enabled
+    pub key: String,
+
+    /// New value
+    pub value: String,
+}
+
+#[derive(Debug, Args)]
+pub struct AliasSetArgs {
+    /// Alias name (letters, digits, '-' and '_')
+    pub name: String,
+
+    /// Ref or locator the alias stands for
+    pub locator: String,
+
+    /// Only use the alias on this domain and its subdomains
+    #[arg(short, long)]
+    pub domain: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct AliasRmArgs {
+    /// Alias name
+    pub name: String,
+
+    /// Remove the alias scoped to this domain
+    #[arg(short, long)]
+    pub domain: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct HistorySearchArgs {
+    /// Text to search for
+    #[arg(value_name = "QUERY")]
+    pub text: String,
+
+    /// Maximum number of results
+    #[arg(short = 'n', long, default_value_t = 20)]
+    pub limit: usize,
+}
+
+#[derive(Debug, Args)]
+pub struct HistoryShowArgs {
+    /// Archive ID
+    pub id: u64,
+
+    /// Export format for the snapshot
+    #[arg(short, long, default_value = "text")]
+    pub format: SnapshotFormat,
+}
+
 // =============================================================================
 // Parse Function
 // =============================================================================
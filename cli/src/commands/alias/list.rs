@@ -0,0 +1,73 @@
+use crate::aliases::{AliasStore, PageObjects};
+use crate::commands::{CommandContext, Execute};
+use crate::error::Result;
+use crate::types::{AliasInfo, AliasListData, CommandResponse};
+
+#[derive(Default)]
+pub struct AliasListCommand {}
+
+impl Execute for AliasListCommand {
+    fn execute(&self, ctx: &CommandContext) -> Result<CommandResponse> {
+        let store = AliasStore::load()?;
+        let pages = PageObjects::load()?;
+
+        let mut aliases = Vec::new();
+        if let Some(session) = store.sessions.get(&ctx.session_id) {
+            for (name, locator) in &session.aliases {
+                aliases.push(AliasInfo {
+                    name: name.clone(),
+                    locator: locator.clone(),
+                    domain: None,
+                    page: None,
+                    file: None,
+                });
+            }
+            for (domain, scoped) in &session.domains {
+                for (name, locator) in scoped {
+                    aliases.push(AliasInfo {
+                        name: name.clone(),
+                        locator: locator.clone(),
+                        domain: Some(domain.clone()),
+                        page: None,
+                        file: None,
+                    });
+                }
+            }
+        }
+        let config = ctx.client.config();
+        if let Some(project_file) = &config.project_file {
+            for (name, locator) in &config.aliases {
+                aliases.push(AliasInfo {
+                    name: name.clone(),
+                    locator: locator.clone(),
+                    domain: None,
+                    page: None,
+                    file: Some(project_file.to_string_lossy().into_owned()),
+                });
+            }
+        }
+        for (page_name, page) in &pages.pages {
+            for (name, locator) in &page.locators {
+                aliases.push(AliasInfo {
+                    name: format!("{}.{}", page_name, name),
+                    locator: locator.clone(),
+                    domain: None,
+                    page: Some(page.url.clone()),
+                    file: None,
+                });
+            }
+        }
+
+        let data = AliasListData {
+            session: ctx.session_id.clone(),
+            aliases,
+        };
+
+        Ok(CommandResponse {
+            id: ctx.command_id(),
+            success: true,
+            data: Some(serde_json::to_value(data)?),
+            error: None,
+        })
+    }
+}
@@ -0,0 +1,50 @@
+use crate::aliases::{ALIAS_PREFIX, AliasStore};
+use crate::commands::{CommandContext, Execute};
+use crate::error::{CliError, Result};
+use crate::types::CommandResponse;
+use serde_json::json;
+
+#[derive(Debug, Clone)]
+pub struct AliasRmCommand {
+    pub name: String,
+    pub domain: Option<String>,
+}
+
+impl AliasRmCommand {
+    pub fn new(name: String, domain: Option<String>) -> Self {
+        Self {
+            name: name
+                .strip_prefix(ALIAS_PREFIX)
+                .map(str::to_string)
+                .unwrap_or(name),
+            domain,
+        }
+    }
+}
+
+impl Execute for AliasRmCommand {
+    fn execute(&self, ctx: &CommandContext) -> Result<CommandResponse> {
+        AliasStore::update(|store| {
+            if !store.remove(&ctx.session_id, &self.name, self.domain.as_deref()) {
+                return Err(CliError::InvalidArguments(format!(
+                    "no alias {}{} for session \"{}\"{}",
+                    ALIAS_PREFIX,
+                    self.name,
+                    ctx.session_id,
+                    self.domain
+                        .as_deref()
+                        .map(|d| format!(" on {}", d))
+                        .unwrap_or_default()
+                )));
+            }
+            Ok(())
+        })?;
+
+        Ok(CommandResponse {
+            id: ctx.command_id(),
+            success: true,
+            data: Some(json!({"executed": true})),
+            error: None,
+        })
+    }
+}
@@ -0,0 +1,55 @@
+use crate::aliases::{ALIAS_PREFIX, AliasStore, validate_alias_name};
+use crate::commands::{CommandContext, Execute};
+use crate::error::{CliError, Result};
+use crate::locator;
+use crate::types::CommandResponse;
+use serde_json::json;
+
+#[derive(Debug, Clone)]
+pub struct AliasSetCommand {
+    pub name: String,
+    pub locator: String,
+    pub domain: Option<String>,
+}
+
+impl AliasSetCommand {
+    pub fn new(name: String, locator: String, domain: Option<String>) -> Self {
+        Self {
+            name: name
+                .strip_prefix(ALIAS_PREFIX)
+                .map(str::to_string)
+                .unwrap_or(name),
+            locator,
+            domain,
+        }
+    }
+}
+
+impl Execute for AliasSetCommand {
+    fn execute(&self, ctx: &CommandContext) -> Result<CommandResponse> {
+        validate_alias_name(&self.name)?;
+        if self.locator.starts_with(ALIAS_PREFIX) {
+            return Err(CliError::InvalidArguments(
+                "an alias cannot point at another alias".to_string(),
+            ));
+        }
+        locator::parse(&self.locator)?;
+
+        AliasStore::update(|store| {
+            store.set(
+                &ctx.session_id,
+                &self.name,
+                &self.locator,
+                self.domain.as_deref(),
+            );
+            Ok(())
+        })?;
+
+        Ok(CommandResponse {
+            id: ctx.command_id(),
+            success: true,
+            data: Some(json!({"executed": true})),
+            error: None,
+        })
+    }
+}
@@ -1,7 +1,7 @@
 //! Click command implementation
 //!
-//! Clicks on an element identified by a ref.
-//! Ref must be valid (from the current snapshot).
+//! Clicks on an element identified by a ref or locator.
+//! Locators are resolved to a ref before the command is sent.
 
 use crate::commands::utils::validate_ref;
 use crate::commands::{CommandContext, Execute};
@@ -23,7 +23,8 @@ impl ClickCommand {
 impl Execute for ClickCommand {
     fn execute(&self, ctx: &CommandContext) -> Result<CommandResponse> {
         validate_ref(&self.r#ref)?;
-        let payload_json = serde_json::to_value(self)?;
+        let resolved = Self::new(ctx.resolve_ref(&self.r#ref)?);
+        let payload_json = serde_json::to_value(resolved)?;
         ctx.execute(CommandType::Click, payload_json)
     }
 }
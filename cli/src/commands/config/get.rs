@@ -0,0 +1,30 @@
+use crate::commands::{CommandContext, Execute};
+use crate::config::{self, Key};
+use crate::error::{CliError, Result};
+use crate::types::CommandResponse;
+
+#[derive(Debug, Clone)]
+pub struct ConfigGetCommand {
+    pub key: String,
+}
+
+impl ConfigGetCommand {
+    pub fn new(key: String) -> Self {
+        Self { key }
+    }
+}
+
+impl Execute for ConfigGetCommand {
+    fn execute(&self, ctx: &CommandContext) -> Result<CommandResponse> {
+        let key = Key::find(&self.key)?;
+        let value = config::value(ctx.client.config(), key)
+            .ok_or_else(|| CliError::CommandFailed(format!("{} is not set", key.name)))?;
+
+        Ok(CommandResponse {
+            id: ctx.command_id(),
+            success: true,
+            data: Some(value),
+            error: None,
+        })
+    }
+}
@@ -0,0 +1,35 @@
+use crate::commands::{CommandContext, Execute};
+use crate::config::{self, KEYS};
+use crate::error::Result;
+use crate::types::{CommandResponse, ConfigEntry, ConfigListData};
+
+#[derive(Debug, Clone, Default)]
+pub struct ConfigListCommand;
+
+impl Execute for ConfigListCommand {
+    fn execute(&self, ctx: &CommandContext) -> Result<CommandResponse> {
+        let entries = KEYS
+            .iter()
+            .filter_map(|key| {
+                config::value(ctx.client.config(), key).map(|value| ConfigEntry {
+                    key: key.name.to_string(),
+                    value,
+                    origin: None,
+                })
+            })
+            .collect();
+
+        let data = ConfigListData {
+            path: config::config_path()?.to_string_lossy().into_owned(),
+            project: None,
+            entries,
+        };
+
+        Ok(CommandResponse {
+            id: ctx.command_id(),
+            success: true,
+            data: Some(serde_json::to_value(data)?),
+            error: None,
+        })
+    }
+}
@@ -0,0 +1,5 @@
+pub mod get;
+pub mod list;
+pub mod path;
+pub mod set;
+pub mod show;
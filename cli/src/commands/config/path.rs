@@ -0,0 +1,24 @@
+use crate::commands::{CommandContext, Execute};
+use crate::config;
+use crate::error::Result;
+use crate::types::{CommandResponse, ConfigPathData};
+
+#[derive(Debug, Clone, Default)]
+pub struct ConfigPathCommand;
+
+impl Execute for ConfigPathCommand {
+    fn execute(&self, ctx: &CommandContext) -> Result<CommandResponse> {
+        let path = config::config_path()?;
+        let data = ConfigPathData {
+            exists: path.exists(),
+            path: path.to_string_lossy().into_owned(),
+        };
+
+        Ok(CommandResponse {
+            id: ctx.command_id(),
+            success: true,
+            data: Some(serde_json::to_value(data)?),
+            error: None,
+        })
+    }
+}
@@ -0,0 +1,30 @@
+use crate::commands::{CommandContext, Execute};
+use crate::config;
+use crate::error::Result;
+use crate::types::CommandResponse;
+use serde_json::json;
+
+#[derive(Debug, Clone)]
+pub struct ConfigSetCommand {
+    pub key: String,
+    pub value: String,
+}
+
+impl ConfigSetCommand {
+    pub fn new(key: String, value: String) -> Self {
+        Self { key, value }
+    }
+}
+
+impl Execute for ConfigSetCommand {
+    fn execute(&self, ctx: &CommandContext) -> Result<CommandResponse> {
+        config::set_value(&config::config_path()?, &self.key, &self.value)?;
+
+        Ok(CommandResponse {
+            id: ctx.command_id(),
+            success: true,
+            data: Some(json!({"executed": true})),
+            error: None,
+        })
+    }
+}
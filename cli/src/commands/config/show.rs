@@ -0,0 +1,45 @@
+use crate::commands::{CommandContext, Execute};
+use crate::config::{self, KEYS};
+use crate::error::Result;
+use crate::types::{CommandResponse, ConfigEntry, ConfigListData};
+
+#[derive(Debug, Clone, Default)]
+pub struct ConfigShowCommand {
+    pub origin: bool,
+}
+
+impl ConfigShowCommand {
+    pub fn new(origin: bool) -> Self {
+        Self { origin }
+    }
+}
+
+impl Execute for ConfigShowCommand {
+    fn execute(&self, ctx: &CommandContext) -> Result<CommandResponse> {
+        let config = ctx.client.config();
+        let entries = KEYS
+            .iter()
+            .map(|key| ConfigEntry {
+                key: key.name.to_string(),
+                value: config::value(config, key).unwrap_or(serde_json::Value::Null),
+                origin: self.origin.then(|| config.origin(key.name).to_string()),
+            })
+            .collect();
+
+        let data = ConfigListData {
+            path: config::config_path()?.to_string_lossy().into_owned(),
+            project: config
+                .project_file
+                .as_ref()
+                .map(|path| path.to_string_lossy().into_owned()),
+            entries,
+        };
+
+        Ok(CommandResponse {
+            id: ctx.command_id(),
+            success: true,
+            data: Some(serde_json::to_value(data)?),
+            error: None,
+        })
+    }
+}
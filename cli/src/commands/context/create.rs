@@ -0,0 +1,43 @@
+use crate::commands::{CommandContext, Execute};
+use crate::config::{self, Context};
+use crate::error::Result;
+use crate::types::CommandResponse;
+use serde_json::json;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone)]
+pub struct ContextCreateCommand {
+    pub name: String,
+    pub context: Context,
+}
+
+impl ContextCreateCommand {
+    pub fn new(
+        name: String,
+        session: Option<String>,
+        profile: Option<String>,
+        socket_path: Option<PathBuf>,
+    ) -> Self {
+        Self {
+            name,
+            context: Context {
+                session,
+                profile,
+                socket_path,
+            },
+        }
+    }
+}
+
+impl Execute for ContextCreateCommand {
+    fn execute(&self, ctx: &CommandContext) -> Result<CommandResponse> {
+        config::create_context(&config::config_path()?, &self.name, &self.context)?;
+
+        Ok(CommandResponse {
+            id: ctx.command_id(),
+            success: true,
+            data: Some(json!({"executed": true})),
+            error: None,
+        })
+    }
+}
@@ -0,0 +1,38 @@
+use crate::commands::{CommandContext, Execute};
+use crate::error::Result;
+use crate::types::{CommandResponse, ContextInfo, ContextListData};
+
+#[derive(Debug, Clone, Default)]
+pub struct ContextListCommand;
+
+impl Execute for ContextListCommand {
+    fn execute(&self, ctx: &CommandContext) -> Result<CommandResponse> {
+        let config = ctx.client.config();
+        let contexts = config
+            .contexts
+            .iter()
+            .map(|(name, context)| ContextInfo {
+                name: name.clone(),
+                active: config.context.as_ref() == Some(name),
+                session: context.session.clone(),
+                profile: context.profile.clone(),
+                socket_path: context
+                    .socket_path
+                    .as_ref()
+                    .map(|path| path.to_string_lossy().into_owned()),
+            })
+            .collect();
+
+        let data = ContextListData {
+            active: config.context.clone(),
+            contexts,
+        };
+
+        Ok(CommandResponse {
+            id: ctx.command_id(),
+            success: true,
+            data: Some(serde_json::to_value(data)?),
+            error: None,
+        })
+    }
+}
@@ -0,0 +1,4 @@
+pub mod create;
+pub mod list;
+pub mod rm;
+pub mod use_cmd;
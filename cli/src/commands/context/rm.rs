@@ -0,0 +1,29 @@
+use crate::commands::{CommandContext, Execute};
+use crate::config;
+use crate::error::Result;
+use crate::types::CommandResponse;
+use serde_json::json;
+
+#[derive(Debug, Clone)]
+pub struct ContextRmCommand {
+    pub name: String,
+}
+
+impl ContextRmCommand {
+    pub fn new(name: String) -> Self {
+        Self { name }
+    }
+}
+
+impl Execute for ContextRmCommand {
+    fn execute(&self, ctx: &CommandContext) -> Result<CommandResponse> {
+        config::remove_context(&config::config_path()?, &self.name)?;
+
+        Ok(CommandResponse {
+            id: ctx.command_id(),
+            success: true,
+            data: Some(json!({"executed": true})),
+            error: None,
+        })
+    }
+}
@@ -0,0 +1,29 @@
+use crate::commands::{CommandContext, Execute};
+use crate::config;
+use crate::error::Result;
+use crate::types::CommandResponse;
+use serde_json::json;
+
+#[derive(Debug, Clone)]
+pub struct ContextUseCommand {
+    pub name: String,
+}
+
+impl ContextUseCommand {
+    pub fn new(name: String) -> Self {
+        Self { name }
+    }
+}
+
+impl Execute for ContextUseCommand {
+    fn execute(&self, ctx: &CommandContext) -> Result<CommandResponse> {
+        config::set_current_context(&config::config_path()?, &self.name, ctx.client.config())?;
+
+        Ok(CommandResponse {
+            id: ctx.command_id(),
+            success: true,
+            data: Some(json!({"executed": true})),
+            error: None,
+        })
+    }
+}
@@ -0,0 +1,178 @@
+//! `daemon install-service`: write systemd user units for the daemon
+//!
+//! The socket unit listens on the configured socket and systemd starts the
+//! service, `browser daemon --foreground`, on the first connection, passing
+//! the socket in with `LISTEN_FDS`.
+
+use crate::commands::{CommandContext, Execute};
+use crate::config::ENV_IPC_SOCKET_PATH;
+use crate::daemon::UNIT_NAME;
+use crate::error::{CliError, Result};
+use crate::types::{CommandResponse, ServiceInstallData};
+use crate::utils::dirs::get_config_dir;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Default)]
+pub struct InstallServiceCommand {
+    user: bool,
+    force: bool,
+}
+
+impl InstallServiceCommand {
+    pub fn new(user: bool, force: bool) -> Self {
+        Self { user, force }
+    }
+}
+
+impl Execute for InstallServiceCommand {
+    fn execute(&self, ctx: &CommandContext) -> Result<CommandResponse> {
+        if !cfg!(target_os = "linux") {
+            return Err(CliError::InvalidArguments(
+                "install-service writes systemd units, which only exist on Linux".to_string(),
+            ));
+        }
+        if !self.user {
+            return Err(CliError::InvalidArguments(
+                "only user services are supported; pass --user".to_string(),
+            ));
+        }
+
+        let exe = std::env::current_exe().map_err(|e| {
+            CliError::CommandFailed(format!("failed to determine current executable: {}", e))
+        })?;
+        let socket = &ctx.client.config().ipc_socket_path;
+        let units = [
+            (format!("{}.socket", UNIT_NAME), socket_unit(socket)),
+            (format!("{}.service", UNIT_NAME), service_unit(&exe, socket)),
+        ];
+
+        let dir = unit_dir()?;
+        if !self.force
+            && let Some(existing) = units
+                .iter()
+                .map(|(name, _)| dir.join(name))
+                .find(|path| path.exists())
+        {
+            return Err(CliError::CommandFailed(format!(
+                "{} already exists; pass --force to overwrite it",
+                existing.display()
+            )));
+        }
+
+        fs::create_dir_all(&dir)?;
+        let mut written = Vec::new();
+        for (name, content) in &units {
+            let path = dir.join(name);
+            fs::write(&path, content)?;
+            written.push(path.to_string_lossy().into_owned());
+        }
+
+        let data = ServiceInstallData {
+            units: written,
+            next_steps: vec![
+                "systemctl --user daemon-reload".to_string(),
+                format!("systemctl --user enable --now {}.socket", UNIT_NAME),
+            ],
+        };
+
+        Ok(CommandResponse {
+            id: ctx.command_id(),
+            success: true,
+            data: Some(serde_json::to_value(data)?),
+            error: None,
+        })
+    }
+}
+
+/// Directory systemd reads user units from ($XDG_CONFIG_HOME/systemd/user)
+fn unit_dir() -> Result<PathBuf> {
+    let config_dir = get_config_dir().map_err(CliError::ConfigError)?;
+    let base = config_dir.parent().unwrap_or(&config_dir);
+    Ok(base.join("systemd").join("user"))
+}
+
+/// Socket unit listening on the daemon socket, private to the user
+fn socket_unit(socket: &Path) -> String {
+    format!(
+        "[Unit]\n\
+         Description=Browser CLI daemon socket\n\
+         \n\
+         [Socket]\n\
+         ListenStream={}\n\
+         SocketMode=0600\n\
+         DirectoryMode=0700\n\
+         \n\
+         [Install]\n\
+         WantedBy=sockets.target\n",
+        escape(&socket.to_string_lossy())
+    )
+}
+
+/// Service unit running the daemon in the foreground on the same socket
+fn service_unit(exe: &Path, socket: &Path) -> String {
+    format!(
+        "[Unit]\n\
+         Description=Browser CLI daemon\n\
+         Documentation=https://github.com/stakpak/tab\n\
+         Requires={name}.socket\n\
+         After={name}.socket\n\
+         \n\
+         [Service]\n\
+         Environment={env}\n\
+         ExecStart={exe} daemon --foreground\n\
+         Restart=on-failure\n\
+         \n\
+         [Install]\n\
+         Also={name}.socket\n",
+        name = UNIT_NAME,
+        env = quote(&format!(
+            "{}={}",
+            ENV_IPC_SOCKET_PATH,
+            socket.to_string_lossy()
+        )),
+        exe = quote(&exe.to_string_lossy()),
+    )
+}
+
+/// Escape `%`, which systemd expands as a specifier
+fn escape(value: &str) -> String {
+    value.replace('%', "%%")
+}
+
+/// Quote a value as one word on a systemd command or environment line
+fn quote(value: &str) -> String {
+    format!(
+        "\"{}\"",
+        escape(value).replace('\\', "\\\\").replace('"', "\\\"")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn units_run_the_daemon_on_the_configured_socket() {
+        let socket = Path::new("/run/user/1000/browser/daemon.sock");
+
+        let socket_text = socket_unit(socket);
+        assert!(socket_text.contains("ListenStream=/run/user/1000/browser/daemon.sock\n"));
+        assert!(socket_text.contains("SocketMode=0600\n"));
+
+        let service = service_unit(Path::new("/opt/my tools/browser"), socket);
+        assert!(service.contains("ExecStart=\"/opt/my tools/browser\" daemon --foreground\n"));
+        assert!(
+            service.contains(
+                "Environment=\"BROWSER_SOCKET_PATH=/run/user/1000/browser/daemon.sock\"\n"
+            )
+        );
+        assert!(service.contains("Requires=browser-daemon.socket\n"));
+    }
+
+    #[test]
+    fn specifiers_and_quotes_are_escaped() {
+        assert_eq!(escape("/tmp/50%/daemon.sock"), "/tmp/50%%/daemon.sock");
+        assert_eq!(quote("a \"b\" 5%"), "\"a \\\"b\\\" 5%%\"");
+    }
+}
@@ -0,0 +1,158 @@
+use crate::commands::{CommandContext, Execute};
+use crate::daemon;
+use crate::error::{CliError, Result};
+use crate::types::{CommandResponse, DaemonLogsData, OutputFormat};
+use serde_json::json;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+/// How often `--follow` checks the log for new output
+const FOLLOW_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone)]
+pub struct DaemonLogsCommand {
+    pub lines: usize,
+    pub follow: bool,
+}
+
+impl DaemonLogsCommand {
+    pub fn new(lines: usize, follow: bool) -> Self {
+        Self { lines, follow }
+    }
+}
+
+impl Execute for DaemonLogsCommand {
+    fn execute(&self, ctx: &CommandContext) -> Result<CommandResponse> {
+        let path = daemon::log_path(ctx.client.config())?;
+        let content = fs::read(&path).map_err(|err| {
+            if err.kind() == std::io::ErrorKind::NotFound {
+                CliError::CommandFailed(format!(
+                    "no daemon log at {}; it is written once the CLI starts the daemon. \
+                     A daemon run with `browser daemon --foreground` logs to its terminal, \
+                     or under systemd to the journal: journalctl --user -u browser-daemon",
+                    path.display()
+                ))
+            } else {
+                err.into()
+            }
+        })?;
+        let lines = daemon::last_lines(&String::from_utf8_lossy(&content), self.lines);
+
+        if self.follow {
+            // Structured formats get one JSON record per line, as a stream
+            let structured = matches!(
+                ctx.client.config().output,
+                OutputFormat::Json | OutputFormat::Ndjson | OutputFormat::Yaml
+            );
+            let mut stdout = std::io::stdout();
+            for line in &lines {
+                write_line(&mut stdout, line, structured)?;
+            }
+            stdout.flush()?;
+            follow(&path, content.len() as u64, structured)?;
+        }
+
+        let data = DaemonLogsData {
+            path: path.to_string_lossy().into_owned(),
+            lines,
+        };
+
+        Ok(CommandResponse {
+            id: ctx.command_id(),
+            success: true,
+            data: Some(serde_json::to_value(data)?),
+            error: None,
+        })
+    }
+}
+
+/// Copy output appended to the log after `offset` to stdout, until interrupted
+///
+/// A log that shrinks was truncated or replaced, and is read from the start.
+/// When `structured`, only complete lines are written, each as a record.
+fn follow(path: &Path, mut offset: u64, structured: bool) -> Result<()> {
+    let mut stdout = std::io::stdout();
+    let mut pending = Vec::new();
+    loop {
+        let len = fs::metadata(path)
+            .map(|metadata| metadata.len())
+            .unwrap_or(0);
+        if len < offset {
+            offset = 0;
+            pending.clear();
+        }
+        if len > offset {
+            let mut file = File::open(path)?;
+            file.seek(SeekFrom::Start(offset))?;
+            let mut appended = Vec::new();
+            file.read_to_end(&mut appended)?;
+            offset += appended.len() as u64;
+            if structured {
+                pending.extend_from_slice(&appended);
+                for line in take_lines(&mut pending) {
+                    write_line(&mut stdout, &line, true)?;
+                }
+            } else {
+                stdout.write_all(&appended)?;
+            }
+            stdout.flush()?;
+        }
+        thread::sleep(FOLLOW_POLL_INTERVAL);
+    }
+}
+
+/// Remove the complete lines from the front of `pending`
+fn take_lines(pending: &mut Vec<u8>) -> Vec<String> {
+    let Some(end) = pending.iter().rposition(|&b| b == b'\n') else {
+        return Vec::new();
+    };
+    let complete: Vec<u8> = pending.drain(..=end).collect();
+    String::from_utf8_lossy(&complete)
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+/// Write a log line as it is, or as a `{"line": ...}` record
+fn write_line(out: &mut impl Write, line: &str, structured: bool) -> Result<()> {
+    if structured {
+        writeln!(out, "{}", json!({ "line": line }))?;
+    } else {
+        writeln!(out, "{}", line)?;
+    }
+    Ok(())
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn take_lines_keeps_a_partial_line_pending() {
+        let mut pending = b"first\r\nsecond\nthi".to_vec();
+        assert_eq!(take_lines(&mut pending), vec!["first", "second"]);
+        assert_eq!(pending, b"thi");
+
+        pending.extend_from_slice(b"rd\n");
+        assert_eq!(take_lines(&mut pending), vec!["third"]);
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn write_line_emits_one_record_per_line() {
+        let mut out = Vec::new();
+        write_line(&mut out, "IPC server \"ready\"", true).unwrap();
+        write_line(&mut out, "plain", false).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "{\"line\":\"IPC server \\\"ready\\\"\"}\nplain\n"
+        );
+    }
+}
@@ -0,0 +1,5 @@
+pub mod install_service;
+pub mod logs;
+pub mod restart;
+pub mod status;
+pub mod stop;
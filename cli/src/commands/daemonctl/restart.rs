@@ -0,0 +1,23 @@
+use crate::commands::{CommandContext, Execute};
+use crate::daemon;
+use crate::error::Result;
+use crate::types::CommandResponse;
+
+#[derive(Debug, Clone, Default)]
+pub struct DaemonRestartCommand;
+
+impl Execute for DaemonRestartCommand {
+    fn execute(&self, ctx: &CommandContext) -> Result<CommandResponse> {
+        let config = ctx.client.config();
+        daemon::stop_daemon(config)?;
+        daemon::ensure_daemon_running(config)?;
+        let status = ctx.client.status()?;
+
+        Ok(CommandResponse {
+            id: ctx.command_id(),
+            success: true,
+            data: Some(serde_json::to_value(status)?),
+            error: None,
+        })
+    }
+}
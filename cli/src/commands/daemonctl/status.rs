@@ -0,0 +1,19 @@
+use crate::commands::{CommandContext, Execute};
+use crate::error::Result;
+use crate::types::CommandResponse;
+
+#[derive(Debug, Clone, Default)]
+pub struct DaemonStatusCommand;
+
+impl Execute for DaemonStatusCommand {
+    fn execute(&self, ctx: &CommandContext) -> Result<CommandResponse> {
+        let status = ctx.client.status()?;
+
+        Ok(CommandResponse {
+            id: ctx.command_id(),
+            success: true,
+            data: Some(serde_json::to_value(status)?),
+            error: None,
+        })
+    }
+}
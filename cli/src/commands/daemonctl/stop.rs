@@ -0,0 +1,31 @@
+use crate::commands::{CommandContext, Execute};
+use crate::daemon;
+use crate::error::Result;
+use crate::types::{CommandResponse, DaemonStopData};
+
+#[derive(Debug, Clone, Default)]
+pub struct DaemonStopCommand;
+
+impl Execute for DaemonStopCommand {
+    fn execute(&self, ctx: &CommandContext) -> Result<CommandResponse> {
+        let data = match daemon::stop_daemon(ctx.client.config())? {
+            Some(stopped) => DaemonStopData {
+                stopped: true,
+                pid: stopped.pid,
+                signal: stopped.signal.map(str::to_string),
+            },
+            None => DaemonStopData {
+                stopped: false,
+                pid: None,
+                signal: None,
+            },
+        };
+
+        Ok(CommandResponse {
+            id: ctx.command_id(),
+            success: true,
+            data: Some(serde_json::to_value(data)?),
+            error: None,
+        })
+    }
+}
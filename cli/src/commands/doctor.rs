@@ -0,0 +1,480 @@
+//! `doctor`: check the environment the CLI and daemon run in
+//!
+//! Each check passes, warns or fails, and says what to do about the last
+//! two. Nothing is started or changed, except probe files written to check
+//! directory access.
+
+use crate::commands::plugins::daemon::get_daemon_config;
+use crate::commands::{CommandContext, Execute};
+use crate::config::{self, Config};
+use crate::error::{CliError, Result};
+use crate::types::{
+    CheckStatus, CommandResponse, DoctorCheck, DoctorData, PongData, protocol_compatible,
+};
+use crate::utils::dirs::get_state_dir;
+use crate::utils::files::is_executable;
+use crate::utils::plugins::{get_platform_suffix, get_plugins_dir, is_plugin_available};
+use std::path::Path;
+
+/// Name of the daemon plugin binary
+const DAEMON_PLUGIN: &str = "browser-daemon";
+
+#[derive(Debug, Clone, Default)]
+pub struct DoctorCommand {
+    /// Context named by `--context`, to resolve the config again
+    context: Option<String>,
+}
+
+impl DoctorCommand {
+    pub fn new(context: Option<String>) -> Self {
+        Self { context }
+    }
+}
+
+impl Execute for DoctorCommand {
+    fn execute(&self, ctx: &CommandContext) -> Result<CommandResponse> {
+        let config = ctx.client.config();
+        let pong = ctx.client.versions();
+
+        let mut checks = vec![
+            check_config(self.context.as_deref()),
+            check_socket(config),
+            check_daemon(config, &pong),
+            check_plugin(config),
+            check_platform(config),
+        ];
+        checks.extend(check_extensions(ctx, &pong));
+        checks.push(check_writable(
+            "temp dir",
+            Ok(std::env::temp_dir()),
+            "TMPDIR",
+        ));
+        checks.push(check_writable(
+            "state dir",
+            get_state_dir(),
+            "XDG_STATE_HOME",
+        ));
+
+        Ok(CommandResponse {
+            id: ctx.command_id(),
+            success: true,
+            data: Some(serde_json::to_value(DoctorData { checks })?),
+            error: None,
+        })
+    }
+}
+
+/// Number of checks in a doctor response that failed
+pub fn failed_checks(response: &CommandResponse) -> usize {
+    response
+        .data
+        .clone()
+        .and_then(|data| serde_json::from_value::<DoctorData>(data).ok())
+        .map_or(0, |data| {
+            data.checks
+                .iter()
+                .filter(|check| check.status == CheckStatus::Fail)
+                .count()
+        })
+}
+
+fn pass(name: &str, message: String) -> DoctorCheck {
+    DoctorCheck {
+        name: name.to_string(),
+        status: CheckStatus::Pass,
+        message,
+        remedy: None,
+    }
+}
+
+fn warn(name: &str, message: String, remedy: String) -> DoctorCheck {
+    DoctorCheck {
+        name: name.to_string(),
+        status: CheckStatus::Warn,
+        message,
+        remedy: Some(remedy),
+    }
+}
+
+fn fail(name: &str, message: String, remedy: String) -> DoctorCheck {
+    DoctorCheck {
+        name: name.to_string(),
+        status: CheckStatus::Fail,
+        message,
+        remedy: Some(remedy),
+    }
+}
+
+/// The config file, project file and context resolve without errors
+fn check_config(context: Option<&str>) -> DoctorCheck {
+    let path = match config::config_path() {
+        Ok(path) => path,
+        Err(err) => {
+            return fail(
+                "config",
+                err.to_string(),
+                "set HOME or XDG_CONFIG_HOME".to_string(),
+            );
+        }
+    };
+
+    match config::load_config(context) {
+        Ok(config) => {
+            let mut message = if path.exists() {
+                path.display().to_string()
+            } else {
+                format!("{} (not created; using defaults)", path.display())
+            };
+            if let Some(project) = &config.project_file {
+                message.push_str(&format!(", project {}", project.display()));
+            }
+            if let Some(context) = &config.context {
+                message.push_str(&format!(", context {}", context));
+            }
+            pass("config", message)
+        }
+        Err(err) => fail(
+            "config",
+            err.to_string(),
+            "fix the file named above; `browser config show --origin` shows where each setting comes from".to_string(),
+        ),
+    }
+}
+
+/// The socket, if there is one, belongs to the user and only they can write to it
+#[cfg(unix)]
+fn check_socket(config: &Config) -> DoctorCheck {
+    let socket = &config.ipc_socket_path;
+    if !socket.exists() {
+        return pass(
+            "socket",
+            format!("{} (not created; no daemon running)", socket.display()),
+        );
+    }
+    match crate::ipc::verify_socket(socket) {
+        Ok(()) => pass(
+            "socket",
+            format!("{} (owned by you, mode 0600)", socket.display()),
+        ),
+        Err(err) => fail(
+            "socket",
+            err.to_string(),
+            format!(
+                "remove {} if no daemon of yours is using it, or set socket_path to a private directory",
+                socket.display()
+            ),
+        ),
+    }
+}
+
+/// Named pipes carry no file permissions to check
+#[cfg(windows)]
+fn check_socket(config: &Config) -> DoctorCheck {
+    pass("socket", config.ipc_socket_path.display().to_string())
+}
+
+/// A daemon answers on the socket and speaks this CLI's protocol
+fn check_daemon(config: &Config, pong: &Result<Option<PongData>>) -> DoctorCheck {
+    match pong {
+        Ok(Some(pong)) if protocol_compatible(&pong.protocol_version) => pass(
+            "daemon",
+            format!(
+                "running, version {} (protocol {})",
+                pong.version, pong.protocol_version
+            ),
+        ),
+        Ok(Some(pong)) => fail(
+            "daemon",
+            format!(
+                "running version {} speaks protocol {}, this CLI speaks {}",
+                pong.version,
+                pong.protocol_version,
+                crate::types::PROTOCOL_VERSION
+            ),
+            "run `browser daemonctl restart` to start a daemon matching the CLI".to_string(),
+        ),
+        Ok(None) => warn(
+            "daemon",
+            "running, but too old to report its version".to_string(),
+            "run `browser daemonctl restart` to start a daemon matching the CLI".to_string(),
+        ),
+        Err(CliError::DaemonNotRunning(_)) if !config.autostart => warn(
+            "daemon",
+            "not running, and autostart is off".to_string(),
+            "start its service, e.g. `systemctl --user start browser-daemon.socket`, or run `browser daemonctl restart`"
+                .to_string(),
+        ),
+        Err(CliError::DaemonNotRunning(_)) => warn(
+            "daemon",
+            "not running".to_string(),
+            "it starts with the next command; if that fails, see `browser daemonctl logs`"
+                .to_string(),
+        ),
+        Err(err) => fail(
+            "daemon",
+            err.to_string(),
+            "run `browser daemonctl restart`, then `browser daemonctl logs` if it still fails"
+                .to_string(),
+        ),
+    }
+}
+
+/// The daemon binary is configured, on the PATH or installed and executable
+fn check_plugin(config: &Config) -> DoctorCheck {
+    if let Some(path) = &config.plugins.daemon.path {
+        return check_binary(path, "plugins.daemon.path");
+    }
+    if is_plugin_available(DAEMON_PLUGIN) {
+        return pass("plugin", format!("{} found on the PATH", DAEMON_PLUGIN));
+    }
+
+    let plugins_dir = match get_plugins_dir() {
+        Ok(dir) => dir,
+        Err(err) => {
+            return fail(
+                "plugin",
+                err,
+                "set HOME, or set plugins.daemon.path to a daemon binary".to_string(),
+            );
+        }
+    };
+    let binary = plugins_dir.join(if cfg!(windows) {
+        format!("{}.exe", DAEMON_PLUGIN)
+    } else {
+        DAEMON_PLUGIN.to_string()
+    });
+    if binary.exists() {
+        return check_binary(&binary, "the plugins directory");
+    }
+    warn(
+        "plugin",
+        format!(
+            "{} is not installed in {}",
+            DAEMON_PLUGIN,
+            plugins_dir.display()
+        ),
+        "it is downloaded when the daemon first starts; offline, set plugins.daemon.path"
+            .to_string(),
+    )
+}
+
+fn check_binary(path: &Path, source: &str) -> DoctorCheck {
+    if !path.exists() {
+        return fail(
+            "plugin",
+            format!("{} from {} does not exist", path.display(), source),
+            "fix plugins.daemon.path, or unset it to download the daemon".to_string(),
+        );
+    }
+    if !is_executable(path) {
+        return fail(
+            "plugin",
+            format!("{} from {} is not executable", path.display(), source),
+            format!("run `chmod +x {}`", path.display()),
+        );
+    }
+    pass("plugin", format!("{} (from {})", path.display(), source))
+}
+
+/// A daemon release exists for this OS and architecture
+fn check_platform(config: &Config) -> DoctorCheck {
+    let (platform, arch) = match get_platform_suffix() {
+        Ok(suffix) => suffix,
+        Err(err) => {
+            return fail(
+                "platform",
+                err,
+                "build the daemon from source and set plugins.daemon.path".to_string(),
+            );
+        }
+    };
+
+    let target = format!("{}-{}", platform, arch);
+    let targets = get_daemon_config(&config.plugins.daemon).targets;
+    if targets.contains(&target) || config.plugins.daemon.path.is_some() {
+        pass("platform", target)
+    } else {
+        warn(
+            "platform",
+            format!("{} has no daemon release", target),
+            "build the daemon from source and set plugins.daemon.path".to_string(),
+        )
+    }
+}
+
+/// One check per connected extension, or one saying none is connected
+fn check_extensions(ctx: &CommandContext, pong: &Result<Option<PongData>>) -> Vec<DoctorCheck> {
+    let Ok(Some(pong)) = pong else {
+        return Vec::new();
+    };
+    if pong.extensions.is_empty() {
+        return vec![warn(
+            "extension",
+            "no browser window is connected".to_string(),
+            format!(
+                "the next command to session {} launches a browser with the extension",
+                ctx.session_id
+            ),
+        )];
+    }
+
+    pong.extensions
+        .iter()
+        .map(|extension| {
+            let version = extension.version.as_deref().unwrap_or("unknown");
+            match &extension.protocol_version {
+                Some(protocol) if protocol_compatible(protocol) => pass(
+                    "extension",
+                    format!(
+                        "session {}: connected, version {} (protocol {})",
+                        extension.session, version, protocol
+                    ),
+                ),
+                Some(protocol) => fail(
+                    "extension",
+                    format!(
+                        "session {}: version {} speaks protocol {}, this CLI speaks {}",
+                        extension.session,
+                        version,
+                        protocol,
+                        crate::types::PROTOCOL_VERSION
+                    ),
+                    "update the browser extension".to_string(),
+                ),
+                None => warn(
+                    "extension",
+                    format!(
+                        "session {}: connected, but too old to report its version",
+                        extension.session
+                    ),
+                    "update the browser extension".to_string(),
+                ),
+            }
+        })
+        .collect()
+}
+
+/// A file can be created in `dir`
+fn check_writable(
+    name: &str,
+    dir: std::result::Result<std::path::PathBuf, String>,
+    env_var: &str,
+) -> DoctorCheck {
+    let remedy = |dir: &str| {
+        format!(
+            "make {} writable, or set {} to another directory",
+            dir, env_var
+        )
+    };
+    let dir = match dir {
+        Ok(dir) => dir,
+        Err(err) => return fail(name, err, remedy("it")),
+    };
+
+    let probe = dir.join(format!(".doctor-{}", std::process::id()));
+    let written = std::fs::create_dir_all(&dir).and_then(|()| std::fs::write(&probe, b""));
+    let _ = std::fs::remove_file(&probe);
+    match written {
+        Ok(()) => pass(name, format!("{} is writable", dir.display())),
+        Err(err) => fail(
+            name,
+            format!("cannot write to {}: {}", dir.display(), err),
+            remedy(&dir.display().to_string()),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failed_checks_counts_only_failures() {
+        let checks = vec![
+            pass("config", "ok".to_string()),
+            warn("daemon", "not running".to_string(), "start it".to_string()),
+            fail("plugin", "missing".to_string(), "install it".to_string()),
+        ];
+        let response = CommandResponse {
+            id: "cmd-1".to_string(),
+            success: true,
+            data: Some(serde_json::to_value(DoctorData { checks }).unwrap()),
+            error: None,
+        };
+        assert_eq!(failed_checks(&response), 1);
+
+        let response = CommandResponse {
+            data: None,
+            ..response
+        };
+        assert_eq!(failed_checks(&response), 0);
+    }
+
+    #[test]
+    fn check_binary_reports_missing_and_non_executable_files() {
+        let dir = std::env::temp_dir().join(format!("browser-doctor-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let missing = check_binary(&dir.join("missing"), "plugins.daemon.path");
+        assert_eq!(missing.status, CheckStatus::Fail);
+        assert!(missing.message.contains("does not exist"));
+
+        let binary = dir.join("browser-daemon");
+        std::fs::write(&binary, "#!/bin/sh\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let check = check_binary(&binary, "plugins.daemon.path");
+            assert_eq!(check.status, CheckStatus::Fail);
+            assert!(check.remedy.unwrap().contains("chmod +x"));
+
+            std::fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o755)).unwrap();
+            let check = check_binary(&binary, "plugins.daemon.path");
+            assert_eq!(check.status, CheckStatus::Pass);
+        }
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn check_daemon_distinguishes_missing_old_and_mismatched_daemons() {
+        let config = Config::default();
+        let not_running = check_daemon(
+            &config,
+            &Err(CliError::DaemonNotRunning("gone".to_string())),
+        );
+        assert_eq!(not_running.status, CheckStatus::Warn);
+        assert!(not_running.remedy.unwrap().contains("next command"));
+
+        let manual = Config {
+            autostart: false,
+            ..Default::default()
+        };
+        let not_running = check_daemon(
+            &manual,
+            &Err(CliError::DaemonNotRunning("gone".to_string())),
+        );
+        assert!(not_running.message.contains("autostart is off"));
+
+        assert_eq!(check_daemon(&config, &Ok(None)).status, CheckStatus::Warn);
+
+        let pong = |protocol: &str| PongData {
+            protocol_version: protocol.to_string(),
+            version: "0.2.4".to_string(),
+            extensions: Vec::new(),
+        };
+        let current = crate::types::PROTOCOL_VERSION;
+        assert_eq!(
+            check_daemon(&config, &Ok(Some(pong(current)))).status,
+            CheckStatus::Pass
+        );
+        assert_eq!(
+            check_daemon(&config, &Ok(Some(pong("99.0")))).status,
+            CheckStatus::Fail
+        );
+
+        let timeout = check_daemon(&config, &Err(CliError::ConnectionTimeout));
+        assert_eq!(timeout.status, CheckStatus::Fail);
+    }
+}
@@ -0,0 +1,70 @@
+//! Snapshot after an action
+//!
+//! Runs the `--then-snapshot`/`--then-diff` follow-up for a mutating command:
+//! waits for the page to settle, takes a snapshot and folds it into the
+//! action's response as `{action, snapshot, diff}`.
+
+use crate::commands::{CommandContext, Execute, SnapshotCommand};
+use crate::error::Result;
+use crate::snapshot::{cache, diff};
+use crate::types::{ActionSnapshotData, CommandResponse, SnapshotData};
+use std::thread;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FollowUpSnapshot {
+    /// Include a diff against the session's previous snapshot
+    pub diff: bool,
+    /// Time to wait after the action before snapshotting, in milliseconds
+    pub settle_ms: u64,
+}
+
+impl FollowUpSnapshot {
+    pub fn new(diff: bool, settle_ms: u64) -> Self {
+        Self { diff, settle_ms }
+    }
+
+    /// Snapshot after a successful action and combine both responses
+    ///
+    /// Failed actions are returned unchanged. If the snapshot itself fails,
+    /// its response is returned so the error is reported.
+    pub fn apply(&self, ctx: &CommandContext, action: CommandResponse) -> Result<CommandResponse> {
+        if !action.success {
+            return Ok(action);
+        }
+
+        if self.settle_ms > 0 {
+            thread::sleep(Duration::from_millis(self.settle_ms));
+        }
+
+        // Read the previous snapshot before the new one replaces it in the cache
+        let previous = self.diff.then(|| cache::load(&ctx.session_id)).flatten();
+
+        let response = SnapshotCommand::default().execute(ctx)?;
+        if !response.success {
+            return Ok(response);
+        }
+        let Some(data) = &response.data else {
+            return Ok(response);
+        };
+        let snapshot: SnapshotData = serde_json::from_value(data.clone())?;
+
+        let diff = self.diff.then(|| {
+            let old = previous.as_ref().map_or("", |data| data.snapshot.as_str());
+            diff::diff(old, &snapshot.snapshot)
+        });
+
+        let combined = ActionSnapshotData {
+            action: action.data.unwrap_or(serde_json::Value::Null),
+            snapshot,
+            diff,
+        };
+
+        Ok(CommandResponse {
+            id: action.id,
+            success: true,
+            data: Some(serde_json::to_value(combined)?),
+            error: None,
+        })
+    }
+}
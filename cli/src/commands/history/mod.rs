@@ -1,2 +1,4 @@
 pub mod back;
 pub mod forward;
+pub mod search;
+pub mod show;
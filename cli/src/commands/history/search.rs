@@ -0,0 +1,44 @@
+use crate::commands::{CommandContext, Execute};
+use crate::error::{CliError, Result};
+use crate::snapshot::archive::Archive;
+use crate::types::{ArchiveSearchData, CommandResponse};
+
+#[derive(Debug, Clone)]
+pub struct HistorySearchCommand {
+    pub query: String,
+    pub session: Option<String>,
+    pub limit: usize,
+}
+
+impl HistorySearchCommand {
+    pub fn new(query: String, session: Option<String>, limit: usize) -> Self {
+        Self {
+            query,
+            session,
+            limit,
+        }
+    }
+}
+
+impl Execute for HistorySearchCommand {
+    fn execute(&self, ctx: &CommandContext) -> Result<CommandResponse> {
+        if self.query.trim().is_empty() {
+            return Err(CliError::InvalidArguments(
+                "Search query cannot be empty".to_string(),
+            ));
+        }
+
+        let matches = Archive::open()?.search(&self.query, self.session.as_deref(), self.limit)?;
+        let data = ArchiveSearchData {
+            query: self.query.clone(),
+            matches,
+        };
+
+        Ok(CommandResponse {
+            id: ctx.command_id(),
+            success: true,
+            data: Some(serde_json::to_value(data)?),
+            error: None,
+        })
+    }
+}
@@ -0,0 +1,30 @@
+use crate::commands::{CommandContext, Execute};
+use crate::error::{CliError, Result};
+use crate::snapshot::archive::Archive;
+use crate::types::CommandResponse;
+
+#[derive(Debug, Clone)]
+pub struct HistoryShowCommand {
+    pub id: u64,
+}
+
+impl HistoryShowCommand {
+    pub fn new(id: u64) -> Self {
+        Self { id }
+    }
+}
+
+impl Execute for HistoryShowCommand {
+    fn execute(&self, ctx: &CommandContext) -> Result<CommandResponse> {
+        let entry = Archive::open()?.get(self.id)?.ok_or_else(|| {
+            CliError::InvalidArguments(format!("no archived snapshot with ID {}", self.id))
+        })?;
+
+        Ok(CommandResponse {
+            id: ctx.command_id(),
+            success: true,
+            data: Some(serde_json::to_value(entry)?),
+            error: None,
+        })
+    }
+}
@@ -5,7 +5,13 @@
 //! - Sending via IPC client
 //! - Formatting the response
 
+pub mod alias;
 pub mod click;
+pub mod config;
+pub mod context;
+pub mod daemonctl;
+pub mod doctor;
+pub mod follow_up;
 pub mod history;
 pub mod navigate;
 pub mod plugins;
@@ -14,10 +20,32 @@ pub mod snapshot;
 pub mod tab;
 pub mod type_cmd;
 pub mod utils;
+pub mod version;
 
+pub use alias::list::AliasListCommand;
+pub use alias::rm::AliasRmCommand;
+pub use alias::set::AliasSetCommand;
 pub use click::ClickCommand;
+pub use config::get::ConfigGetCommand;
+pub use config::list::ConfigListCommand;
+pub use config::path::ConfigPathCommand;
+pub use config::set::ConfigSetCommand;
+pub use config::show::ConfigShowCommand;
+pub use context::create::ContextCreateCommand;
+pub use context::list::ContextListCommand;
+pub use context::rm::ContextRmCommand;
+pub use context::use_cmd::ContextUseCommand;
+pub use daemonctl::install_service::InstallServiceCommand;
+pub use daemonctl::logs::DaemonLogsCommand;
+pub use daemonctl::restart::DaemonRestartCommand;
+pub use daemonctl::status::DaemonStatusCommand;
+pub use daemonctl::stop::DaemonStopCommand;
+pub use doctor::DoctorCommand;
+pub use follow_up::FollowUpSnapshot;
 pub use history::back::BackCommand;
 pub use history::forward::ForwardCommand;
+pub use history::search::HistorySearchCommand;
+pub use history::show::HistoryShowCommand;
 pub use navigate::NavigateCommand;
 pub use scroll::ScrollCommand;
 pub use snapshot::SnapshotCommand;
@@ -26,11 +54,18 @@ pub use tab::list::TabListCommand;
 pub use tab::new::TabNewCommand;
 pub use tab::switch::TabSwitchCommand;
 pub use type_cmd::TypeCommand;
+pub use version::VersionCommand;
 
-use crate::commands::utils::current_timestamp;
-use crate::error::Result;
+use crate::aliases::{self, ALIAS_PREFIX};
+use crate::commands::utils::{current_timestamp, timestamp_after};
+use crate::error::{CliError, Result};
 use crate::ipc::IpcClient;
-use crate::types::{Command, CommandResponse, CommandType};
+use crate::locator::{self, Locator};
+use crate::snapshot::{Snapshot, cache};
+use crate::types::{Command, CommandResponse, CommandType, TabListData};
+use serde_json::json;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
 use uuid::Uuid;
 
 pub trait Execute {
@@ -41,6 +76,10 @@ pub struct CommandContext {
     pub client: IpcClient,
     pub session_id: String,
     pub profile: Option<String>,
+    /// Caller-supplied ID that command IDs are derived from instead of a random one
+    pub request_id: Option<String>,
+    /// Command IDs handed out so far, numbering those derived from `request_id`
+    issued: AtomicUsize,
 }
 
 impl CommandContext {
@@ -49,6 +88,29 @@ impl CommandContext {
             client,
             session_id,
             profile,
+            request_id: None,
+            issued: AtomicUsize::new(0),
+        }
+    }
+
+    /// Derive command IDs from this ID, so daemon logs can be correlated
+    /// with the caller's own traces
+    pub fn with_request_id(mut self, request_id: Option<String>) -> Self {
+        self.request_id = request_id;
+        self
+    }
+
+    /// ID for the next command, unique within the invocation
+    ///
+    /// With a request ID the first command gets the ID itself and later ones
+    /// `<ID>.1`, `<ID>.2` and so on; otherwise each gets a new UUID.
+    pub fn command_id(&self) -> String {
+        let Some(request_id) = &self.request_id else {
+            return Uuid::new_v4().to_string();
+        };
+        match self.issued.fetch_add(1, Ordering::Relaxed) {
+            0 => request_id.clone(),
+            n => format!("{}.{}", request_id, n),
         }
     }
 
@@ -63,14 +125,133 @@ impl CommandContext {
         };
 
         let command = Command {
-            id: Uuid::new_v4().to_string(),
+            id: self.command_id(),
             session_id: self.session_id.clone(),
             profile: self.profile.clone(),
             command_type,
             params,
             timestamp: current_timestamp(),
+            deadline: Some(timestamp_after(Duration::from_millis(
+                self.client.config().command_timeout_ms,
+            ))),
         };
 
         self.client.send_command(command)
     }
+
+    /// Resolve a locator to an element ref
+    ///
+    /// `@name` aliases are expanded first. Raw refs pass through unchanged.
+    /// Other locators are matched against the session's last snapshot while
+    /// the active tab still shows the page it was taken of, then handed to
+    /// the extension's `find` command when it can evaluate them.
+    pub fn resolve_ref(&self, input: &str) -> Result<String> {
+        let cached = cache::load(&self.session_id);
+
+        let expanded;
+        let input = if input.starts_with(ALIAS_PREFIX) {
+            let url = cached.as_ref().map(|data| data.url.as_str());
+            expanded =
+                aliases::resolve(&self.session_id, input, url, &self.client.config().aliases)?;
+            expanded.as_str()
+        } else {
+            input
+        };
+
+        let locator = locator::parse(input)?;
+        if let Locator::Ref(r#ref) = &locator {
+            return Ok(r#ref.clone());
+        }
+
+        let mut stale = None;
+        if let Some(data) = &cached
+            && let Some(r#ref) = locator.resolve_in_snapshot(&Snapshot::parse(data))
+        {
+            // Refs from a snapshot of another page would point at the wrong element
+            match self.current_url() {
+                Some(url) if same_page(&url, &data.url) => return Ok(r#ref),
+                url => stale = Some(url),
+            }
+        }
+
+        let Some(params) = locator.find_params() else {
+            return Err(CliError::CommandFailed(match stale {
+                Some(url) => format!(
+                    "the last snapshot is of {} but the page is now {}; run `browser snapshot` to refresh it before using locator: {}",
+                    cached.as_ref().map_or("", |data| data.url.as_str()),
+                    url.as_deref().unwrap_or("unknown"),
+                    input
+                ),
+                None => format!(
+                    "no element in the last snapshot matches locator: {} (run `browser snapshot` to refresh it)",
+                    input
+                ),
+            }));
+        };
+
+        let response = self.execute(CommandType::Find, params)?;
+        if !response.success {
+            return Err(CliError::CommandFailed(
+                response
+                    .error
+                    .unwrap_or_else(|| "Unknown error".to_string()),
+            ));
+        }
+
+        response
+            .data
+            .as_ref()
+            .and_then(|data| data["result"][0]["ref"].as_str())
+            .map(str::to_string)
+            .ok_or_else(|| {
+                CliError::CommandFailed(format!("no element matches locator: {}", input))
+            })
+    }
+
+    /// URL of the session's active tab, if the daemon reports one
+    fn current_url(&self) -> Option<String> {
+        let response = self.execute(CommandType::TabList, json!({})).ok()?;
+        let tabs: TabListData = serde_json::from_value(response.data?).ok()?;
+        tabs.tabs
+            .into_iter()
+            .find(|tab| tab.id == tabs.active_tab_id)
+            .map(|tab| tab.url)
+    }
+}
+
+/// Whether two URLs name the same page, ignoring a trailing slash
+fn same_page(a: &str, b: &str) -> bool {
+    a.trim_end_matches('/') == b.trim_end_matches('/')
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn context(request_id: Option<&str>) -> CommandContext {
+        CommandContext::new(
+            IpcClient::new(Config::default()),
+            "default".to_string(),
+            None,
+        )
+        .with_request_id(request_id.map(str::to_string))
+    }
+
+    #[test]
+    fn command_ids_derived_from_request_id_are_unique() {
+        let ctx = context(Some("trace-7f3a"));
+        let ids: Vec<_> = (0..3).map(|_| ctx.command_id()).collect();
+        assert_eq!(ids, vec!["trace-7f3a", "trace-7f3a.1", "trace-7f3a.2"]);
+    }
+
+    #[test]
+    fn command_ids_without_request_id_are_unique() {
+        let ctx = context(None);
+        assert_ne!(ctx.command_id(), ctx.command_id());
+    }
 }
@@ -1,4 +1,4 @@
-use crate::commands::utils::{normalize_url, validate_url};
+use crate::commands::utils::validate_url;
 use crate::commands::{CommandContext, Execute};
 use crate::error::Result;
 use crate::types::{CommandResponse, CommandType};
@@ -12,7 +12,7 @@ pub struct NavigateCommand {
 impl NavigateCommand {
     pub fn new(url: String) -> Self {
         Self {
-            url: normalize_url(&url),
+            url: url.trim().to_string(),
         }
     }
 }
@@ -20,7 +20,9 @@ impl NavigateCommand {
 impl Execute for NavigateCommand {
     fn execute(&self, ctx: &CommandContext) -> Result<CommandResponse> {
         validate_url(&self.url)?;
-        let payload_json = serde_json::to_value(self)?;
+        // Relative paths need the project's base_url, so resolve at send time
+        let url = ctx.client.config().resolve_url(&self.url)?;
+        let payload_json = serde_json::to_value(Self { url })?;
         ctx.execute(CommandType::Navigate, payload_json)
     }
 }
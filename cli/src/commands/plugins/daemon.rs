@@ -1,7 +1,11 @@
-use crate::utils::plugins::{PluginConfig, execute_plugin_command, get_plugin_path};
+use crate::config::PluginSettings;
+#[cfg(not(unix))]
+use crate::utils::plugins::execute_plugin_command;
+use crate::utils::plugins::{PluginConfig, get_plugin_path};
 use std::process::Command;
 
-fn get_daemon_config() -> PluginConfig {
+/// Where the daemon plugin is downloaded from, and the targets it is built for
+pub fn get_daemon_config(settings: &PluginSettings) -> PluginConfig {
     PluginConfig {
         name: "browser-daemon".to_string(),
         base_url: "https://github.com/stakpak/tab".to_string(),
@@ -11,18 +15,30 @@ fn get_daemon_config() -> PluginConfig {
             "darwin-aarch64".to_string(),
             "windows-x86_64".to_string(),
         ],
-        version: None,
+        version: settings.version.clone(),
         repo: Some("tab".to_string()),
         owner: Some("stakpak".to_string()),
         version_arg: Some("--version".to_string()),
     }
 }
 
-pub async fn run_daemon(args: Vec<String>) -> Result<(), String> {
-    let plugin_config = get_daemon_config();
-    let plugin_path = get_plugin_path(plugin_config).await;
+pub async fn run_daemon(args: Vec<String>, settings: &PluginSettings) -> Result<(), String> {
+    let plugin_path = match &settings.path {
+        Some(path) => path.to_string_lossy().into_owned(),
+        None => get_plugin_path(get_daemon_config(settings)).await,
+    };
 
     let mut cmd = Command::new(&plugin_path);
     cmd.args(&args);
+
+    // Become the daemon, so the PID the CLI records is the one to signal
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        let err = cmd.exec();
+        Err(format!("Failed to execute daemon command: {}", err))
+    }
+
+    #[cfg(not(unix))]
     execute_plugin_command(cmd, "daemon".to_string())
 }
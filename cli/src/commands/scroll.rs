@@ -26,7 +26,12 @@ impl ScrollCommand {
 
 impl Execute for ScrollCommand {
     fn execute(&self, ctx: &CommandContext) -> Result<CommandResponse> {
-        let payload_json = serde_json::to_value(self)?;
+        let r#ref = match &self.r#ref {
+            Some(r#ref) => Some(ctx.resolve_ref(r#ref)?),
+            None => None,
+        };
+        let resolved = Self::new(self.direction.clone(), r#ref, self.amount);
+        let payload_json = serde_json::to_value(resolved)?;
         ctx.execute(CommandType::Scroll, payload_json)
     }
 }
@@ -1,11 +1,14 @@
 //! Snapshot command implementation
 //!
 //! Takes a snapshot of the current page DOM.
-//! Returns HTML and element refs for subsequent commands.
+//! Returns HTML and element refs for subsequent commands, and caches the
+//! result so later locators can be resolved against it. When the archive is
+//! enabled the result is also appended to it.
 
 use crate::commands::{CommandContext, Execute};
 use crate::error::Result;
-use crate::types::{CommandResponse, CommandType};
+use crate::snapshot::{archive, cache};
+use crate::types::{CommandResponse, CommandType, SnapshotData};
 use serde_json::json;
 
 #[derive(Default)]
@@ -13,6 +16,22 @@ pub struct SnapshotCommand {}
 
 impl Execute for SnapshotCommand {
     fn execute(&self, ctx: &CommandContext) -> Result<CommandResponse> {
-        ctx.execute(CommandType::Snapshot, json!({}))
+        let response = ctx.execute(CommandType::Snapshot, json!({}))?;
+
+        if response.success
+            && let Some(data) = &response.data
+            && let Ok(snapshot) = serde_json::from_value::<SnapshotData>(data.clone())
+        {
+            // The cache only speeds up locator resolution; a failed write is not fatal
+            let _ = cache::store(&ctx.session_id, &snapshot);
+
+            if let Err(e) =
+                archive::record(&ctx.session_id, &snapshot, &ctx.client.config().archive)
+            {
+                eprintln!("Warning: failed to archive snapshot: {}", e);
+            }
+        }
+
+        Ok(response)
     }
 }
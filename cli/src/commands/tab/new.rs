@@ -1,4 +1,3 @@
-use crate::commands::utils::normalize_url;
 use crate::commands::{CommandContext, Execute};
 use crate::error::Result;
 use crate::types::{CommandResponse, CommandType};
@@ -12,14 +11,19 @@ pub struct TabNewCommand {
 impl TabNewCommand {
     pub fn new(url: Option<String>) -> Self {
         Self {
-            url: url.as_deref().map(normalize_url),
+            url: url.map(|url| url.trim().to_string()),
         }
     }
 }
 
 impl Execute for TabNewCommand {
     fn execute(&self, ctx: &CommandContext) -> Result<CommandResponse> {
-        let payload_json = serde_json::to_value(self)?;
+        let url = self
+            .url
+            .as_deref()
+            .map(|url| ctx.client.config().resolve_url(url))
+            .transpose()?;
+        let payload_json = serde_json::to_value(Self { url })?;
         ctx.execute(CommandType::TabNew, payload_json)
     }
 }
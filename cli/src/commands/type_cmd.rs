@@ -1,5 +1,5 @@
-// Types text into an element identified by a ref.
-//! Ref must be valid (from the current snapshot).
+// Types text into an element identified by a ref or locator.
+//! Locators are resolved to a ref before the command is sent.
 //!
 //! Note: Module named type_cmd.rs because "type" is a Rust keyword.
 
@@ -24,7 +24,8 @@ impl TypeCommand {
 impl Execute for TypeCommand {
     fn execute(&self, ctx: &CommandContext) -> Result<CommandResponse> {
         validate_ref(&self.r#ref)?;
-        let payload_json = serde_json::to_value(self)?;
+        let resolved = Self::new(ctx.resolve_ref(&self.r#ref)?, self.text.clone());
+        let payload_json = serde_json::to_value(resolved)?;
         ctx.execute(CommandType::Type, payload_json)
     }
 }
@@ -1,5 +1,6 @@
 use crate::error::CliError;
 use crate::error::Result;
+use std::time::Duration;
 use time::OffsetDateTime;
 use time::format_description::well_known::Rfc3339;
 
@@ -48,3 +49,10 @@ pub fn current_timestamp() -> String {
         .format(&Rfc3339)
         .expect("format timestamp")
 }
+
+/// Timestamp `duration` from now, in the same format as `current_timestamp`
+pub fn timestamp_after(duration: Duration) -> String {
+    (OffsetDateTime::now_utc() + duration)
+        .format(&Rfc3339)
+        .expect("format timestamp")
+}
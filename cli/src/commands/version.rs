@@ -0,0 +1,40 @@
+use crate::commands::{CommandContext, Execute};
+use crate::error::{CliError, Result};
+use crate::types::{CommandResponse, ComponentVersion, PROTOCOL_VERSION, VersionData};
+
+/// `version --all`: the CLI's version, and those the running daemon reports
+/// for itself and its connected extensions
+#[derive(Debug, Clone, Default)]
+pub struct VersionCommand;
+
+impl Execute for VersionCommand {
+    fn execute(&self, ctx: &CommandContext) -> Result<CommandResponse> {
+        let pong = match ctx.client.versions() {
+            Ok(pong) => Some(pong),
+            Err(CliError::DaemonNotRunning(_)) => None,
+            Err(err) => return Err(err),
+        };
+
+        let data = VersionData {
+            cli: ComponentVersion {
+                version: Some(env!("CARGO_PKG_VERSION").to_string()),
+                protocol_version: Some(PROTOCOL_VERSION.to_string()),
+            },
+            daemon: pong.as_ref().map(|pong| ComponentVersion {
+                version: pong.as_ref().map(|pong| pong.version.clone()),
+                protocol_version: pong.as_ref().map(|pong| pong.protocol_version.clone()),
+            }),
+            extensions: pong
+                .flatten()
+                .map(|pong| pong.extensions)
+                .unwrap_or_default(),
+        };
+
+        Ok(CommandResponse {
+            id: ctx.command_id(),
+            success: true,
+            data: Some(serde_json::to_value(data)?),
+            error: None,
+        })
+    }
+}
@@ -0,0 +1,268 @@
+//! Named contexts in `config.toml`
+//!
+//! A context bundles the settings that pick which browser to talk to, so
+//! switching between them is one flag instead of three:
+//!
+//! ```toml
+//! context = "staging"
+//!
+//! [contexts.staging]
+//! session = "stg"
+//! profile = "/home/me/profiles/stg"
+//! socket_path = "/run/user/1000/browser-stg.sock"
+//! ```
+//!
+//! The active context is chosen by `--context`, then `BROWSER_CONTEXT`, then
+//! the `context` key. Its settings override the config and project files and
+//! are overridden by environment variables and flags.
+
+use super::file::edit;
+use super::{Config, Origin};
+use crate::error::{CliError, Result};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Settings bundled under a context name
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Context {
+    pub session: Option<String>,
+    pub profile: Option<String>,
+    pub socket_path: Option<PathBuf>,
+}
+
+impl Context {
+    /// Override `config` with the settings this context has
+    fn apply(&self, config: &mut Config, name: &str) {
+        let origin = Origin::Context(name.to_string());
+
+        if let Some(session) = &self.session {
+            config.default_session = session.clone();
+            config.set_origin("session", origin.clone());
+        }
+        if let Some(profile) = &self.profile {
+            config.profile = Some(profile.clone());
+            config.set_origin("profile", origin.clone());
+        }
+        if let Some(socket_path) = &self.socket_path {
+            config.ipc_socket_path = socket_path.clone();
+            config.set_origin("socket_path", origin.clone());
+        }
+    }
+}
+
+/// Context names are bare TOML keys
+pub fn validate_context_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(CliError::InvalidArguments(format!(
+            "invalid context name '{}': use letters, digits, '-' and '_'",
+            name
+        )))
+    }
+}
+
+impl Config {
+    /// Apply the context named `name`, chosen by `origin`
+    ///
+    /// A name from a flag or environment variable that does not exist is an
+    /// argument error; one from the config file is a config error.
+    pub(super) fn use_context(&mut self, name: &str, origin: &Origin) -> Result<()> {
+        let Some(context) = self.contexts.get(name).cloned() else {
+            let names: Vec<&str> = self.contexts.keys().map(String::as_str).collect();
+            let message = format!(
+                "unknown context '{}' from {} (contexts: {})",
+                name,
+                origin,
+                if names.is_empty() {
+                    "none".to_string()
+                } else {
+                    names.join(", ")
+                }
+            );
+            return Err(match origin {
+                Origin::File(_) => CliError::ConfigError(message),
+                _ => CliError::InvalidArguments(message),
+            });
+        };
+
+        context.apply(self, name);
+        self.context = Some(name.to_string());
+        Ok(())
+    }
+}
+
+/// Add a context to the config file at `path`
+pub fn create_context(path: &Path, name: &str, context: &Context) -> Result<()> {
+    validate_context_name(name)?;
+    edit(path, |document| {
+        let contexts = document
+            .entry("contexts")
+            .or_insert_with(|| {
+                // Implicit, so only `[contexts.NAME]` headers are written
+                let mut table = toml_edit::Table::new();
+                table.set_implicit(true);
+                toml_edit::Item::Table(table)
+            })
+            .as_table_like_mut()
+            .ok_or_else(|| CliError::ConfigError("`contexts` is not a table".to_string()))?;
+        if contexts.contains_key(name) {
+            return Err(CliError::InvalidArguments(format!(
+                "context '{}' already exists",
+                name
+            )));
+        }
+
+        let mut table = toml_edit::Table::new();
+        if let Some(session) = &context.session {
+            table.insert("session", toml_edit::value(session.as_str()));
+        }
+        if let Some(profile) = &context.profile {
+            table.insert("profile", toml_edit::value(profile.as_str()));
+        }
+        if let Some(socket_path) = &context.socket_path {
+            table.insert(
+                "socket_path",
+                toml_edit::value(socket_path.to_string_lossy().as_ref()),
+            );
+        }
+        contexts.insert(name, toml_edit::Item::Table(table));
+        Ok(())
+    })
+}
+
+/// Make `name` the active context in the config file at `path`
+pub fn set_current_context(path: &Path, name: &str, config: &Config) -> Result<()> {
+    if !config.contexts.contains_key(name) {
+        return Err(CliError::InvalidArguments(format!(
+            "unknown context '{}'",
+            name
+        )));
+    }
+    edit(path, |document| {
+        document.insert("context", toml_edit::value(name));
+        Ok(())
+    })
+}
+
+/// Remove a context from the config file at `path`, and unset it if active
+pub fn remove_context(path: &Path, name: &str) -> Result<()> {
+    edit(path, |document| {
+        let removed = document
+            .get_mut("contexts")
+            .and_then(toml_edit::Item::as_table_like_mut)
+            .and_then(|contexts| contexts.remove(name));
+        if removed.is_none() {
+            return Err(CliError::InvalidArguments(format!(
+                "unknown context '{}'",
+                name
+            )));
+        }
+        if document.get("context").and_then(toml_edit::Item::as_str) == Some(name) {
+            document.remove("context");
+        }
+        Ok(())
+    })
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{CONFIG_FILE, ConfigFile};
+    use std::fs;
+
+    fn temp_file(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "browser-context-test-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir.join(CONFIG_FILE)
+    }
+
+    fn load(path: &Path) -> Config {
+        let mut config = Config::default();
+        ConfigFile::load(path).unwrap().apply(&mut config, path);
+        config
+    }
+
+    #[test]
+    fn create_use_and_remove_round_trip() {
+        let path = temp_file("round-trip");
+        let staging = Context {
+            session: Some("stg".to_string()),
+            socket_path: Some(PathBuf::from("/run/user/1000/browser-stg.sock")),
+            ..Default::default()
+        };
+        create_context(&path, "staging", &staging).expect("create");
+        set_current_context(&path, "staging", &load(&path)).expect("use");
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "context = \"staging\"\n\n[contexts.staging]\nsession = \"stg\"\nsocket_path = \"/run/user/1000/browser-stg.sock\"\n"
+        );
+        assert_eq!(load(&path).contexts.get("staging"), Some(&staging));
+
+        let err = create_context(&path, "staging", &Context::default()).unwrap_err();
+        assert!(err.to_string().contains("already exists"));
+
+        remove_context(&path, "staging").expect("remove");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "");
+        assert!(load(&path).contexts.is_empty());
+
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn use_context_overrides_with_context_origin() {
+        let mut config = Config::default();
+        config.contexts.insert(
+            "staging".to_string(),
+            Context {
+                session: Some("stg".to_string()),
+                profile: Some("/profiles/stg".to_string()),
+                socket_path: None,
+            },
+        );
+
+        config
+            .use_context("staging", &Origin::Flag("--context"))
+            .unwrap();
+        assert_eq!(config.default_session, "stg");
+        assert_eq!(config.profile.as_deref(), Some("/profiles/stg"));
+        assert_eq!(
+            config.origin("session"),
+            Origin::Context("staging".to_string())
+        );
+        assert_eq!(config.origin("socket_path"), Origin::Default);
+
+        let err = config
+            .use_context("prod", &Origin::Flag("--context"))
+            .unwrap_err();
+        assert!(matches!(err, CliError::InvalidArguments(_)));
+        assert!(err.to_string().contains("contexts: staging"));
+
+        let err = config
+            .use_context("prod", &Origin::File(PathBuf::from("config.toml")))
+            .unwrap_err();
+        assert!(matches!(err, CliError::ConfigError(_)));
+    }
+
+    #[test]
+    fn context_names_must_be_bare_keys() {
+        assert!(validate_context_name("staging-2_eu").is_ok());
+        assert!(validate_context_name("").is_err());
+        assert!(validate_context_name("a.b").is_err());
+        assert!(validate_context_name("with space").is_err());
+    }
+}
@@ -0,0 +1,630 @@
+//! The `config.toml` file
+//!
+//! Holds the same settings as environment variables and flags, which
+//! override it. Top-level keys set the connection, session and output;
+//! `[archive]` and `[plugins.daemon]` tables hold the rest, and
+//! `[contexts.NAME]` tables hold named contexts (see `context`):
+//!
+//! ```toml
+//! session = "work"
+//! output = "json"
+//! command_timeout_ms = 60000
+//!
+//! [archive]
+//! enabled = true
+//! max_age_days = 30
+//!
+//! [plugins.daemon]
+//! version = "v0.2.4"
+//! ```
+//!
+//! Unknown keys are rejected so typos do not silently fall back to defaults.
+//! `config set` edits the file in place, keeping comments and layout.
+
+use super::context::Context;
+use super::project::validate_base_url;
+use super::{Config, Origin, VersionMismatch};
+use crate::error::{CliError, Result};
+use crate::types::OutputFormat;
+use crate::utils::dirs::get_config_dir;
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Config file name in the config directory
+pub const CONFIG_FILE: &str = "config.toml";
+
+// =============================================================================
+// File Contents
+// =============================================================================
+
+/// Settings read from `config.toml`; absent keys leave the default
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigFile {
+    pub socket_path: Option<PathBuf>,
+    pub session: Option<String>,
+    pub profile: Option<String>,
+    pub output: Option<OutputFormat>,
+    pub connection_timeout_ms: Option<u64>,
+    pub command_timeout_ms: Option<u64>,
+    pub startup_timeout_ms: Option<u64>,
+    pub startup_backoff_ms: Option<u64>,
+    pub autostart: Option<bool>,
+    pub version_mismatch: Option<VersionMismatch>,
+    pub base_url: Option<String>,
+    pub allowed_domains: Option<Vec<String>>,
+    /// Name of the active context
+    pub context: Option<String>,
+    pub contexts: BTreeMap<String, Context>,
+    pub archive: ArchiveFile,
+    pub plugins: PluginsFile,
+}
+
+/// The `[archive]` table; a limit of 0 disables it
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ArchiveFile {
+    pub enabled: Option<bool>,
+    pub max_entries: Option<usize>,
+    pub max_age_days: Option<u64>,
+    pub max_bytes: Option<u64>,
+}
+
+/// The `[plugins]` table
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PluginsFile {
+    pub daemon: PluginFile,
+}
+
+/// A `[plugins.<name>]` table
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PluginFile {
+    pub version: Option<String>,
+    pub path: Option<PathBuf>,
+}
+
+impl ConfigFile {
+    /// Read the file at `path`, or an empty one if it does not exist
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Self::parse(&fs::read_to_string(path)?, path)
+    }
+
+    /// Parse file contents; `path` is only used in errors
+    pub fn parse(contents: &str, path: &Path) -> Result<Self> {
+        let invalid = |message: String| {
+            CliError::ConfigError(format!("{}: {}", path.display(), message.trim_end()))
+        };
+
+        let file: Self = toml::from_str(contents).map_err(|e| invalid(e.to_string()))?;
+        if let Some(base_url) = &file.base_url {
+            validate_base_url(base_url).map_err(invalid)?;
+        }
+        Ok(file)
+    }
+
+    /// Override `config` with the settings present in the file at `path`
+    ///
+    /// Contexts are stored but not applied; `Config::load` picks the active one.
+    pub fn apply(self, config: &mut Config, path: &Path) {
+        let origin = Origin::File(path.to_path_buf());
+        config.contexts = self.contexts;
+
+        if let Some(socket_path) = self.socket_path {
+            config.ipc_socket_path = socket_path;
+            config.set_origin("socket_path", origin.clone());
+        }
+        if let Some(session) = self.session {
+            config.default_session = session;
+            config.set_origin("session", origin.clone());
+        }
+        if let Some(profile) = self.profile {
+            config.profile = Some(profile);
+            config.set_origin("profile", origin.clone());
+        }
+        if let Some(output) = self.output {
+            config.output = output;
+            config.set_origin("output", origin.clone());
+        }
+        if let Some(timeout) = self.connection_timeout_ms {
+            config.connection_timeout_ms = timeout;
+            config.set_origin("connection_timeout_ms", origin.clone());
+        }
+        if let Some(timeout) = self.command_timeout_ms {
+            config.command_timeout_ms = timeout;
+            config.set_origin("command_timeout_ms", origin.clone());
+        }
+        if let Some(timeout) = self.startup_timeout_ms {
+            config.startup_timeout_ms = timeout;
+            config.set_origin("startup_timeout_ms", origin.clone());
+        }
+        if let Some(backoff) = self.startup_backoff_ms {
+            config.startup_backoff_ms = backoff;
+            config.set_origin("startup_backoff_ms", origin.clone());
+        }
+        if let Some(autostart) = self.autostart {
+            config.autostart = autostart;
+            config.set_origin("autostart", origin.clone());
+        }
+        if let Some(mismatch) = self.version_mismatch {
+            config.version_mismatch = mismatch;
+            config.set_origin("version_mismatch", origin.clone());
+        }
+        if let Some(base_url) = self.base_url {
+            config.base_url = Some(base_url.trim_end_matches('/').to_string());
+            config.set_origin("base_url", origin.clone());
+        }
+        if let Some(domains) = self.allowed_domains {
+            config.allowed_domains = domains.iter().map(|d| d.to_lowercase()).collect();
+            config.set_origin("allowed_domains", origin.clone());
+        }
+
+        if let Some(enabled) = self.archive.enabled {
+            config.archive.enabled = enabled;
+            config.set_origin("archive.enabled", origin.clone());
+        }
+        if let Some(max_entries) = self.archive.max_entries {
+            config.archive.max_entries = limit(max_entries);
+            config.set_origin("archive.max_entries", origin.clone());
+        }
+        if let Some(max_age_days) = self.archive.max_age_days {
+            config.archive.max_age_days = limit(max_age_days);
+            config.set_origin("archive.max_age_days", origin.clone());
+        }
+        if let Some(max_bytes) = self.archive.max_bytes {
+            config.archive.max_bytes = limit(max_bytes);
+            config.set_origin("archive.max_bytes", origin.clone());
+        }
+
+        if let Some(version) = self.plugins.daemon.version {
+            config.plugins.daemon.version = Some(version);
+            config.set_origin("plugins.daemon.version", origin.clone());
+        }
+        if let Some(path) = self.plugins.daemon.path {
+            config.plugins.daemon.path = Some(path);
+            config.set_origin("plugins.daemon.path", origin.clone());
+        }
+    }
+}
+
+/// `0` means no limit
+fn limit<T: Default + PartialEq>(value: T) -> Option<T> {
+    (value != T::default()).then_some(value)
+}
+
+// =============================================================================
+// Keys
+// =============================================================================
+
+/// Value type of a config key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    String,
+    Integer,
+    Bool,
+    Output,
+    VersionMismatch,
+    /// Comma-separated on the command line, an array in the file
+    List,
+}
+
+/// A config key, named by its dotted path in the file
+#[derive(Debug)]
+pub struct Key {
+    pub name: &'static str,
+    kind: Kind,
+}
+
+/// Every key `config get` and `config set` accept
+pub const KEYS: &[Key] = &[
+    Key {
+        name: "socket_path",
+        kind: Kind::String,
+    },
+    Key {
+        name: "session",
+        kind: Kind::String,
+    },
+    Key {
+        name: "profile",
+        kind: Kind::String,
+    },
+    Key {
+        name: "output",
+        kind: Kind::Output,
+    },
+    Key {
+        name: "connection_timeout_ms",
+        kind: Kind::Integer,
+    },
+    Key {
+        name: "command_timeout_ms",
+        kind: Kind::Integer,
+    },
+    Key {
+        name: "startup_timeout_ms",
+        kind: Kind::Integer,
+    },
+    Key {
+        name: "startup_backoff_ms",
+        kind: Kind::Integer,
+    },
+    Key {
+        name: "autostart",
+        kind: Kind::Bool,
+    },
+    Key {
+        name: "version_mismatch",
+        kind: Kind::VersionMismatch,
+    },
+    Key {
+        name: "base_url",
+        kind: Kind::String,
+    },
+    Key {
+        name: "allowed_domains",
+        kind: Kind::List,
+    },
+    Key {
+        name: "archive.enabled",
+        kind: Kind::Bool,
+    },
+    Key {
+        name: "archive.max_entries",
+        kind: Kind::Integer,
+    },
+    Key {
+        name: "archive.max_age_days",
+        kind: Kind::Integer,
+    },
+    Key {
+        name: "archive.max_bytes",
+        kind: Kind::Integer,
+    },
+    Key {
+        name: "plugins.daemon.version",
+        kind: Kind::String,
+    },
+    Key {
+        name: "plugins.daemon.path",
+        kind: Kind::String,
+    },
+];
+
+impl Key {
+    /// Look up a key by name
+    pub fn find(name: &str) -> Result<&'static Key> {
+        KEYS.iter().find(|key| key.name == name).ok_or_else(|| {
+            let names: Vec<&str> = KEYS.iter().map(|key| key.name).collect();
+            CliError::InvalidArguments(format!(
+                "unknown config key '{}' (valid keys: {})",
+                name,
+                names.join(", ")
+            ))
+        })
+    }
+
+    /// Parse a command-line value for this key
+    fn parse(&self, input: &str) -> Result<toml_edit::Value> {
+        let invalid = |expected: &str| {
+            CliError::InvalidArguments(format!(
+                "invalid value '{}' for {}: expected {}",
+                input, self.name, expected
+            ))
+        };
+
+        match self.kind {
+            Kind::String => Ok(input.into()),
+            Kind::Integer => input
+                .trim()
+                .parse::<u64>()
+                .ok()
+                .and_then(|n| i64::try_from(n).ok())
+                .map(toml_edit::Value::from)
+                .ok_or_else(|| invalid("a non-negative integer")),
+            Kind::List => Ok(input
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .collect::<toml_edit::Array>()
+                .into()),
+            Kind::Bool => match input.trim() {
+                "true" | "1" | "yes" => Ok(true.into()),
+                "false" | "0" | "no" => Ok(false.into()),
+                _ => Err(invalid("true or false")),
+            },
+            Kind::Output => OutputFormat::from_str(input.trim(), true)
+                .map(|format| output_name(format).into())
+                .map_err(|_| {
+                    let names: Vec<&str> = OutputFormat::value_variants()
+                        .iter()
+                        .map(|format| output_name(*format))
+                        .collect();
+                    invalid(&format!("one of {}", names.join(", ")))
+                }),
+            Kind::VersionMismatch => VersionMismatch::from_str(input.trim(), true)
+                .map(|mismatch| mismatch_name(mismatch).into())
+                .map_err(|_| invalid("warn or refuse")),
+        }
+    }
+}
+
+fn output_name(format: OutputFormat) -> &'static str {
+    match format {
+        OutputFormat::Human => "human",
+        OutputFormat::Json => "json",
+        OutputFormat::Ndjson => "ndjson",
+        OutputFormat::Yaml => "yaml",
+        OutputFormat::Table => "table",
+        OutputFormat::Template => "template",
+        OutputFormat::Quiet => "quiet",
+    }
+}
+
+fn mismatch_name(mismatch: VersionMismatch) -> &'static str {
+    match mismatch {
+        VersionMismatch::Warn => "warn",
+        VersionMismatch::Refuse => "refuse",
+    }
+}
+
+/// Effective value of a key, or `None` if it is unset
+pub fn value(config: &Config, key: &Key) -> Option<serde_json::Value> {
+    let path = |path: &Path| path.to_string_lossy().into_owned().into();
+    match key.name {
+        "socket_path" => Some(path(&config.ipc_socket_path)),
+        "session" => Some(config.default_session.clone().into()),
+        "profile" => config.profile.clone().map(Into::into),
+        "output" => Some(output_name(config.output).into()),
+        "connection_timeout_ms" => Some(config.connection_timeout_ms.into()),
+        "command_timeout_ms" => Some(config.command_timeout_ms.into()),
+        "startup_timeout_ms" => Some(config.startup_timeout_ms.into()),
+        "startup_backoff_ms" => Some(config.startup_backoff_ms.into()),
+        "autostart" => Some(config.autostart.into()),
+        "version_mismatch" => Some(mismatch_name(config.version_mismatch).into()),
+        "base_url" => config.base_url.clone().map(Into::into),
+        "allowed_domains" => {
+            (!config.allowed_domains.is_empty()).then(|| config.allowed_domains.clone().into())
+        }
+        "archive.enabled" => Some(config.archive.enabled.into()),
+        "archive.max_entries" => Some(config.archive.max_entries.unwrap_or(0).into()),
+        "archive.max_age_days" => Some(config.archive.max_age_days.unwrap_or(0).into()),
+        "archive.max_bytes" => Some(config.archive.max_bytes.unwrap_or(0).into()),
+        "plugins.daemon.version" => config.plugins.daemon.version.clone().map(Into::into),
+        "plugins.daemon.path" => config.plugins.daemon.path.as_deref().map(path),
+        _ => None,
+    }
+}
+
+// =============================================================================
+// File Access
+// =============================================================================
+
+/// Path of the config file
+pub fn config_path() -> Result<PathBuf> {
+    Ok(get_config_dir()
+        .map_err(CliError::ConfigError)?
+        .join(CONFIG_FILE))
+}
+
+/// Set a key in the config file at `path`, creating the file if needed
+///
+/// The rest of the file, including comments, is kept as it is, even if it
+/// does not load, so a broken file can be repaired one key at a time.
+pub fn set_value(path: &Path, name: &str, input: &str) -> Result<()> {
+    let key = Key::find(name)?;
+    let value = key.parse(input)?;
+
+    edit(path, |document| {
+        let mut segments: Vec<&str> = key.name.split('.').collect();
+        let field = segments.pop().unwrap_or(key.name);
+        let mut table: &mut dyn toml_edit::TableLike = document.as_table_mut();
+        for segment in segments {
+            if !table.contains_key(segment) {
+                // Implicit, so `[plugins.daemon]` is written without an empty `[plugins]`
+                let mut child = toml_edit::Table::new();
+                child.set_implicit(true);
+                table.insert(segment, toml_edit::Item::Table(child));
+            }
+            table = table
+                .get_mut(segment)
+                .and_then(toml_edit::Item::as_table_like_mut)
+                .ok_or_else(|| {
+                    CliError::ConfigError(format!(
+                        "{}: `{}` is not a table",
+                        path.display(),
+                        segment
+                    ))
+                })?;
+        }
+        match table.get_mut(field).and_then(toml_edit::Item::as_value_mut) {
+            // Replace in place so comments around the key survive
+            Some(existing) => {
+                let decor = existing.decor().clone();
+                *existing = value;
+                *existing.decor_mut() = decor;
+            }
+            None => {
+                table.insert(field, toml_edit::value(value));
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Apply `change` to the config file at `path`, creating the file if needed
+///
+/// If the file loads before the change, the result is validated before it is
+/// written, so a change never breaks a working file. A file that does not
+/// load, for example because of a misspelled key, is edited as it is.
+pub(super) fn edit(
+    path: &Path,
+    change: impl FnOnce(&mut toml_edit::DocumentMut) -> Result<()>,
+) -> Result<()> {
+    let contents = if path.exists() {
+        fs::read_to_string(path)?
+    } else {
+        String::new()
+    };
+    let mut document = contents.parse::<toml_edit::DocumentMut>().map_err(|e| {
+        CliError::ConfigError(format!("{}: {}", path.display(), e.to_string().trim_end()))
+    })?;
+
+    let was_valid = ConfigFile::parse(&contents, path).is_ok();
+
+    change(&mut document)?;
+    separate_first_table(&mut document);
+
+    let contents = document.to_string();
+    if was_valid {
+        ConfigFile::parse(&contents, path)?;
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, contents)?;
+    Ok(())
+}
+
+/// Keep a blank line between top-level keys and the first table header
+///
+/// A key added to a file that starts with a table is written directly above
+/// the header otherwise.
+fn separate_first_table(document: &mut toml_edit::DocumentMut) {
+    if !document.iter().any(|(_, item)| item.is_value()) {
+        return;
+    }
+
+    let mut tables = Vec::new();
+    collect_tables(document.as_table_mut(), &mut tables);
+    let Some(first) = tables
+        .into_iter()
+        .min_by_key(|table| table.position().unwrap_or(usize::MAX))
+    else {
+        return;
+    };
+    let prefix = first.decor().prefix().and_then(|prefix| prefix.as_str());
+    if prefix.is_some_and(|prefix| !prefix.contains('\n')) {
+        first.decor_mut().set_prefix("\n");
+    }
+}
+
+/// Tables that have a header, looking through implicit ones
+fn collect_tables<'a>(table: &'a mut toml_edit::Table, out: &mut Vec<&'a mut toml_edit::Table>) {
+    for (_, item) in table.iter_mut() {
+        if let Some(child) = item.as_table_mut() {
+            if child.is_implicit() {
+                collect_tables(child, out);
+            } else {
+                out.push(child);
+            }
+        }
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_file(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "browser-config-file-test-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir.join(CONFIG_FILE)
+    }
+
+    #[test]
+    fn set_value_creates_file_and_tables() {
+        let path = temp_file("create");
+        set_value(&path, "output", "JSON").expect("set output");
+        set_value(&path, "plugins.daemon.version", "v0.2.4").expect("set version");
+        set_value(&path, "archive.max_entries", "50").expect("set limit");
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "output = \"json\"\n\n[plugins.daemon]\nversion = \"v0.2.4\"\n\n[archive]\nmax_entries = 50\n"
+        );
+
+        let mut config = Config::default();
+        ConfigFile::load(&path).unwrap().apply(&mut config, &path);
+        assert!(matches!(config.output, OutputFormat::Json));
+        assert_eq!(config.plugins.daemon.version.as_deref(), Some("v0.2.4"));
+        assert_eq!(config.archive.max_entries, Some(50));
+
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn set_value_keeps_comments() {
+        let path = temp_file("comments");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "# my settings\nsession = \"work\" # daily\n").unwrap();
+
+        set_value(&path, "session", "home").expect("set session");
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "# my settings\nsession = \"home\" # daily\n"
+        );
+
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn set_value_rejects_unknown_keys_and_bad_values() {
+        let path = temp_file("invalid");
+
+        let err = set_value(&path, "sesion", "x").unwrap_err();
+        assert!(err.to_string().contains("unknown config key 'sesion'"));
+
+        let err = set_value(&path, "command_timeout_ms", "soon").unwrap_err();
+        assert!(err.to_string().contains("expected a non-negative integer"));
+
+        let err = set_value(&path, "output", "xml").unwrap_err();
+        assert!(err.to_string().contains("one of human, json"));
+
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn set_value_edits_a_file_with_an_unknown_key() {
+        let path = temp_file("typo");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "sesion = \"work\"\n").unwrap();
+
+        set_value(&path, "session", "work").expect("set session");
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "sesion = \"work\"\nsession = \"work\"\n"
+        );
+
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn value_reports_effective_settings() {
+        let config = Config::default();
+        let key = |name| Key::find(name).unwrap();
+        assert_eq!(
+            value(&config, key("command_timeout_ms")),
+            Some(30000.into())
+        );
+        assert_eq!(value(&config, key("archive.max_age_days")), Some(0.into()));
+        assert_eq!(value(&config, key("profile")), None);
+        assert_eq!(value(&config, key("output")), Some("human".into()));
+    }
+}
@@ -0,0 +1,679 @@
+//! Configuration for the Browser CLI
+//!
+//! Settings are layered, each overriding the one before: built-in defaults,
+//! the user's `config.toml`, the project's `.browser.toml`, the active
+//! context, environment variables, then command-line flags (applied by
+//! `main`). Every setting remembers which layer it came from.
+
+mod context;
+mod file;
+mod project;
+
+pub use context::{
+    Context, create_context, remove_context, set_current_context, validate_context_name,
+};
+pub use file::{CONFIG_FILE, ConfigFile, KEYS, Key, config_path, set_value, value};
+pub use project::{PROJECT_FILE, ProjectFile, discover};
+
+use crate::error::Result;
+use crate::types::OutputFormat;
+#[cfg(unix)]
+use crate::utils::dirs::get_runtime_dir;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+// =============================================================================
+// Constants
+// =============================================================================
+
+/// Socket file name in the runtime directory
+#[cfg(unix)]
+pub const DEFAULT_IPC_SOCKET_NAME: &str = "daemon.sock";
+
+/// Default named pipe path for IPC communication (Windows)
+#[cfg(windows)]
+pub const DEFAULT_IPC_SOCKET_PATH: &str = r"\\.\pipe\browser-daemon";
+
+/// Environment variable for custom socket path
+pub const ENV_IPC_SOCKET_PATH: &str = "BROWSER_SOCKET_PATH";
+
+/// Environment variable for session name
+pub const ENV_SESSION_NAME: &str = "BROWSER_SESSION";
+
+/// Environment variable for browser profile directory
+pub const ENV_PROFILE: &str = "BROWSER_PROFILE";
+
+/// Environment variable for the active context
+pub const ENV_CONTEXT: &str = "BROWSER_CONTEXT";
+
+/// Environment variable enabling the snapshot archive ("1" or "true")
+pub const ENV_ARCHIVE: &str = "BROWSER_ARCHIVE";
+
+/// Environment variable for the maximum number of archived snapshots
+pub const ENV_ARCHIVE_MAX_ENTRIES: &str = "BROWSER_ARCHIVE_MAX_ENTRIES";
+
+/// Environment variable for the maximum age of archived snapshots, in days
+pub const ENV_ARCHIVE_MAX_AGE_DAYS: &str = "BROWSER_ARCHIVE_MAX_AGE_DAYS";
+
+/// Environment variable for the maximum archive size, in bytes
+pub const ENV_ARCHIVE_MAX_BYTES: &str = "BROWSER_ARCHIVE_MAX_BYTES";
+
+/// Default session name
+pub const DEFAULT_SESSION_NAME: &str = "default";
+
+/// Default number of snapshots kept in the archive
+pub const DEFAULT_ARCHIVE_MAX_ENTRIES: usize = 1000;
+
+/// Default archive size limit (100 MiB)
+pub const DEFAULT_ARCHIVE_MAX_BYTES: u64 = 100 * 1024 * 1024;
+
+// =============================================================================
+// Config Struct
+// =============================================================================
+
+/// CLI configuration
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Path to the IPC socket for daemon communication
+    pub ipc_socket_path: PathBuf,
+
+    /// Default session name to use
+    pub default_session: String,
+
+    /// Connection timeout in milliseconds
+    pub connection_timeout_ms: u64,
+
+    /// Command timeout in milliseconds
+    pub command_timeout_ms: u64,
+
+    /// How long to wait for an auto-started daemon, in milliseconds
+    pub startup_timeout_ms: u64,
+
+    /// First wait between checks for a starting daemon, in milliseconds; doubles each time
+    pub startup_backoff_ms: u64,
+
+    /// Whether commands start a daemon when none is running
+    pub autostart: bool,
+
+    /// Browser profile directory, if not the system default
+    pub profile: Option<String>,
+
+    /// Default output format
+    pub output: OutputFormat,
+
+    /// What to do when the daemon or extension speaks another protocol major version
+    pub version_mismatch: VersionMismatch,
+
+    /// Snapshot archive settings
+    pub archive: ArchiveConfig,
+
+    /// Plugin settings
+    pub plugins: PluginsConfig,
+
+    /// URL that `navigate` paths starting with `/` are relative to
+    pub base_url: Option<String>,
+
+    /// Domains `navigate` and `tab new` may open; empty allows any
+    pub allowed_domains: Vec<String>,
+
+    /// Aliases from the project file
+    pub aliases: BTreeMap<String, String>,
+
+    /// Project file in effect, if one was found
+    pub project_file: Option<PathBuf>,
+
+    /// Named contexts from the config file
+    pub contexts: BTreeMap<String, Context>,
+
+    /// Active context, if one is in use
+    pub context: Option<String>,
+
+    /// Where each non-default setting came from, by key name
+    pub origins: BTreeMap<&'static str, Origin>,
+}
+
+/// The layer a setting came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Origin {
+    Default,
+    File(PathBuf),
+    Context(String),
+    Env(&'static str),
+    Flag(&'static str),
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Origin::Default => write!(f, "default"),
+            Origin::File(path) => write!(f, "{}", path.display()),
+            Origin::Context(name) => write!(f, "context {}", name),
+            Origin::Env(var) => write!(f, "env {}", var),
+            Origin::Flag(flag) => write!(f, "flag {}", flag),
+        }
+    }
+}
+
+/// What to do when another component speaks a different protocol major version
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum VersionMismatch {
+    /// Print a warning and send the command anyway
+    #[default]
+    Warn,
+    /// Fail without sending the command
+    Refuse,
+}
+
+/// Settings for downloaded plugins
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PluginsConfig {
+    /// The `browser-daemon` plugin
+    pub daemon: PluginSettings,
+}
+
+/// How a plugin binary is found
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PluginSettings {
+    /// Version to download instead of the latest release
+    pub version: Option<String>,
+
+    /// Binary to run instead of downloading one
+    pub path: Option<PathBuf>,
+}
+
+/// Snapshot archive settings
+///
+/// Each limit is optional; when set, the oldest snapshots are pruned until
+/// the archive is within it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveConfig {
+    /// Whether `snapshot` results are archived
+    pub enabled: bool,
+
+    /// Maximum number of archived snapshots
+    pub max_entries: Option<usize>,
+
+    /// Maximum age of archived snapshots, in days
+    pub max_age_days: Option<u64>,
+
+    /// Maximum size of the archive file, in bytes
+    pub max_bytes: Option<u64>,
+}
+
+impl Default for ArchiveConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_entries: Some(DEFAULT_ARCHIVE_MAX_ENTRIES),
+            max_age_days: None,
+            max_bytes: Some(DEFAULT_ARCHIVE_MAX_BYTES),
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            ipc_socket_path: default_socket_path(),
+            default_session: DEFAULT_SESSION_NAME.to_string(),
+            connection_timeout_ms: 5000,
+            command_timeout_ms: 30000,
+            startup_timeout_ms: 10000,
+            startup_backoff_ms: 50,
+            autostart: true,
+            profile: None,
+            output: OutputFormat::default(),
+            version_mismatch: VersionMismatch::default(),
+            archive: ArchiveConfig::default(),
+            plugins: PluginsConfig::default(),
+            base_url: None,
+            allowed_domains: Vec::new(),
+            aliases: BTreeMap::new(),
+            project_file: None,
+            contexts: BTreeMap::new(),
+            context: None,
+            origins: BTreeMap::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Load configuration from defaults and environment variables
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        config.apply_env();
+        config
+    }
+
+    /// Load defaults, then the user config file and project file where they
+    /// exist, then the active context, then the environment
+    ///
+    /// `context` is the name given by `--context`, which takes precedence over
+    /// `BROWSER_CONTEXT` and the `context` key in the user config file.
+    pub fn load(
+        user_file: Option<&Path>,
+        project_file: Option<&Path>,
+        context: Option<&str>,
+    ) -> Result<Self> {
+        let mut config = Self::default();
+        let mut selected = context.map(|name| (name.to_string(), Origin::Flag("--context")));
+        if selected.is_none()
+            && let Ok(name) = std::env::var(ENV_CONTEXT)
+            && !name.is_empty()
+        {
+            selected = Some((name, Origin::Env(ENV_CONTEXT)));
+        }
+
+        if let Some(path) = user_file {
+            let file = ConfigFile::load(path)?;
+            if selected.is_none()
+                && let Some(name) = &file.context
+            {
+                selected = Some((name.clone(), Origin::File(path.to_path_buf())));
+            }
+            file.apply(&mut config, path);
+        }
+        if let Some(path) = project_file {
+            ProjectFile::load(path)?.apply(&mut config, path);
+        }
+        if let Some((name, origin)) = selected {
+            config.use_context(&name, &origin)?;
+        }
+        config.apply_env();
+        Ok(config)
+    }
+
+    /// Where a setting came from
+    pub fn origin(&self, key: &str) -> Origin {
+        self.origins.get(key).cloned().unwrap_or(Origin::Default)
+    }
+
+    /// Record where a setting came from
+    pub fn set_origin(&mut self, key: &'static str, origin: Origin) {
+        self.origins.insert(key, origin);
+    }
+
+    /// Override settings from environment variables
+    fn apply_env(&mut self) {
+        // Override socket path from environment
+        if let Ok(socket_path) = std::env::var(ENV_IPC_SOCKET_PATH) {
+            self.ipc_socket_path = PathBuf::from(socket_path);
+            self.set_origin("socket_path", Origin::Env(ENV_IPC_SOCKET_PATH));
+        }
+
+        // Override default session from environment
+        if let Ok(session_name) = std::env::var(ENV_SESSION_NAME) {
+            self.default_session = session_name;
+            self.set_origin("session", Origin::Env(ENV_SESSION_NAME));
+        }
+
+        // Override profile from environment
+        if let Ok(profile) = std::env::var(ENV_PROFILE) {
+            self.profile = Some(profile);
+            self.set_origin("profile", Origin::Env(ENV_PROFILE));
+        }
+
+        // Archive settings from environment
+        if let Ok(enabled) = std::env::var(ENV_ARCHIVE) {
+            self.archive.enabled = matches!(enabled.as_str(), "1" | "true" | "yes");
+            self.set_origin("archive.enabled", Origin::Env(ENV_ARCHIVE));
+        }
+        if let Some(max_entries) = env_limit(ENV_ARCHIVE_MAX_ENTRIES) {
+            self.archive.max_entries = max_entries;
+            self.set_origin("archive.max_entries", Origin::Env(ENV_ARCHIVE_MAX_ENTRIES));
+        }
+        if let Some(max_age_days) = env_limit(ENV_ARCHIVE_MAX_AGE_DAYS) {
+            self.archive.max_age_days = max_age_days;
+            self.set_origin(
+                "archive.max_age_days",
+                Origin::Env(ENV_ARCHIVE_MAX_AGE_DAYS),
+            );
+        }
+        if let Some(max_bytes) = env_limit(ENV_ARCHIVE_MAX_BYTES) {
+            self.archive.max_bytes = max_bytes;
+            self.set_origin("archive.max_bytes", Origin::Env(ENV_ARCHIVE_MAX_BYTES));
+        }
+    }
+
+    /// Get the IPC socket path, with environment override
+    pub fn get_socket_path(&self) -> PathBuf {
+        // Check environment variable first
+        if let Ok(socket_path) = std::env::var(ENV_IPC_SOCKET_PATH) {
+            PathBuf::from(socket_path)
+        } else {
+            self.ipc_socket_path.clone()
+        }
+    }
+}
+
+// =============================================================================
+// Helper Functions
+// =============================================================================
+
+/// Read an optional numeric limit from the environment
+///
+/// `0` disables the limit; unparsable values are ignored.
+fn env_limit<T: std::str::FromStr + Default + PartialEq>(var: &str) -> Option<Option<T>> {
+    let value = std::env::var(var).ok()?.trim().parse::<T>().ok()?;
+    Some((value != T::default()).then_some(value))
+}
+
+/// Default socket path: `$XDG_RUNTIME_DIR/browser/daemon.sock`, so only the
+/// current user can reach their daemon
+#[cfg(unix)]
+pub fn default_socket_path() -> PathBuf {
+    get_runtime_dir().join(DEFAULT_IPC_SOCKET_NAME)
+}
+
+/// Default named pipe path
+#[cfg(windows)]
+pub fn default_socket_path() -> PathBuf {
+    PathBuf::from(DEFAULT_IPC_SOCKET_PATH)
+}
+
+/// Load the global CLI configuration
+///
+/// The user config file is skipped if the config directory cannot be
+/// determined; the project file is looked for from the current directory up.
+/// `context` is the name given by `--context`, if any.
+pub fn load_config(context: Option<&str>) -> Result<Config> {
+    let project_file = std::env::current_dir().ok().and_then(|dir| discover(&dir));
+    Config::load(
+        config_path().ok().as_deref(),
+        project_file.as_deref(),
+        context,
+    )
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::sync::Mutex;
+
+    // Mutex to serialize tests that modify environment variables
+    static ENV_MUTEX: Mutex<()> = Mutex::new(());
+
+    #[test]
+    fn default_config_has_correct_values() {
+        let config = Config::default();
+        assert_eq!(config.ipc_socket_path, default_socket_path());
+        assert_eq!(config.default_session, DEFAULT_SESSION_NAME);
+        assert_eq!(config.connection_timeout_ms, 5000);
+        assert_eq!(config.command_timeout_ms, 30000);
+    }
+
+    #[test]
+    fn from_env_loads_socket_path_from_environment() {
+        let _lock = ENV_MUTEX.lock().unwrap();
+        let test_path = "/tmp/test-socket.sock";
+
+        unsafe {
+            env::set_var(ENV_IPC_SOCKET_PATH, test_path);
+        }
+
+        let config = Config::from_env();
+        assert_eq!(config.ipc_socket_path, PathBuf::from(test_path));
+
+        unsafe {
+            env::remove_var(ENV_IPC_SOCKET_PATH);
+        }
+    }
+
+    #[test]
+    fn from_env_loads_session_name_from_environment() {
+        let _lock = ENV_MUTEX.lock().unwrap();
+        let test_session = "test-session";
+
+        unsafe {
+            env::set_var(ENV_SESSION_NAME, test_session);
+        }
+
+        let config = Config::from_env();
+        assert_eq!(config.default_session, test_session);
+
+        unsafe {
+            env::remove_var(ENV_SESSION_NAME);
+        }
+    }
+
+    #[test]
+    fn from_env_uses_defaults_when_env_vars_not_set() {
+        let _lock = ENV_MUTEX.lock().unwrap();
+
+        unsafe {
+            env::remove_var(ENV_IPC_SOCKET_PATH);
+            env::remove_var(ENV_SESSION_NAME);
+        }
+
+        let config = Config::from_env();
+        assert_eq!(config.ipc_socket_path, default_socket_path());
+        assert_eq!(config.default_session, DEFAULT_SESSION_NAME);
+    }
+
+    #[test]
+    fn from_env_loads_archive_settings() {
+        let _lock = ENV_MUTEX.lock().unwrap();
+
+        unsafe {
+            env::set_var(ENV_ARCHIVE, "1");
+            env::set_var(ENV_ARCHIVE_MAX_ENTRIES, "50");
+            env::set_var(ENV_ARCHIVE_MAX_AGE_DAYS, "7");
+            env::set_var(ENV_ARCHIVE_MAX_BYTES, "0");
+        }
+
+        let config = Config::from_env();
+        assert_eq!(
+            config.archive,
+            ArchiveConfig {
+                enabled: true,
+                max_entries: Some(50),
+                max_age_days: Some(7),
+                max_bytes: None,
+            }
+        );
+
+        unsafe {
+            env::remove_var(ENV_ARCHIVE);
+            env::remove_var(ENV_ARCHIVE_MAX_ENTRIES);
+            env::remove_var(ENV_ARCHIVE_MAX_AGE_DAYS);
+            env::remove_var(ENV_ARCHIVE_MAX_BYTES);
+        }
+    }
+
+    #[test]
+    fn get_socket_path_returns_env_var_when_set() {
+        let _lock = ENV_MUTEX.lock().unwrap();
+        let test_path = "/tmp/override-socket.sock";
+
+        unsafe {
+            env::set_var(ENV_IPC_SOCKET_PATH, test_path);
+        }
+
+        let config = Config::default();
+        assert_eq!(config.get_socket_path(), PathBuf::from(test_path));
+
+        unsafe {
+            env::remove_var(ENV_IPC_SOCKET_PATH);
+        }
+    }
+
+    #[test]
+    fn get_socket_path_returns_config_value_when_env_not_set() {
+        let _lock = ENV_MUTEX.lock().unwrap();
+
+        unsafe {
+            env::remove_var(ENV_IPC_SOCKET_PATH);
+        }
+
+        let config = Config {
+            ipc_socket_path: PathBuf::from("/custom/path.sock"),
+            ..Default::default()
+        };
+        assert_eq!(config.get_socket_path(), PathBuf::from("/custom/path.sock"));
+    }
+
+    #[test]
+    fn load_config_returns_env_based_config() {
+        let _lock = ENV_MUTEX.lock().unwrap();
+        let dir = temp_config_dir("load-config");
+
+        unsafe {
+            env::set_var("XDG_CONFIG_HOME", &dir);
+            env::remove_var(ENV_IPC_SOCKET_PATH);
+            env::remove_var(ENV_SESSION_NAME);
+        }
+
+        let config = load_config(None).expect("load config");
+        assert_eq!(config.ipc_socket_path, default_socket_path());
+        assert_eq!(config.default_session, DEFAULT_SESSION_NAME);
+
+        unsafe {
+            env::remove_var("XDG_CONFIG_HOME");
+        }
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn load_layers_file_under_environment() {
+        let _lock = ENV_MUTEX.lock().unwrap();
+        let dir = temp_config_dir("layers");
+        let path = dir.join(CONFIG_FILE);
+        std::fs::write(
+            &path,
+            "session = \"work\"\ncommand_timeout_ms = 60000\noutput = \"json\"\n\n[archive]\nenabled = true\nmax_entries = 0\n",
+        )
+        .expect("write config");
+
+        unsafe {
+            env::remove_var(ENV_ARCHIVE);
+            env::remove_var(ENV_ARCHIVE_MAX_ENTRIES);
+            env::set_var(ENV_SESSION_NAME, "from-env");
+        }
+
+        let config = Config::load(Some(&path), None, None).expect("load config");
+        assert_eq!(config.default_session, "from-env");
+        assert_eq!(config.command_timeout_ms, 60000);
+        assert_eq!(config.connection_timeout_ms, 5000);
+        assert!(matches!(config.output, OutputFormat::Json));
+        assert!(config.archive.enabled);
+        assert_eq!(config.archive.max_entries, None);
+
+        unsafe {
+            env::remove_var(ENV_SESSION_NAME);
+        }
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn load_layers_project_between_file_and_environment() {
+        let _lock = ENV_MUTEX.lock().unwrap();
+        let dir = temp_config_dir("project-layers");
+        let user = dir.join(CONFIG_FILE);
+        let project = dir.join(PROJECT_FILE);
+        std::fs::write(
+            &user,
+            "session = \"work\"\nbase_url = \"http://user.test\"\n",
+        )
+        .expect("write config");
+        std::fs::write(&project, "base_url = \"http://localhost:3000\"\n").expect("write project");
+
+        unsafe {
+            env::set_var(ENV_SESSION_NAME, "from-env");
+        }
+
+        let config = Config::load(Some(&user), Some(&project), None).expect("load config");
+        assert_eq!(config.default_session, "from-env");
+        assert_eq!(config.base_url.as_deref(), Some("http://localhost:3000"));
+        assert_eq!(config.origin("session"), Origin::Env(ENV_SESSION_NAME));
+        assert_eq!(config.origin("base_url"), Origin::File(project.clone()));
+        assert_eq!(config.project_file, Some(project));
+
+        unsafe {
+            env::remove_var(ENV_SESSION_NAME);
+        }
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn load_selects_context_by_flag_then_env_then_file() {
+        let _lock = ENV_MUTEX.lock().unwrap();
+        let dir = temp_config_dir("contexts");
+        let path = dir.join(CONFIG_FILE);
+        std::fs::write(
+            &path,
+            "session = \"work\"\ncontext = \"a\"\n\n[contexts.a]\nsession = \"sa\"\n\n[contexts.b]\nsession = \"sb\"\nsocket_path = \"/tmp/b.sock\"\n",
+        )
+        .expect("write config");
+
+        unsafe {
+            env::remove_var(ENV_CONTEXT);
+            env::remove_var(ENV_SESSION_NAME);
+            env::remove_var(ENV_IPC_SOCKET_PATH);
+        }
+        let config = Config::load(Some(&path), None, None).expect("load config");
+        assert_eq!(config.context.as_deref(), Some("a"));
+        assert_eq!(config.default_session, "sa");
+
+        unsafe {
+            env::set_var(ENV_CONTEXT, "b");
+        }
+        let config = Config::load(Some(&path), None, None).expect("load config");
+        assert_eq!(config.default_session, "sb");
+        assert_eq!(config.ipc_socket_path, PathBuf::from("/tmp/b.sock"));
+
+        let config = Config::load(Some(&path), None, Some("a")).expect("load config");
+        assert_eq!(config.context.as_deref(), Some("a"));
+
+        // Environment variables still override the context's settings
+        unsafe {
+            env::set_var(ENV_SESSION_NAME, "from-env");
+        }
+        let config = Config::load(Some(&path), None, None).expect("load config");
+        assert_eq!(config.default_session, "from-env");
+        assert_eq!(
+            config.origin("socket_path"),
+            Origin::Context("b".to_string())
+        );
+
+        let err = Config::load(Some(&path), None, Some("c")).unwrap_err();
+        assert!(matches!(err, crate::error::CliError::InvalidArguments(_)));
+
+        unsafe {
+            env::remove_var(ENV_CONTEXT);
+            env::remove_var(ENV_SESSION_NAME);
+        }
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn load_rejects_unknown_keys() {
+        let _lock = ENV_MUTEX.lock().unwrap();
+        let dir = temp_config_dir("unknown");
+        let path = dir.join(CONFIG_FILE);
+        std::fs::write(&path, "sesion = \"typo\"\n").expect("write config");
+
+        let err = Config::load(Some(&path), None, None).unwrap_err();
+        assert!(matches!(err, crate::error::CliError::ConfigError(_)));
+        assert!(err.to_string().contains("unknown field `sesion`"));
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    fn temp_config_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!(
+            "browser-config-test-{}-{}",
+            name,
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        dir
+    }
+}
@@ -0,0 +1,257 @@
+//! Project-local `.browser.toml`
+//!
+//! Found by walking up from the current directory, like cargo finds a
+//! workspace, so each app in a monorepo can carry its own defaults:
+//!
+//! ```toml
+//! session = "checkout"
+//! profile = ".browser-profile"   # relative to this file
+//! base_url = "http://localhost:3000"
+//! allowed_domains = ["localhost", "stripe.com"]
+//!
+//! [aliases]
+//! pay = 'role=button[name="Pay"]'
+//! ```
+//!
+//! It overrides the user's `config.toml` and is overridden by environment
+//! variables and flags. Only the keys above are accepted: a checked-out
+//! repository should not be able to point the CLI at another daemon binary
+//! or socket.
+
+use super::{Config, Origin};
+use crate::aliases::{self, validate_alias_name};
+use crate::commands::utils::normalize_url;
+use crate::error::{CliError, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Project file name
+pub const PROJECT_FILE: &str = ".browser.toml";
+
+/// Settings read from `.browser.toml`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectFile {
+    pub session: Option<String>,
+    pub profile: Option<PathBuf>,
+    pub base_url: Option<String>,
+    pub allowed_domains: Option<Vec<String>>,
+    pub aliases: BTreeMap<String, String>,
+}
+
+impl ProjectFile {
+    /// Read and validate the project file at `path`
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        let invalid = |message: String| {
+            CliError::ConfigError(format!("{}: {}", path.display(), message.trim_end()))
+        };
+
+        let file: Self = toml::from_str(&contents).map_err(|e| invalid(e.to_string()))?;
+        if let Some(base_url) = &file.base_url {
+            validate_base_url(base_url).map_err(invalid)?;
+        }
+        for name in file.aliases.keys() {
+            validate_alias_name(name).map_err(|e| invalid(e.to_string()))?;
+        }
+        Ok(file)
+    }
+
+    /// Override `config` with the settings present in the file at `path`
+    pub fn apply(self, config: &mut Config, path: &Path) {
+        let origin = Origin::File(path.to_path_buf());
+
+        if let Some(session) = self.session {
+            config.default_session = session;
+            config.set_origin("session", origin.clone());
+        }
+        if let Some(profile) = self.profile {
+            // Relative profiles live next to the project file
+            let profile = match path.parent() {
+                Some(dir) if profile.is_relative() => dir.join(profile),
+                _ => profile,
+            };
+            config.profile = Some(profile.to_string_lossy().into_owned());
+            config.set_origin("profile", origin.clone());
+        }
+        if let Some(base_url) = self.base_url {
+            config.base_url = Some(base_url.trim_end_matches('/').to_string());
+            config.set_origin("base_url", origin.clone());
+        }
+        if let Some(domains) = self.allowed_domains {
+            config.allowed_domains = domains.iter().map(|d| d.to_lowercase()).collect();
+            config.set_origin("allowed_domains", origin.clone());
+        }
+        config.aliases = self.aliases;
+        config.project_file = Some(path.to_path_buf());
+    }
+}
+
+/// Find `.browser.toml` in `start` or the nearest ancestor that has one
+pub fn discover(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(PROJECT_FILE))
+        .find(|candidate| candidate.is_file())
+}
+
+pub(super) fn validate_base_url(base_url: &str) -> std::result::Result<(), String> {
+    if base_url.starts_with("http://") || base_url.starts_with("https://") {
+        Ok(())
+    } else {
+        Err(format!(
+            "base_url must start with http:// or https://, got \"{}\"",
+            base_url
+        ))
+    }
+}
+
+impl Config {
+    /// Turn a `navigate` or `tab new` argument into the URL to open
+    ///
+    /// Paths starting with `/` are joined to `base_url`. The result must be
+    /// on one of `allowed_domains` (or a subdomain), when that is set.
+    pub fn resolve_url(&self, input: &str) -> Result<String> {
+        let input = input.trim();
+        let url = if input.starts_with('/') {
+            let base_url = self.base_url.as_deref().ok_or_else(|| {
+                CliError::InvalidArguments(format!(
+                    "{} is a relative path; set base_url in {} to use one",
+                    input, PROJECT_FILE
+                ))
+            })?;
+            format!("{}{}", base_url, input)
+        } else {
+            normalize_url(input)
+        };
+
+        if self.allowed_domains.is_empty() {
+            return Ok(url);
+        }
+
+        let allowed = aliases::url_host(&url).is_some_and(|host| {
+            self.allowed_domains
+                .iter()
+                .any(|domain| aliases::host_matches_domain(&host, domain))
+        });
+        if !allowed {
+            return Err(CliError::InvalidArguments(format!(
+                "{} is not on an allowed domain ({}), set by {}",
+                url,
+                self.allowed_domains.join(", "),
+                self.origin("allowed_domains")
+            )));
+        }
+        Ok(url)
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "browser-project-test-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("create temp dir");
+        dir
+    }
+
+    #[test]
+    fn discover_walks_up_to_nearest_file() {
+        let root = temp_dir("discover");
+        let app = root.join("apps").join("shop");
+        let nested = app.join("src").join("pages");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(root.join(PROJECT_FILE), "").unwrap();
+        fs::write(app.join(PROJECT_FILE), "").unwrap();
+
+        assert_eq!(discover(&nested), Some(app.join(PROJECT_FILE)));
+        assert_eq!(discover(&root.join("apps")), Some(root.join(PROJECT_FILE)));
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn apply_sets_project_defaults_with_origin() {
+        let dir = temp_dir("apply");
+        let path = dir.join(PROJECT_FILE);
+        fs::write(
+            &path,
+            "session = \"shop\"\nprofile = \"profile\"\nbase_url = \"http://localhost:3000/\"\nallowed_domains = [\"Localhost\"]\n\n[aliases]\npay = \"e5\"\n",
+        )
+        .unwrap();
+
+        let mut config = Config::default();
+        ProjectFile::load(&path).unwrap().apply(&mut config, &path);
+
+        assert_eq!(config.default_session, "shop");
+        assert_eq!(
+            config.profile.as_deref(),
+            Some(dir.join("profile").to_string_lossy().as_ref())
+        );
+        assert_eq!(config.base_url.as_deref(), Some("http://localhost:3000"));
+        assert_eq!(config.allowed_domains, vec!["localhost"]);
+        assert_eq!(config.aliases.get("pay").map(String::as_str), Some("e5"));
+        assert_eq!(config.origin("session"), Origin::File(path.clone()));
+        assert_eq!(config.origin("command_timeout_ms"), Origin::Default);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn load_rejects_keys_outside_the_project_schema() {
+        let dir = temp_dir("reject");
+        let path = dir.join(PROJECT_FILE);
+
+        fs::write(&path, "socket_path = \"/tmp/other.sock\"\n").unwrap();
+        let err = ProjectFile::load(&path).unwrap_err();
+        assert!(err.to_string().contains("unknown field `socket_path`"));
+
+        fs::write(&path, "base_url = \"localhost:3000\"\n").unwrap();
+        let err = ProjectFile::load(&path).unwrap_err();
+        assert!(err.to_string().contains("base_url must start with http"));
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn resolve_url_joins_base_url_and_checks_domains() {
+        let mut config = Config {
+            base_url: Some("http://localhost:3000".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            config.resolve_url("/cart?x=1").unwrap(),
+            "http://localhost:3000/cart?x=1"
+        );
+        assert_eq!(
+            config.resolve_url("example.com").unwrap(),
+            "https://example.com"
+        );
+
+        config.allowed_domains = vec!["localhost".to_string(), "example.com".to_string()];
+        assert!(config.resolve_url("/cart").is_ok());
+        assert!(config.resolve_url("https://docs.example.com/a").is_ok());
+        assert!(matches!(
+            config.resolve_url("https://evil.com"),
+            Err(CliError::InvalidArguments(_))
+        ));
+
+        config.base_url = None;
+        assert!(matches!(
+            config.resolve_url("/cart"),
+            Err(CliError::InvalidArguments(_))
+        ));
+    }
+}
@@ -2,10 +2,15 @@
 //!
 //! Provides functionality to:
 //! - Check if daemon is running
-//! - Start daemon if not running
+//! - Start daemon if not running, one process at a time
+//! - Replace a socket left behind by a daemon that died
 //! - Wait for daemon to be ready
+//! - Run daemon in the foreground, for service managers and containers
+//! - Stop daemon, by request or by signalling the PID in its pidfile
 
-use std::process::{Command, Stdio};
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
 use std::thread;
 use std::time::{Duration, Instant};
 
@@ -20,11 +25,144 @@ use std::os::unix::process::CommandExt;
 // Constants
 // =============================================================================
 
-/// Maximum time to wait for daemon to start (in milliseconds)
-const DAEMON_STARTUP_TIMEOUT_MS: u64 = 10000;
+/// Longest wait between checks for a starting daemon (in milliseconds)
+const DAEMON_STARTUP_BACKOFF_MAX_MS: u64 = 1000;
 
-/// Polling interval when waiting for daemon to start (in milliseconds)
-const DAEMON_POLL_INTERVAL_MS: u64 = 100;
+/// Polling interval when waiting for daemon to exit (in milliseconds)
+const DAEMON_EXIT_POLL_INTERVAL_MS: u64 = 100;
+
+/// Maximum time to wait for daemon to exit after a shutdown request (in milliseconds)
+///
+/// The daemon itself waits up to 10 seconds for in-flight commands.
+const DAEMON_SHUTDOWN_TIMEOUT_MS: u64 = 15000;
+
+/// Maximum time to wait for daemon to exit after each signal (in milliseconds)
+#[cfg(unix)]
+const DAEMON_SIGNAL_TIMEOUT_MS: u64 = 5000;
+
+/// Size at which the daemon log is rotated when a daemon is started
+const LOG_MAX_BYTES: u64 = 1024 * 1024;
+
+/// Rotated logs kept beside the current one (`daemon.log.1` to `daemon.log.3`)
+const LOG_ROTATIONS: usize = 3;
+
+/// Log lines included in the error when the daemon fails to start
+const LOG_TAIL_LINES: usize = 20;
+
+// =============================================================================
+// Daemon Files
+// =============================================================================
+
+/// Pidfile of the daemon listening on the configured socket
+///
+/// Kept beside the socket, so each socket path has its own.
+pub fn pidfile_path(config: &Config) -> PathBuf {
+    config.ipc_socket_path.with_extension("pid")
+}
+
+/// Name shared by the systemd service and socket units
+pub const UNIT_NAME: &str = "browser-daemon";
+
+/// Marker left by a socket-activated daemon, naming the socket unit
+///
+/// Sits next to the pidfile. While that unit listens, systemd owns the
+/// socket and restarts the daemon on the next connection.
+fn activation_marker_path(config: &Config) -> PathBuf {
+    config.ipc_socket_path.with_extension("activated")
+}
+
+/// Log file the daemon's output is written to when the CLI starts it
+///
+/// Lives in the state directory and is named after the socket, so the
+/// default `daemon.sock` logs to `daemon.log`.
+pub fn log_path(config: &Config) -> Result<PathBuf> {
+    let state_dir = crate::utils::dirs::get_state_dir().map_err(CliError::ConfigError)?;
+    let stem = config
+        .ipc_socket_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "daemon".to_string());
+    Ok(state_dir.join(format!("{}.log", stem)))
+}
+
+/// Lock file held while a CLI process starts the daemon
+pub fn lock_path(config: &Config) -> PathBuf {
+    config.ipc_socket_path.with_extension("lock")
+}
+
+/// PID recorded in a pidfile, if it exists and holds one
+fn read_pidfile(path: &Path) -> Option<u32> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// Open the daemon log for appending, creating the state directory
+///
+/// Rotates the log first if it has grown past `LOG_MAX_BYTES`. The log can
+/// hold page content and error details, so it is readable only by the
+/// current user, in a directory only they can enter.
+fn open_log(path: &Path) -> Result<File> {
+    let mut options = OpenOptions::new();
+    options.create(true).append(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+        if let Some(dir) = path.parent() {
+            crate::utils::dirs::ensure_private_dir(dir).map_err(CliError::ConfigError)?;
+        }
+        rotate_log(path)?;
+        let file = options.mode(0o600).open(path)?;
+        // Logs written before the mode was set may be readable by others
+        if file.metadata()?.permissions().mode() & 0o077 != 0 {
+            file.set_permissions(fs::Permissions::from_mode(0o600))?;
+        }
+        Ok(file)
+    }
+
+    #[cfg(not(unix))]
+    {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        rotate_log(path)?;
+        Ok(options.open(path)?)
+    }
+}
+
+/// Shift `daemon.log` to `daemon.log.1`, and so on, once it is too large
+///
+/// The oldest rotation is overwritten.
+fn rotate_log(path: &Path) -> Result<()> {
+    let len = fs::metadata(path)
+        .map(|metadata| metadata.len())
+        .unwrap_or(0);
+    if len < LOG_MAX_BYTES {
+        return Ok(());
+    }
+
+    let rotation = |n: usize| {
+        let mut name = path.as_os_str().to_owned();
+        name.push(format!(".{}", n));
+        PathBuf::from(name)
+    };
+    for n in (1..LOG_ROTATIONS).rev() {
+        if rotation(n).exists() {
+            fs::rename(rotation(n), rotation(n + 1))?;
+        }
+    }
+    fs::rename(path, rotation(1))?;
+    Ok(())
+}
+
+/// The last `count` lines of `content`
+pub fn last_lines(content: &str, count: usize) -> Vec<String> {
+    let lines: Vec<&str> = content.lines().collect();
+    lines[lines.len().saturating_sub(count)..]
+        .iter()
+        .map(|line| line.to_string())
+        .collect()
+}
 
 // =============================================================================
 // Daemon Manager
@@ -35,16 +173,217 @@ const DAEMON_POLL_INTERVAL_MS: u64 = 100;
 /// Returns Ok(()) if daemon is running (or was successfully started)
 /// Returns Err if daemon could not be started or reached
 pub fn ensure_daemon_running(config: &Config) -> Result<()> {
+    // Only a socket nothing listens on is replaced: not one that is someone
+    // else's (refused when connecting), nor a daemon that is slow to answer
+    if config.ipc_socket_path.exists() {
+        match IpcClient::new(config.clone()).ping() {
+            Ok(true) => return Ok(()),
+            Ok(false) => {
+                return Err(CliError::ProtocolError(
+                    "the daemon did not answer ping with pong".to_string(),
+                ));
+            }
+            // Nothing is listening, so the socket is stale
+            Err(CliError::DaemonNotRunning(_)) => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    prepare_socket_dir(config)?;
+
+    // Parallel commands on a cold system all get here; the first to take the
+    // lock starts the daemon and the rest find it running once they get it
+    let _lock = acquire_startup_lock(config)?;
     if is_daemon_running(config) {
         return Ok(());
     }
 
-    start_daemon(config)?;
-    wait_for_daemon_ready(config)?;
+    remove_stale_socket(config)?;
+    let mut launch = start_daemon(config)?;
+    wait_for_daemon_ready(config, &mut launch)?;
 
     Ok(())
 }
 
+/// Get ready to run the daemon in the foreground on the configured socket
+///
+/// Used by `browser daemon --foreground`, which then execs the daemon in
+/// place of the CLI: no new session, output to the terminal or service
+/// manager rather than the log, and this process's PID in the pidfile.
+/// Returns the arguments to run the daemon with.
+///
+/// If a service manager passed in a listening socket (`LISTEN_FDS`), it is
+/// left alone; the daemon inherits it, and `LISTEN_PID` still matches since
+/// exec keeps the PID.
+pub fn prepare_foreground(config: &Config, mut args: Vec<String>) -> Result<Vec<String>> {
+    if args
+        .iter()
+        .any(|arg| arg == "-s" || arg == "--socket" || arg.starts_with("--socket="))
+    {
+        return Err(CliError::InvalidArguments(
+            "--foreground runs on the configured socket; set socket_path or BROWSER_SOCKET_PATH instead of --socket".to_string(),
+        ));
+    }
+
+    if !socket_activated() {
+        if is_daemon_running(config) {
+            return Err(CliError::CommandFailed(format!(
+                "a daemon is already running on {}",
+                config.ipc_socket_path.display()
+            )));
+        }
+        prepare_socket_dir(config)?;
+        remove_stale_socket(config)?;
+    }
+
+    #[cfg(unix)]
+    fs::write(pidfile_path(config), format!("{}\n", std::process::id()))?;
+
+    let marker = activation_marker_path(config);
+    if socket_activated() {
+        // systemd names the fds after the socket unit unless told otherwise
+        let unit = std::env::var("LISTEN_FDNAMES")
+            .ok()
+            .and_then(|names| names.split(':').next().map(str::to_string))
+            .filter(|name| name.ends_with(".socket"))
+            .unwrap_or_else(|| format!("{}.socket", UNIT_NAME));
+        fs::write(&marker, format!("{}\n", unit))?;
+    } else {
+        let _ = fs::remove_file(&marker);
+    }
+
+    args.push("--socket".to_string());
+    args.push(config.ipc_socket_path.to_string_lossy().into_owned());
+    Ok(args)
+}
+
+/// Whether a service manager passed this process a listening socket
+///
+/// Follows systemd's protocol: `LISTEN_FDS` counts the sockets, from fd 3,
+/// and `LISTEN_PID` must be this process, not one the variables leaked from.
+pub fn socket_activated() -> bool {
+    let count = std::env::var("LISTEN_FDS")
+        .ok()
+        .and_then(|v| v.parse::<u32>().ok());
+    let pid = std::env::var("LISTEN_PID")
+        .ok()
+        .and_then(|v| v.parse::<u32>().ok());
+    count.is_some_and(|count| count >= 1) && pid == Some(std::process::id())
+}
+
+/// The systemd socket unit still listening for the daemon, if it was socket-activated
+///
+/// Trusts the marker only while `systemctl --user is-active` agrees, and
+/// removes it otherwise, so a unit that was stopped or uninstalled does not
+/// keep the CLI from managing the daemon itself.
+fn activating_unit(config: &Config) -> Option<String> {
+    let marker = activation_marker_path(config);
+    let unit = fs::read_to_string(&marker).ok()?.trim().to_string();
+    let active = !unit.is_empty()
+        && Command::new("systemctl")
+            .args(["--user", "is-active", "--quiet", &unit])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success());
+    if active {
+        Some(unit)
+    } else {
+        let _ = fs::remove_file(&marker);
+        None
+    }
+}
+
+/// Remove a socket nothing listens on, unless its daemon is still alive
+///
+/// A crashed daemon leaves its socket behind. If the pidfile names a live
+/// daemon for this socket, it is hung rather than gone, and is left alone.
+fn remove_stale_socket(config: &Config) -> Result<()> {
+    let socket = &config.ipc_socket_path;
+    if !socket.exists() {
+        return Ok(());
+    }
+
+    let pidfile = pidfile_path(config);
+    if let Some(pid) =
+        read_pidfile(&pidfile).filter(|&pid| process_alive(pid) && serves_socket(pid, config))
+    {
+        return Err(CliError::DaemonNotRunning(format!(
+            "the daemon (pid {}) is running but not accepting connections on {}; run `browser daemonctl restart`",
+            pid,
+            socket.display()
+        )));
+    }
+
+    eprintln!(
+        "Warning: removing stale socket {} left by a daemon that is no longer running",
+        socket.display()
+    );
+    fs::remove_file(socket)?;
+    let _ = fs::remove_file(pidfile);
+    Ok(())
+}
+
+/// Take the advisory startup lock, waiting while another process holds it
+///
+/// The lock is released when the returned file is dropped, or when the
+/// process exits. The lock file itself is left in place.
+fn acquire_startup_lock(config: &Config) -> Result<File> {
+    let path = lock_path(config);
+    let mut options = OpenOptions::new();
+    options.create(true).truncate(false).write(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let file = options.open(&path)?;
+
+    let mut backoff = Backoff::new(config);
+    loop {
+        match file.try_lock() {
+            Ok(()) => return Ok(file),
+            Err(TryLockError::WouldBlock) => {}
+            Err(TryLockError::Error(err)) => return Err(err.into()),
+        }
+        if !backoff.wait() {
+            return Err(CliError::DaemonNotRunning(format!(
+                "timed out after {}ms waiting for another process to start the daemon (lock {})",
+                config.startup_timeout_ms,
+                path.display()
+            )));
+        }
+    }
+}
+
+/// Waits between checks for a starting daemon, doubling up to a limit
+struct Backoff {
+    next: Duration,
+    deadline: Instant,
+}
+
+impl Backoff {
+    /// Start backing off from `startup_backoff_ms` until `startup_timeout_ms` passes
+    fn new(config: &Config) -> Self {
+        Self {
+            next: Duration::from_millis(config.startup_backoff_ms.max(1)),
+            deadline: Instant::now() + Duration::from_millis(config.startup_timeout_ms),
+        }
+    }
+
+    /// Sleep until the next check; false once the timeout has passed
+    fn wait(&mut self) -> bool {
+        let remaining = self.deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return false;
+        }
+        thread::sleep(self.next.min(remaining));
+        self.next = (self.next * 2).min(Duration::from_millis(DAEMON_STARTUP_BACKOFF_MAX_MS));
+        true
+    }
+}
+
 /// Check if daemon is running by attempting a ping
 fn is_daemon_running(config: &Config) -> bool {
     // Quick check: if socket doesn't exist, daemon is definitely not running
@@ -57,8 +396,15 @@ fn is_daemon_running(config: &Config) -> bool {
     client.ping().unwrap_or(false)
 }
 
+/// A daemon process started by this CLI
+struct Launch {
+    child: Child,
+    /// Log the daemon writes to, and its length before the daemon started
+    log: Option<(PathBuf, u64)>,
+}
+
 /// Start the daemon process
-fn start_daemon(config: &Config) -> Result<()> {
+fn start_daemon(config: &Config) -> Result<Launch> {
     let self_exe = std::env::current_exe().map_err(|e| {
         CliError::DaemonNotRunning(format!("failed to determine current executable: {}", e))
     })?;
@@ -70,70 +416,326 @@ fn start_daemon(config: &Config) -> Result<()> {
         config.ipc_socket_path.to_string_lossy().to_string(),
     ];
 
+    // Without a log the daemon still runs, its output is just lost
+    let log = log_path(config).and_then(|path| {
+        let file = open_log(&path)?;
+        let offset = file.metadata()?.len();
+        Ok((path, offset, file.try_clone()?, file))
+    });
+    let (log, stdout, stderr) = match log {
+        Ok((path, offset, stdout, stderr)) => (
+            Some((path, offset)),
+            Stdio::from(stdout),
+            Stdio::from(stderr),
+        ),
+        Err(_) => (None, Stdio::null(), Stdio::null()),
+    };
+
     // Spawn daemon as background process
     #[cfg(unix)]
     {
         // Use setsid to start the daemon in a new session (detached from terminal)
         // without exiting the parent process (CLI)
         // unsafe block for setsid
-        unsafe {
+        let child = unsafe {
             Command::new(&self_exe)
                 .args(&args)
                 .stdin(Stdio::null())
-                .stdout(Stdio::null())
-                .stderr(Stdio::null())
+                .stdout(stdout)
+                .stderr(stderr)
                 .pre_exec(|| {
                     // detaches the child from the terminal
                     libc::setsid();
                     Ok(())
                 })
                 .spawn()
-                .map_err(|e| {
-                    CliError::DaemonNotRunning(format!("failed to start daemon: {}", e))
-                })?;
-        }
+                .map_err(|e| CliError::DaemonNotRunning(format!("failed to start daemon: {}", e)))?
+        };
 
-        Ok(())
+        // `browser daemon` execs the daemon binary, so this is the daemon's PID
+        fs::write(pidfile_path(config), format!("{}\n", child.id()))?;
+        Ok(Launch { child, log })
     }
 
     #[cfg(windows)]
     {
         use std::os::windows::process::CommandExt;
-        Command::new(&self_exe)
+        let child = Command::new(&self_exe)
             .args(&args)
             .stdin(Stdio::null())
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
+            .stdout(stdout)
+            .stderr(stderr)
             .creation_flags(0x00000008) // DETACHED_PROCESS
             .spawn()
             .map_err(|e| CliError::DaemonNotRunning(format!("failed to start daemon: {}", e)))?;
 
-        Ok(())
+        fs::write(pidfile_path(config), format!("{}\n", child.id()))?;
+        Ok(Launch { child, log })
+    }
+}
+
+/// Create the directory the daemon will put its socket in
+///
+/// The default runtime directory is made private to the current user; a
+/// configured socket path's directory is only created if missing.
+fn prepare_socket_dir(config: &Config) -> Result<()> {
+    let Some(dir) = config.ipc_socket_path.parent() else {
+        return Ok(());
+    };
+
+    #[cfg(unix)]
+    if config.ipc_socket_path == crate::config::default_socket_path() {
+        return crate::utils::dirs::ensure_private_dir(dir).map_err(CliError::DaemonNotRunning);
     }
+
+    std::fs::create_dir_all(dir)?;
+    Ok(())
 }
 
 /// Wait for daemon to become ready (respond to ping)
-fn wait_for_daemon_ready(config: &Config) -> Result<()> {
-    let timeout = Duration::from_millis(DAEMON_STARTUP_TIMEOUT_MS);
-    let poll_interval = Duration::from_millis(DAEMON_POLL_INTERVAL_MS);
-    let start = Instant::now();
+///
+/// Fails as soon as the daemon process exits, rather than at the timeout.
+fn wait_for_daemon_ready(config: &Config, launch: &mut Launch) -> Result<()> {
+    let mut backoff = Backoff::new(config);
 
     loop {
-        // Check if we've exceeded timeout
-        if start.elapsed() > timeout {
-            return Err(CliError::DaemonNotRunning(
-                "daemon failed to start within timeout".to_string(),
-            ));
-        }
-
         // Try to ping
         if is_daemon_running(config) {
             return Ok(());
         }
 
-        // Wait before next attempt
+        if let Ok(Some(status)) = launch.child.try_wait() {
+            let _ = fs::remove_file(pidfile_path(config));
+            return Err(startup_failure(
+                &format!("daemon exited during startup ({})", status),
+                launch,
+            ));
+        }
+
+        // Wait before next attempt, unless we've exceeded the timeout
+        if !backoff.wait() {
+            return Err(startup_failure(
+                &format!(
+                    "daemon did not respond on {} within {}ms",
+                    config.ipc_socket_path.display(),
+                    config.startup_timeout_ms
+                ),
+                launch,
+            ));
+        }
+    }
+}
+
+/// Describe a failed start with what the daemon logged and likely causes
+fn startup_failure(reason: &str, launch: &Launch) -> CliError {
+    let mut message = reason.to_string();
+
+    match &launch.log {
+        Some((path, offset)) => {
+            // Only what this daemon wrote, not earlier runs
+            let output = fs::read(path)
+                .map(|content| content.get(*offset as usize..).unwrap_or_default().to_vec())
+                .unwrap_or_default();
+            let output = String::from_utf8_lossy(&output);
+            let lines = last_lines(&output, LOG_TAIL_LINES);
+            if lines.is_empty() {
+                message.push_str(&format!(
+                    "\n\nThe daemon wrote nothing to {}",
+                    path.display()
+                ));
+            } else {
+                message.push_str(&format!("\n\nLast lines of {}:", path.display()));
+                for line in &lines {
+                    message.push_str(&format!("\n  {}", line));
+                }
+            }
+            for hint in startup_hints(&output) {
+                message.push_str(&format!("\n\nHint: {}", hint));
+            }
+        }
+        None => message.push_str("\n\nThe daemon log could not be opened"),
+    }
+
+    CliError::DaemonNotRunning(message)
+}
+
+/// Likely causes of a failed start, recognised from the daemon's output
+fn startup_hints(output: &str) -> Vec<&'static str> {
+    let output = output.to_lowercase();
+    let mut hints = Vec::new();
+    if output.contains("failed to download") {
+        hints.push(
+            "the daemon binary could not be downloaded; check your network connection, or set plugins.daemon.path to a local copy",
+        );
+    }
+    if output.contains("eaddrinuse") || output.contains("address already in use") {
+        hints.push(
+            "the daemon's WebSocket port (9222 unless BROWSER_WS_PORT is set) is in use; stop the process holding it or set BROWSER_WS_PORT",
+        );
+    }
+    if output.contains("could not find a chrome") || output.contains("browser executable not found")
+    {
+        hints.push(
+            "no Chrome or Chromium browser was found; install one or set BROWSER_PATH to its executable",
+        );
+    }
+    hints
+}
+
+// =============================================================================
+// Stopping
+// =============================================================================
+
+/// How a running daemon was stopped
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stopped {
+    /// PID of the daemon, when known from it or its pidfile
+    pub pid: Option<u32>,
+    /// Signal that stopped it, if it did not shut down on request
+    pub signal: Option<&'static str>,
+}
+
+/// Stop the daemon on the configured socket
+///
+/// Asks the daemon to shut down and waits for it to exit. If it cannot be
+/// reached or does not exit in time, signals the PID it reported or the one
+/// in its pidfile: SIGTERM first, then SIGKILL. Returns `None` if no daemon
+/// was running.
+///
+/// A socket-activated daemon is left to systemd: while its socket unit
+/// listens, waiting for the daemon to stop would start a new one, and the
+/// socket is not ours to remove.
+pub fn stop_daemon(config: &Config) -> Result<Option<Stopped>> {
+    if let Some(unit) = activating_unit(config) {
+        let service = format!("{}.service", unit.trim_end_matches(".socket"));
+        return Err(CliError::CommandFailed(format!(
+            "the daemon on {} is socket-activated by systemd, which starts it again on the next connection; run `systemctl --user stop {} {}` to stop it, or `systemctl --user restart {}` to restart it",
+            config.ipc_socket_path.display(),
+            unit,
+            service,
+            service
+        )));
+    }
+
+    let pidfile = pidfile_path(config);
+    let client = IpcClient::new(config.clone());
+
+    let mut pid = None;
+    let mut reachable = false;
+    if config.ipc_socket_path.exists() {
+        pid = client.status().ok().map(|status| status.pid);
+        if client.shutdown().is_ok() {
+            reachable = true;
+            let timeout = Duration::from_millis(DAEMON_SHUTDOWN_TIMEOUT_MS);
+            if wait_for_exit(config, pid, timeout) {
+                let _ = fs::remove_file(&pidfile);
+                return Ok(Some(Stopped { pid, signal: None }));
+            }
+        }
+    }
+
+    let pid = pid.or_else(|| read_pidfile(&pidfile).filter(|&pid| serves_socket(pid, config)));
+
+    #[cfg(unix)]
+    if let Some(pid) = pid.filter(|&pid| process_alive(pid)) {
+        let signal = signal_daemon(config, pid)?;
+        let _ = fs::remove_file(&pidfile);
+        // A killed daemon leaves its socket behind
+        if config.ipc_socket_path.exists()
+            && crate::ipc::verify_socket(&config.ipc_socket_path).is_ok()
+        {
+            let _ = fs::remove_file(&config.ipc_socket_path);
+        }
+        return Ok(Some(Stopped {
+            pid: Some(pid),
+            signal: Some(signal),
+        }));
+    }
+
+    if reachable || is_daemon_running(config) {
+        return Err(CliError::CommandFailed(format!(
+            "daemon at {} did not stop and its PID is unknown",
+            config.ipc_socket_path.display()
+        )));
+    }
+
+    // Nothing is running; whatever pidfile is left is stale
+    let _ = fs::remove_file(&pidfile);
+    Ok(None)
+}
+
+/// Wait until the daemon stops answering and, if known, its process is gone
+///
+/// Waiting for the process too means its WebSocket port is free again
+/// before a restart.
+fn wait_for_exit(config: &Config, pid: Option<u32>, timeout: Duration) -> bool {
+    let poll_interval = Duration::from_millis(DAEMON_EXIT_POLL_INTERVAL_MS);
+    let start = Instant::now();
+
+    while start.elapsed() < timeout {
+        if !pid.is_some_and(process_alive) && !is_daemon_running(config) {
+            return true;
+        }
         thread::sleep(poll_interval);
     }
+    false
+}
+
+/// Send SIGTERM, then SIGKILL if the daemon outlives it; returns the signal that worked
+#[cfg(unix)]
+fn signal_daemon(config: &Config, pid: u32) -> Result<&'static str> {
+    let timeout = Duration::from_millis(DAEMON_SIGNAL_TIMEOUT_MS);
+    for (signal, name) in [(libc::SIGTERM, "SIGTERM"), (libc::SIGKILL, "SIGKILL")] {
+        // SAFETY: kill has no memory-safety preconditions
+        if unsafe { libc::kill(pid as libc::pid_t, signal) } != 0 {
+            let err = std::io::Error::last_os_error();
+            if err.raw_os_error() == Some(libc::ESRCH) {
+                return Ok(name);
+            }
+            return Err(CliError::CommandFailed(format!(
+                "failed to send {} to daemon (pid {}): {}",
+                name, pid, err
+            )));
+        }
+        if wait_for_exit(config, Some(pid), timeout) {
+            return Ok(name);
+        }
+    }
+    Err(CliError::CommandFailed(format!(
+        "daemon (pid {}) did not exit after SIGKILL",
+        pid
+    )))
+}
+
+/// Whether a process with this PID exists and we may signal it
+#[cfg(unix)]
+fn process_alive(pid: u32) -> bool {
+    // SAFETY: signal 0 only checks that the process exists and can be signalled
+    pid > 0 && unsafe { libc::kill(pid as libc::pid_t, 0) } == 0
+}
+
+/// PIDs are only used for signalling, which Windows does not support
+#[cfg(not(unix))]
+fn process_alive(_pid: u32) -> bool {
+    false
+}
+
+/// Whether the process with this PID is the daemon for the configured socket
+///
+/// A pidfile outlives a crashed daemon and its PID may since have been
+/// reused, so on Linux the process must have been started with the socket
+/// path as an argument. Elsewhere the pidfile is trusted.
+fn serves_socket(pid: u32, config: &Config) -> bool {
+    if !cfg!(target_os = "linux") {
+        return true;
+    }
+    let Ok(cmdline) = fs::read(format!("/proc/{}/cmdline", pid)) else {
+        return false;
+    };
+    let socket = config.ipc_socket_path.to_string_lossy();
+    cmdline
+        .split(|&byte| byte == 0)
+        .any(|arg| arg == socket.as_bytes())
 }
 
 // =============================================================================
@@ -153,4 +755,209 @@ mod tests {
         };
         assert!(!is_daemon_running(&config));
     }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "browser-daemon-test-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn last_lines_keeps_the_tail() {
+        let content = "one\ntwo\nthree\n";
+        assert_eq!(last_lines(content, 2), vec!["two", "three"]);
+        assert_eq!(last_lines(content, 10), vec!["one", "two", "three"]);
+        assert!(last_lines(content, 0).is_empty());
+    }
+
+    #[test]
+    fn rotate_log_shifts_large_logs() {
+        let dir = temp_dir("rotate");
+        let log = dir.join("daemon.log");
+        fs::write(&log, "small").unwrap();
+        rotate_log(&log).unwrap();
+        assert!(log.exists());
+
+        for n in 1..=LOG_ROTATIONS {
+            fs::write(dir.join(format!("daemon.log.{}", n)), n.to_string()).unwrap();
+        }
+        fs::write(&log, vec![b'x'; LOG_MAX_BYTES as usize]).unwrap();
+        rotate_log(&log).unwrap();
+
+        assert!(!log.exists());
+        assert_eq!(
+            fs::metadata(dir.join("daemon.log.1")).unwrap().len(),
+            LOG_MAX_BYTES
+        );
+        assert_eq!(fs::read_to_string(dir.join("daemon.log.2")).unwrap(), "1");
+        assert_eq!(fs::read_to_string(dir.join("daemon.log.3")).unwrap(), "2");
+        assert!(!dir.join("daemon.log.4").exists());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn open_log_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = temp_dir("log-mode").join("state");
+        let log = dir.join("daemon.log");
+        let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+
+        open_log(&log).unwrap();
+        assert_eq!(mode(&log), 0o600);
+        assert_eq!(mode(&dir), 0o700);
+
+        // A log left readable by an older version is restricted
+        fs::set_permissions(&log, fs::Permissions::from_mode(0o644)).unwrap();
+        open_log(&log).unwrap();
+        assert_eq!(mode(&log), 0o600);
+
+        let _ = fs::remove_dir_all(dir.parent().unwrap());
+    }
+
+    #[test]
+    fn startup_lock_is_exclusive_until_dropped() {
+        let dir = temp_dir("lock");
+        let config = Config {
+            ipc_socket_path: dir.join("daemon.sock"),
+            startup_timeout_ms: 100,
+            startup_backoff_ms: 10,
+            ..Default::default()
+        };
+
+        let held = acquire_startup_lock(&config).expect("first lock");
+        let err = acquire_startup_lock(&config).unwrap_err();
+        assert!(matches!(err, CliError::DaemonNotRunning(_)));
+        assert!(err.to_string().contains("another process"));
+
+        drop(held);
+        acquire_startup_lock(&config).expect("lock after release");
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn stop_daemon_ignores_stale_activation_marker() {
+        let dir = temp_dir("activated");
+        let config = Config {
+            ipc_socket_path: dir.join("daemon.sock"),
+            ..Default::default()
+        };
+        let marker = activation_marker_path(&config);
+
+        // No such unit is active, so the marker is left over and dropped
+        fs::write(&marker, "browser-daemon-test-missing.socket\n").unwrap();
+        assert_eq!(stop_daemon(&config).unwrap(), None);
+        assert!(!marker.exists());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn remove_stale_socket_spares_live_daemons() {
+        let dir = temp_dir("stale");
+        let config = Config {
+            ipc_socket_path: dir.join("daemon.sock"),
+            ..Default::default()
+        };
+        let bind =
+            || drop(std::os::unix::net::UnixListener::bind(&config.ipc_socket_path).unwrap());
+
+        // A live process started with the socket path, standing in for a hung daemon
+        bind();
+        let mut holder = Command::new("sh")
+            .args(["-c", "sleep 5; exit 0"])
+            .arg(&config.ipc_socket_path)
+            .spawn()
+            .unwrap();
+        fs::write(pidfile_path(&config), holder.id().to_string()).unwrap();
+        // spawn returns before exec has set up the new command line
+        while cfg!(target_os = "linux") && !serves_socket(holder.id(), &config) {
+            thread::sleep(Duration::from_millis(10));
+        }
+        let err = remove_stale_socket(&config).unwrap_err();
+        assert!(
+            err.to_string().contains("not accepting connections"),
+            "{}",
+            err
+        );
+        assert!(config.ipc_socket_path.exists());
+
+        holder.kill().unwrap();
+        holder.wait().unwrap();
+        remove_stale_socket(&config).expect("remove stale socket");
+        assert!(!config.ipc_socket_path.exists());
+        assert!(!pidfile_path(&config).exists());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn startup_hints_recognise_common_failures() {
+        let output = "Failed to download browser-daemon: timed out\n\
+                      Error: listen EADDRINUSE: address already in use :::9222\n\
+                      Error: Could not find a Chrome/Chromium browser.";
+        let hints = startup_hints(output);
+        assert_eq!(hints.len(), 3);
+        assert!(hints[0].contains("plugins.daemon.path"));
+        assert!(hints[1].contains("9222"));
+        assert!(hints[2].contains("BROWSER_PATH"));
+
+        assert!(startup_hints("browser-daemon started successfully").is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn wait_for_daemon_ready_reports_early_exit_with_log() {
+        let dir = temp_dir("early-exit");
+        let log = dir.join("daemon.log");
+        fs::write(&log, "output of an earlier run\n").unwrap();
+        let offset = fs::metadata(&log).unwrap().len();
+
+        let file = OpenOptions::new().append(true).open(&log).unwrap();
+        let child = Command::new("sh")
+            .args([
+                "-c",
+                "echo 'Error: listen EADDRINUSE: address already in use :::9222'; exit 3",
+            ])
+            .stdout(file)
+            .spawn()
+            .unwrap();
+        let mut launch = Launch {
+            child,
+            log: Some((log.clone(), offset)),
+        };
+        let config = Config {
+            ipc_socket_path: dir.join("daemon.sock"),
+            ..Default::default()
+        };
+
+        let started = Instant::now();
+        let err = wait_for_daemon_ready(&config, &mut launch).unwrap_err();
+        assert!(started.elapsed() < Duration::from_millis(config.startup_timeout_ms));
+
+        let message = err.to_string();
+        assert!(message.contains("exit status: 3"), "{}", message);
+        assert!(
+            message.contains("  Error: listen EADDRINUSE"),
+            "{}",
+            message
+        );
+        assert!(!message.contains("earlier run"), "{}", message);
+        assert!(
+            message.contains("Hint: the daemon's WebSocket port"),
+            "{}",
+            message
+        );
+
+        let _ = fs::remove_dir_all(dir);
+    }
 }
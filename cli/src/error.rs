@@ -36,6 +36,10 @@ pub enum CliError {
     #[error("invalid session: {0}")]
     InvalidSession(String),
 
+    /// Invalid or unreadable config file
+    #[error("config error: {0}")]
+    ConfigError(String),
+
     /// Protocol error (malformed message)
     #[error("protocol error: {0}")]
     ProtocolError(String),
@@ -50,6 +54,23 @@ pub enum CliError {
 }
 
 impl CliError {
+    /// Stable machine-readable name of this error, used in JSON output
+    pub fn kind(&self) -> &'static str {
+        match self {
+            CliError::DaemonNotRunning(_) => "daemon_not_running",
+            CliError::ConnectionFailed(_) => "connection_failed",
+            CliError::ConnectionTimeout => "connection_timeout",
+            CliError::CommandFailed(_) => "command_failed",
+            CliError::CommandTimeout => "command_timeout",
+            CliError::InvalidArguments(_) => "invalid_arguments",
+            CliError::InvalidSession(_) => "invalid_session",
+            CliError::ConfigError(_) => "config_error",
+            CliError::ProtocolError(_) => "protocol_error",
+            CliError::IoError(_) => "io_error",
+            CliError::SerializationError(_) => "serialization_error",
+        }
+    }
+
     /// Get the exit code for this error
     pub fn exit_code(&self) -> i32 {
         match self {
@@ -59,12 +80,32 @@ impl CliError {
             CliError::InvalidArguments(_) => 64,   // EX_USAGE
             CliError::InvalidSession(_) => 65,     // EX_DATAERR
             CliError::ProtocolError(_) => 76,      // EX_PROTOCOL
+            CliError::ConfigError(_) => 78,        // EX_CONFIG
             CliError::IoError(_) => 74,            // EX_IOERR
             CliError::SerializationError(_) => 65, // EX_DATAERR
         }
     }
 }
 
+/// A failed invocation, with the ID of the daemon command that failed if any
+#[derive(Debug)]
+pub struct Failure {
+    pub error: CliError,
+    pub command_id: Option<String>,
+    /// The output already reports the failure, so it is not printed again
+    pub reported: bool,
+}
+
+impl From<CliError> for Failure {
+    fn from(error: CliError) -> Self {
+        Self {
+            error,
+            command_id: None,
+            reported: false,
+        }
+    }
+}
+
 // =============================================================================
 // Tests
 // =============================================================================
@@ -120,4 +161,23 @@ mod tests {
         let err = CliError::ProtocolError("test".to_string());
         assert_eq!(err.exit_code(), 76);
     }
+
+    #[test]
+    fn config_error_returns_exit_code_78() {
+        let err = CliError::ConfigError("test".to_string());
+        assert_eq!(err.exit_code(), 78);
+    }
+
+    #[test]
+    fn kind_is_snake_case_variant_name() {
+        assert_eq!(
+            CliError::DaemonNotRunning("test".to_string()).kind(),
+            "daemon_not_running"
+        );
+        assert_eq!(CliError::ConnectionTimeout.kind(), "connection_timeout");
+        assert_eq!(
+            CliError::IoError(std::io::Error::other("test")).kind(),
+            "io_error"
+        );
+    }
 }
@@ -0,0 +1,718 @@
+//! Async IPC client over tokio, for many commands in flight at once
+//!
+//! Keeps one connection to the daemon open and matches each response to its
+//! command by ID, so concurrent callers share the connection. Ping and cancel
+//! replies carry no command ID; the daemon answers them as soon as they
+//! arrive, so they are matched in the order they were sent.
+//!
+//! The one-shot CLI sends one command at a time with the blocking
+//! `IpcClient`; this client is for modes that keep running, such as a REPL,
+//! a gateway or batches.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::UnixStream;
+use tokio::net::unix::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::sync::{OnceCell, oneshot};
+use tokio::task::JoinHandle;
+
+use super::{
+    IpcClient, MESSAGE_DELIMITER, check_versions, deserialize_message, lock, serialize_message,
+    unsupported,
+};
+use crate::config::Config;
+use crate::error::{CliError, Result};
+use crate::types::{
+    CancelAck, CancelPayload, Command, CommandId, CommandResponse, IpcMessage, IpcMessageType,
+    PongData,
+};
+
+/// ID the daemon puts in error responses it cannot tie to a command
+const UNKNOWN_ID: &str = "unknown";
+
+/// Where the reader task delivers a reply
+type Reply = oneshot::Sender<Result<IpcMessage>>;
+
+/// Requests waiting for a reply on one connection
+#[derive(Default)]
+struct Waiting {
+    /// Commands, by ID
+    commands: HashMap<CommandId, Reply>,
+    /// Pings and cancels, in the order they were sent
+    control: VecDeque<Reply>,
+    /// Why the connection stopped, once it has
+    closed: Option<String>,
+}
+
+impl Waiting {
+    /// Mark the connection closed and fail everything still waiting on it
+    fn close(&mut self, reason: String) {
+        for (_, reply) in self.commands.drain() {
+            let _ = reply.send(Err(CliError::ConnectionFailed(reason.clone())));
+        }
+        for reply in self.control.drain(..) {
+            let _ = reply.send(Err(CliError::ConnectionFailed(reason.clone())));
+        }
+        self.closed = Some(reason);
+    }
+}
+
+/// One open connection and the task reading replies from it
+struct Connection {
+    writer: tokio::sync::Mutex<OwnedWriteHalf>,
+    waiting: Arc<Mutex<Waiting>>,
+    reader: JoinHandle<()>,
+}
+
+impl Connection {
+    fn open(stream: UnixStream) -> Self {
+        let (reader, writer) = stream.into_split();
+        let waiting = Arc::new(Mutex::new(Waiting::default()));
+        Self {
+            writer: tokio::sync::Mutex::new(writer),
+            waiting: Arc::clone(&waiting),
+            reader: tokio::spawn(read_replies(reader, waiting)),
+        }
+    }
+
+    fn is_open(&self) -> bool {
+        lock(&self.waiting).closed.is_none()
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
+
+/// Async IPC client sharing one daemon connection between concurrent commands
+///
+/// Like `IpcClient`, it pings the daemon to compare protocol versions before
+/// the first command. If the daemon closes the connection, as daemons that
+/// predate keep-alive do after each reply, the next request reconnects.
+pub struct AsyncIpcClient {
+    config: Config,
+    /// Start the daemon when there is none to connect to
+    autostart: bool,
+    connection: tokio::sync::Mutex<Option<Arc<Connection>>>,
+    /// Set once protocol versions have been compared
+    versions_checked: OnceCell<()>,
+}
+
+// Nothing in the one-shot CLI sends concurrent commands yet
+#[allow(dead_code)]
+impl AsyncIpcClient {
+    pub fn new(config: Config) -> Self {
+        Self {
+            config,
+            autostart: false,
+            connection: tokio::sync::Mutex::new(None),
+            versions_checked: OnceCell::new(),
+        }
+    }
+
+    /// Start the daemon on first use if it is not running
+    pub fn with_autostart(mut self) -> Self {
+        self.autostart = true;
+        self
+    }
+
+    /// Configuration this client was created with
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub async fn ping(&self) -> Result<bool> {
+        let response = self.control(IpcMessageType::Ping, None).await?;
+        Ok(matches!(response.message_type, IpcMessageType::Pong))
+    }
+
+    /// Ask the daemon for its versions and those of its connected extensions
+    ///
+    /// Returns `None` for daemons that predate the version handshake.
+    pub async fn versions(&self) -> Result<Option<PongData>> {
+        let response = self.control(IpcMessageType::Ping, None).await?;
+        if !matches!(response.message_type, IpcMessageType::Pong) {
+            return Err(CliError::ProtocolError(
+                "unexpected reply to ping".to_string(),
+            ));
+        }
+
+        match response.payload {
+            Some(payload) if !payload.is_null() => Ok(Some(serde_json::from_value(payload)?)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Ask the daemon to cancel a command, queued or in flight
+    ///
+    /// Returns whether the daemon still had the command to cancel.
+    pub async fn cancel(&self, command_id: &str) -> Result<bool> {
+        let payload = CancelPayload {
+            command_id: command_id.to_string(),
+        };
+        let response = self
+            .control(IpcMessageType::Cancel, Some(serde_json::to_value(payload)?))
+            .await?;
+        if !matches!(response.message_type, IpcMessageType::CancelAck) {
+            return Err(unsupported("cancel"));
+        }
+
+        let payload = response
+            .payload
+            .ok_or_else(|| CliError::ProtocolError("missing cancel_ack payload".to_string()))?;
+        let ack: CancelAck = serde_json::from_value(payload)?;
+        Ok(ack.cancelled)
+    }
+
+    /// Send a command and wait for its response
+    ///
+    /// Other commands may be sent while this one waits; each command ID can
+    /// only be in flight once.
+    pub async fn send_command(&self, command: Command) -> Result<CommandResponse> {
+        self.versions_checked
+            .get_or_try_init(|| async {
+                if let Some(pong) = self.versions().await? {
+                    check_versions(&pong, &command.session_id, self.config.version_mismatch)?;
+                }
+                Ok::<(), CliError>(())
+            })
+            .await?;
+
+        let command_id = command.id.clone();
+        let message = IpcMessage {
+            message_type: IpcMessageType::Command,
+            payload: Some(serde_json::to_value(command)?),
+            keep_alive: true,
+        };
+        let timeout = Duration::from_millis(self.config.command_timeout_ms);
+        let response = self
+            .request(message, Some(command_id), timeout, CliError::CommandTimeout)
+            .await?;
+
+        if !matches!(response.message_type, IpcMessageType::Response) {
+            return Err(CliError::ProtocolError(
+                "unexpected response type".to_string(),
+            ));
+        }
+
+        let payload = response
+            .payload
+            .ok_or_else(|| CliError::ProtocolError("missing response payload".to_string()))?;
+        Ok(serde_json::from_value(payload)?)
+    }
+}
+
+impl AsyncIpcClient {
+    /// Send a control message and wait for the reply
+    ///
+    /// Control messages are answered immediately, so the wait is bounded by
+    /// the connection timeout.
+    async fn control(
+        &self,
+        message_type: IpcMessageType,
+        payload: Option<serde_json::Value>,
+    ) -> Result<IpcMessage> {
+        let message = IpcMessage {
+            message_type,
+            payload,
+            keep_alive: true,
+        };
+        let timeout = Duration::from_millis(self.config.connection_timeout_ms);
+        self.request(message, None, timeout, CliError::ConnectionTimeout)
+            .await
+    }
+
+    /// Send `message` and wait up to `timeout` for the reply
+    ///
+    /// Replies to commands are matched by `command_id`, and others in order.
+    async fn request(
+        &self,
+        message: IpcMessage,
+        command_id: Option<CommandId>,
+        timeout: Duration,
+        on_timeout: CliError,
+    ) -> Result<IpcMessage> {
+        let connection = self.connection().await?;
+        let (sender, receiver) = oneshot::channel();
+        let bytes = serialize_message(&message)?;
+
+        let exchange = async {
+            // Holding the writer while queueing keeps control replies in send order
+            let mut writer = connection.writer.lock().await;
+            {
+                let mut waiting = lock(&connection.waiting);
+                if let Some(reason) = &waiting.closed {
+                    return Err(CliError::ConnectionFailed(reason.clone()));
+                }
+                match &command_id {
+                    Some(id) if waiting.commands.contains_key(id) => {
+                        return Err(CliError::InvalidArguments(format!(
+                            "command {} is already in flight",
+                            id
+                        )));
+                    }
+                    Some(id) => {
+                        waiting.commands.insert(id.clone(), sender);
+                    }
+                    None => waiting.control.push_back(sender),
+                }
+            }
+            if let Err(err) = writer.write_all(&bytes).await {
+                // A partly written message leaves the connection unusable
+                lock(&connection.waiting).close(err.to_string());
+            }
+            drop(writer);
+
+            receiver.await.unwrap_or_else(|_| {
+                Err(CliError::ConnectionFailed(
+                    "the connection to the daemon was dropped".to_string(),
+                ))
+            })
+        };
+
+        match tokio::time::timeout(timeout, exchange).await {
+            Ok(result) => result,
+            Err(_) => {
+                // A late response is then dropped. A control reply still
+                // arrives in its turn, so its dropped receiver stays queued.
+                if let Some(id) = &command_id {
+                    lock(&connection.waiting).commands.remove(id);
+                }
+                Err(on_timeout)
+            }
+        }
+    }
+
+    /// The open connection, connecting first if there is none
+    async fn connection(&self) -> Result<Arc<Connection>> {
+        let mut slot = self.connection.lock().await;
+        if let Some(connection) = slot.as_ref()
+            && connection.is_open()
+        {
+            return Ok(Arc::clone(connection));
+        }
+
+        let connection = Arc::new(Connection::open(self.connect().await?));
+        *slot = Some(Arc::clone(&connection));
+        Ok(connection)
+    }
+
+    /// Connect as `IpcClient` does, with the same socket checks and autostart
+    async fn connect(&self) -> Result<UnixStream> {
+        let mut client = IpcClient::new(self.config.clone());
+        if self.autostart {
+            client = client.with_autostart();
+        }
+        let stream = tokio::task::spawn_blocking(move || client.connect())
+            .await
+            .map_err(|err| CliError::ConnectionFailed(err.to_string()))??;
+
+        stream.set_read_timeout(None)?;
+        stream.set_write_timeout(None)?;
+        stream.set_nonblocking(true)?;
+        Ok(UnixStream::from_std(stream)?)
+    }
+}
+
+/// Read replies until the connection closes, handing each to its waiter
+async fn read_replies(reader: OwnedReadHalf, waiting: Arc<Mutex<Waiting>>) {
+    let mut reader = BufReader::new(reader);
+    let mut buf = Vec::new();
+    let reason = loop {
+        buf.clear();
+        match reader.read_until(MESSAGE_DELIMITER, &mut buf).await {
+            Ok(0) => break "the daemon closed the connection".to_string(),
+            Ok(_) => {}
+            Err(err) => break err.to_string(),
+        }
+        if buf.last().copied() == Some(MESSAGE_DELIMITER) {
+            buf.pop();
+        }
+        let message = match deserialize_message(&buf) {
+            Ok(message) => message,
+            Err(err) => break format!("invalid message from the daemon: {}", err),
+        };
+
+        // A reply without keep-alive means the daemon is closing the
+        // connection; mark it closed before the waiter can send again on it
+        let keep_alive = message.keep_alive;
+        let mut pending = lock(&waiting);
+        deliver(&mut pending, message);
+        if !keep_alive {
+            pending.close("the daemon closed the connection".to_string());
+            return;
+        }
+    };
+    lock(&waiting).close(reason);
+}
+
+/// Hand a reply to the request it answers
+///
+/// Responses go to the command with their ID. Daemons that do not know a
+/// control message answer it with an error response they cannot tie to a
+/// command, so those go to the oldest control message, like other replies.
+/// Responses to commands that timed out are dropped.
+fn deliver(waiting: &mut Waiting, message: IpcMessage) {
+    let reply = match message.message_type {
+        IpcMessageType::Response => {
+            let id = message
+                .payload
+                .as_ref()
+                .and_then(|payload| payload.get("id"))
+                .and_then(|id| id.as_str());
+            match id {
+                None | Some(UNKNOWN_ID) => waiting.control.pop_front(),
+                Some(id) => waiting.commands.remove(id),
+            }
+        }
+        _ => waiting.control.pop_front(),
+    };
+    if let Some(reply) = reply {
+        let _ = reply.send(Ok(message));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc::unix_tests::{
+        answer_handshake, bind_listener, cleanup_socket, unique_socket_path,
+    };
+    use crate::types::{CommandType, PROTOCOL_VERSION};
+    use serde_json::json;
+    use std::io::{BufRead, BufReader, Write};
+    use std::thread;
+
+    fn command(id: &str, session_id: &str) -> Command {
+        Command {
+            id: id.to_string(),
+            session_id: session_id.to_string(),
+            profile: None,
+            command_type: CommandType::Snapshot,
+            params: None,
+            timestamp: "2026-01-01T00:00:00Z".to_string(),
+            deadline: None,
+        }
+    }
+
+    fn config(socket_path: &std::path::Path) -> Config {
+        Config {
+            ipc_socket_path: socket_path.to_path_buf(),
+            connection_timeout_ms: 1000,
+            command_timeout_ms: 1000,
+            ..Default::default()
+        }
+    }
+
+    fn read_json(reader: &mut BufReader<std::os::unix::net::UnixStream>) -> serde_json::Value {
+        let mut buf = String::new();
+        reader.read_line(&mut buf).expect("read line");
+        serde_json::from_str(buf.trim_end()).expect("parse json")
+    }
+
+    fn write_json(
+        reader: &mut BufReader<std::os::unix::net::UnixStream>,
+        value: serde_json::Value,
+    ) {
+        reader
+            .get_mut()
+            .write_all(format!("{}\n", value).as_bytes())
+            .expect("write message");
+    }
+
+    #[tokio::test]
+    async fn connect_returns_error_when_missing() {
+        let socket_path = unique_socket_path("async-missing");
+        let client = AsyncIpcClient::new(config(&socket_path));
+
+        let result = client.connect().await;
+        assert!(matches!(result, Err(CliError::DaemonNotRunning(_))));
+    }
+
+    #[tokio::test]
+    async fn connect_connects_to_listener() {
+        let socket_path = unique_socket_path("async-listener");
+        cleanup_socket(&socket_path);
+
+        let listener = bind_listener(&socket_path);
+        let handle = thread::spawn(move || {
+            let (_stream, _addr) = listener.accept().expect("accept connection");
+        });
+
+        let client = AsyncIpcClient::new(config(&socket_path));
+        assert!(client.connect().await.is_ok());
+
+        handle.join().expect("listener thread");
+        cleanup_socket(&socket_path);
+    }
+
+    #[tokio::test]
+    async fn ping_sends_ping_and_expects_pong() {
+        let socket_path = unique_socket_path("async-ping");
+        cleanup_socket(&socket_path);
+
+        let listener = bind_listener(&socket_path);
+        let handle = thread::spawn(move || {
+            let (stream, _addr) = listener.accept().expect("accept connection");
+            let mut reader = BufReader::new(stream);
+            let incoming = read_json(&mut reader);
+            assert_eq!(incoming["type"], "ping");
+            assert!(incoming["payload"].is_null());
+            assert_eq!(incoming["keepAlive"], true);
+            write_json(&mut reader, json!({"type": "pong", "payload": null}));
+        });
+
+        let client = AsyncIpcClient::new(config(&socket_path));
+        assert!(client.ping().await.expect("ping result"));
+
+        handle.join().expect("listener thread");
+        cleanup_socket(&socket_path);
+    }
+
+    #[tokio::test]
+    async fn send_command_round_trips_response() {
+        let socket_path = unique_socket_path("async-command");
+        cleanup_socket(&socket_path);
+
+        let listener = bind_listener(&socket_path);
+        let handle = thread::spawn(move || {
+            let (stream, _addr) = listener.accept().expect("accept connection");
+            let mut reader = BufReader::new(stream);
+            answer_handshake(&mut reader);
+            let incoming = read_json(&mut reader);
+            assert_eq!(incoming["type"], "command");
+            assert_eq!(incoming["payload"]["sessionId"], "session-1");
+            assert_eq!(incoming["payload"]["id"], "cmd-1");
+            write_json(
+                &mut reader,
+                json!({
+                    "type": "response",
+                    "payload": {"id": "cmd-1", "success": true},
+                    "keepAlive": true
+                }),
+            );
+        });
+
+        let client = AsyncIpcClient::new(config(&socket_path));
+        let response = client
+            .send_command(command("cmd-1", "session-1"))
+            .await
+            .expect("send command");
+        assert_eq!(response.id, "cmd-1");
+        assert!(response.success);
+
+        handle.join().expect("listener thread");
+        cleanup_socket(&socket_path);
+    }
+
+    #[tokio::test]
+    async fn concurrent_commands_share_a_connection_and_match_responses_by_id() {
+        let socket_path = unique_socket_path("async-concurrent");
+        cleanup_socket(&socket_path);
+
+        let listener = bind_listener(&socket_path);
+        let handle = thread::spawn(move || {
+            // A second connection would never be accepted, so this would time out
+            let (stream, _addr) = listener.accept().expect("accept connection");
+            let mut reader = BufReader::new(stream);
+            answer_handshake(&mut reader);
+            let mut ids: Vec<String> = (0..3)
+                .map(|_| {
+                    let incoming = read_json(&mut reader);
+                    incoming["payload"]["id"].as_str().expect("id").to_string()
+                })
+                .collect();
+            ids.sort();
+            assert_eq!(ids, ["cmd-a", "cmd-b", "cmd-c"]);
+
+            // Answer in reverse, as commands in other sessions finish
+            for id in ids.iter().rev() {
+                write_json(
+                    &mut reader,
+                    json!({
+                        "type": "response",
+                        "payload": {"id": id, "success": true, "data": {"from": id}},
+                        "keepAlive": true
+                    }),
+                );
+            }
+        });
+
+        let client = AsyncIpcClient::new(config(&socket_path));
+        let (a, b, c) = tokio::join!(
+            client.send_command(command("cmd-a", "default")),
+            client.send_command(command("cmd-b", "work")),
+            client.send_command(command("cmd-c", "other")),
+        );
+        for (response, id) in [(a, "cmd-a"), (b, "cmd-b"), (c, "cmd-c")] {
+            let response = response.expect("send command");
+            assert_eq!(response.id, id);
+            assert_eq!(response.data, Some(json!({"from": id})));
+        }
+
+        handle.join().expect("listener thread");
+        cleanup_socket(&socket_path);
+    }
+
+    #[tokio::test]
+    async fn send_command_times_out_when_daemon_does_not_answer() {
+        let socket_path = unique_socket_path("async-silent");
+        cleanup_socket(&socket_path);
+
+        let listener = bind_listener(&socket_path);
+        let handle = thread::spawn(move || {
+            let (stream, _addr) = listener.accept().expect("accept connection");
+            let mut reader = BufReader::new(stream);
+            answer_handshake(&mut reader);
+            read_json(&mut reader);
+            // Hold the connection open without answering
+            thread::sleep(Duration::from_millis(200));
+        });
+
+        let client = AsyncIpcClient::new(Config {
+            command_timeout_ms: 50,
+            ..config(&socket_path)
+        });
+        let result = client.send_command(command("cmd-1", "default")).await;
+        assert!(matches!(result, Err(CliError::CommandTimeout)));
+
+        handle.join().expect("listener thread");
+        cleanup_socket(&socket_path);
+    }
+
+    #[tokio::test]
+    async fn ping_times_out_as_connection_timeout() {
+        let socket_path = unique_socket_path("async-silent-ping");
+        cleanup_socket(&socket_path);
+
+        let listener = bind_listener(&socket_path);
+        let handle = thread::spawn(move || {
+            let (_stream, _addr) = listener.accept().expect("accept connection");
+            thread::sleep(Duration::from_millis(200));
+        });
+
+        let client = AsyncIpcClient::new(Config {
+            connection_timeout_ms: 50,
+            ..config(&socket_path)
+        });
+        let result = client.ping().await;
+        assert!(matches!(result, Err(CliError::ConnectionTimeout)));
+
+        handle.join().expect("listener thread");
+        cleanup_socket(&socket_path);
+    }
+
+    #[tokio::test]
+    async fn cancel_is_answered_on_the_command_connection() {
+        let socket_path = unique_socket_path("async-cancel");
+        cleanup_socket(&socket_path);
+
+        let listener = bind_listener(&socket_path);
+        let handle = thread::spawn(move || {
+            let (stream, _addr) = listener.accept().expect("accept connection");
+            let mut reader = BufReader::new(stream);
+            answer_handshake(&mut reader);
+            let incoming = read_json(&mut reader);
+            assert_eq!(incoming["type"], "command");
+
+            let incoming = read_json(&mut reader);
+            assert_eq!(
+                incoming,
+                json!({"type": "cancel", "payload": {"commandId": "cmd-1"}, "keepAlive": true})
+            );
+            write_json(
+                &mut reader,
+                json!({
+                    "type": "cancel_ack",
+                    "payload": {"commandId": "cmd-1", "cancelled": true},
+                    "keepAlive": true
+                }),
+            );
+            write_json(
+                &mut reader,
+                json!({
+                    "type": "response",
+                    "payload": {"id": "cmd-1", "success": false, "error": "Command cancelled"},
+                    "keepAlive": true
+                }),
+            );
+        });
+
+        let client = AsyncIpcClient::new(config(&socket_path));
+        let cancel = async {
+            // Let the command go out first
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            client.cancel("cmd-1").await
+        };
+        let (response, cancelled) =
+            tokio::join!(client.send_command(command("cmd-1", "default")), cancel);
+        assert!(cancelled.expect("cancel"));
+        let response = response.expect("send command");
+        assert_eq!(response.error.as_deref(), Some("Command cancelled"));
+
+        handle.join().expect("listener thread");
+        cleanup_socket(&socket_path);
+    }
+
+    #[tokio::test]
+    async fn cancel_reports_daemons_that_do_not_support_it() {
+        let socket_path = unique_socket_path("async-old-cancel");
+        cleanup_socket(&socket_path);
+
+        let listener = bind_listener(&socket_path);
+        let handle = thread::spawn(move || {
+            let (stream, _addr) = listener.accept().expect("accept connection");
+            let mut reader = BufReader::new(stream);
+            read_json(&mut reader);
+            write_json(
+                &mut reader,
+                json!({
+                    "type": "response",
+                    "payload": {"id": "unknown", "success": false, "error": "Unknown message type: cancel"}
+                }),
+            );
+        });
+
+        let client = AsyncIpcClient::new(config(&socket_path));
+        let result = client.cancel("cmd-1").await;
+        assert!(matches!(result, Err(CliError::ProtocolError(_))));
+
+        handle.join().expect("listener thread");
+        cleanup_socket(&socket_path);
+    }
+
+    #[tokio::test]
+    async fn reconnects_when_the_daemon_closes_the_connection() {
+        let socket_path = unique_socket_path("async-no-keep-alive");
+        cleanup_socket(&socket_path);
+
+        let listener = bind_listener(&socket_path);
+        let handle = thread::spawn(move || {
+            // Daemons that predate keep-alive answer once, without echoing it
+            for _ in 0..2 {
+                let (stream, _addr) = listener.accept().expect("accept connection");
+                let mut reader = BufReader::new(stream);
+                read_json(&mut reader);
+                write_json(
+                    &mut reader,
+                    json!({
+                        "type": "pong",
+                        "payload": {"protocolVersion": PROTOCOL_VERSION, "version": "0.2.4", "extensions": []}
+                    }),
+                );
+            }
+        });
+
+        let client = AsyncIpcClient::new(config(&socket_path));
+        assert!(client.ping().await.expect("first ping"));
+        assert!(client.ping().await.expect("second ping"));
+
+        handle.join().expect("listener thread");
+        cleanup_socket(&socket_path);
+    }
+}
@@ -0,0 +1,1424 @@
+//! IPC Client for CLI <-> Daemon communication
+//!
+//! Implements the client side of the newline-delimited JSON protocol
+//! over Unix sockets (or named pipes on Windows). `IpcClient` blocks and
+//! sends one message at a time; on Unix, `AsyncIpcClient` shares one
+//! connection between many commands in flight.
+//!
+//! Protocol reference: packages/daemon/src/ipc-server.ts
+
+#[cfg(unix)]
+mod async_client;
+
+#[cfg(unix)]
+pub use async_client::AsyncIpcClient;
+
+use std::cell::{Cell, RefCell};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+#[cfg(unix)]
+use std::sync::mpsc;
+#[cfg(unix)]
+use std::thread;
+
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+
+#[cfg(windows)]
+use std::fs::OpenOptions;
+#[cfg(windows)]
+use std::os::windows::fs::OpenOptionsExt;
+
+use crate::config::{Config, VersionMismatch};
+use crate::error::{CliError, Result};
+use crate::types::{
+    CancelAck, CancelPayload, Command, CommandId, CommandResponse, DaemonStatusData, IpcMessage,
+    IpcMessageType, PROTOCOL_VERSION, PongData, protocol_compatible,
+};
+
+// =============================================================================
+// Platform-specific stream types
+// =============================================================================
+
+#[cfg(unix)]
+type IpcStream = UnixStream;
+
+#[cfg(windows)]
+type IpcStream = std::fs::File;
+
+// =============================================================================
+// Constants
+// =============================================================================
+
+/// Message delimiter for framing (newline-delimited JSON)
+const MESSAGE_DELIMITER: u8 = b'\n';
+
+/// Time spent talking to the daemon, summed over every command sent
+///
+/// The version handshake before the first command counts towards neither.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IpcTimings {
+    /// Connecting to the socket, including starting the daemon
+    pub connect: Duration,
+    /// Sending commands and waiting for their responses
+    pub daemon: Duration,
+}
+
+/// IPC client for communicating with browser-daemon
+///
+/// Pings and commands ask the daemon to keep the connection open, so the
+/// messages one invocation sends share a connection. Daemons that predate
+/// this close it after each reply, and the client reconnects.
+///
+/// Before the first command, the client pings the daemon to compare protocol
+/// versions, and warns or refuses as `version_mismatch` says.
+pub struct IpcClient {
+    config: Config,
+    timings: Cell<IpcTimings>,
+    /// Connection kept open by the daemon for the next message
+    connection: RefCell<Option<IpcStream>>,
+    /// Start the daemon when there is none to connect to
+    autostart: bool,
+    /// Whether protocol versions have been compared yet
+    versions_checked: Cell<bool>,
+    /// Command sent and not yet answered, for a `Canceller` to cancel
+    in_flight: Arc<Mutex<Option<CommandId>>>,
+}
+
+impl IpcClient {
+    pub fn new(config: Config) -> Self {
+        Self {
+            config,
+            timings: Cell::new(IpcTimings::default()),
+            connection: RefCell::new(None),
+            autostart: false,
+            versions_checked: Cell::new(false),
+            in_flight: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Start the daemon on first use if it is not running
+    ///
+    /// Connecting is attempted first, so a running daemon costs no extra
+    /// round trip.
+    pub fn with_autostart(mut self) -> Self {
+        self.autostart = true;
+        self
+    }
+
+    /// Configuration this client was created with
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Time spent in `send_command` so far
+    pub fn timings(&self) -> IpcTimings {
+        self.timings.get()
+    }
+
+    pub fn ping(&self) -> Result<bool> {
+        let response = self.exchange(IpcMessageType::Ping, true)?;
+        Ok(matches!(response.message_type, IpcMessageType::Pong))
+    }
+
+    /// Ask the daemon for its versions and those of its connected extensions
+    ///
+    /// Returns `None` for daemons that predate the version handshake.
+    pub fn versions(&self) -> Result<Option<PongData>> {
+        let response = self.exchange(IpcMessageType::Ping, true)?;
+        if !matches!(response.message_type, IpcMessageType::Pong) {
+            return Err(CliError::ProtocolError(
+                "unexpected reply to ping".to_string(),
+            ));
+        }
+
+        match response.payload {
+            Some(payload) if !payload.is_null() => Ok(Some(serde_json::from_value(payload)?)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Ask the daemon for its PID, uptime, version, socket and session count
+    pub fn status(&self) -> Result<DaemonStatusData> {
+        let response = self.exchange(IpcMessageType::Status, false)?;
+        if !matches!(response.message_type, IpcMessageType::Status) {
+            return Err(unsupported("status"));
+        }
+
+        let payload = response
+            .payload
+            .ok_or_else(|| CliError::ProtocolError("missing status payload".to_string()))?;
+        Ok(serde_json::from_value(payload)?)
+    }
+
+    /// Ask the daemon to shut down gracefully
+    ///
+    /// Returns once the daemon has acknowledged; it keeps running while
+    /// in-flight commands finish and browsers are closed.
+    pub fn shutdown(&self) -> Result<()> {
+        let response = self.exchange(IpcMessageType::Shutdown, false)?;
+        if !matches!(response.message_type, IpcMessageType::Shutdown) {
+            return Err(unsupported("shutdown"));
+        }
+        Ok(())
+    }
+
+    /// Ask the daemon to cancel a command, queued or in flight
+    ///
+    /// The command's own connection is busy waiting for its response, so
+    /// this is sent on a new one. Returns whether the daemon still had the
+    /// command to cancel.
+    pub fn cancel(&self, command_id: &str) -> Result<bool> {
+        let timeout = Duration::from_millis(self.config.connection_timeout_ms);
+        let payload = CancelPayload {
+            command_id: command_id.to_string(),
+        };
+        let message = IpcMessage {
+            message_type: IpcMessageType::Cancel,
+            payload: Some(serde_json::to_value(payload)?),
+            keep_alive: false,
+        };
+        let mut stream = self.connect()?;
+        let response = round_trip_on(&mut stream, &message, timeout, CliError::ConnectionTimeout)?;
+        if !matches!(response.message_type, IpcMessageType::CancelAck) {
+            return Err(unsupported("cancel"));
+        }
+
+        let payload = response
+            .payload
+            .ok_or_else(|| CliError::ProtocolError("missing cancel_ack payload".to_string()))?;
+        let ack: CancelAck = serde_json::from_value(payload)?;
+        Ok(ack.cancelled)
+    }
+
+    /// Handle that cancels whichever command this client is waiting on
+    ///
+    /// It can be moved to another thread, such as a Ctrl-C handler.
+    pub fn canceller(&self) -> Canceller {
+        Canceller {
+            config: self.config.clone(),
+            in_flight: Arc::clone(&self.in_flight),
+        }
+    }
+
+    /// Send a control message without a payload and read the reply
+    ///
+    /// Control messages are answered immediately, so the whole exchange is
+    /// bounded by the connection timeout.
+    fn exchange(&self, message_type: IpcMessageType, keep_alive: bool) -> Result<IpcMessage> {
+        let timeout = Duration::from_millis(self.config.connection_timeout_ms);
+        let message = IpcMessage {
+            message_type,
+            payload: None,
+            keep_alive,
+        };
+        let (response, _) = self.round_trip(&message, timeout, CliError::ConnectionTimeout)?;
+        Ok(response)
+    }
+
+    /// Send a command to the daemon and wait for response
+    pub fn send_command(&self, command: Command) -> Result<CommandResponse> {
+        let command_timeout = Duration::from_millis(self.config.command_timeout_ms);
+
+        if !self.versions_checked.replace(true)
+            && let Some(pong) = self.versions()?
+        {
+            check_versions(&pong, &command.session_id, self.config.version_mismatch)?;
+        }
+
+        let command_id = command.id.clone();
+        let payload = serde_json::to_value(command)?;
+        let message = IpcMessage {
+            message_type: IpcMessageType::Command,
+            payload: Some(payload),
+            keep_alive: true,
+        };
+        *lock(&self.in_flight) = Some(command_id);
+        let result = self.round_trip(&message, command_timeout, CliError::CommandTimeout);
+        *lock(&self.in_flight) = None;
+        let (response, connected) = result?;
+
+        let mut timings = self.timings.get();
+        timings.daemon += connected.elapsed();
+        self.timings.set(timings);
+
+        if !matches!(response.message_type, IpcMessageType::Response) {
+            return Err(CliError::ProtocolError(
+                "unexpected response type".to_string(),
+            ));
+        }
+
+        let payload = response
+            .payload
+            .ok_or_else(|| CliError::ProtocolError("missing response payload".to_string()))?;
+
+        let command_response: CommandResponse = serde_json::from_value(payload)?;
+        Ok(command_response)
+    }
+
+    /// Send `message` and read the reply within `timeout`
+    ///
+    /// Uses the kept-open connection if there is one. Returns the reply and
+    /// when the connection was ready, and keeps the connection if the daemon
+    /// said it would. Time spent connecting, including for the version
+    /// handshake, is added to the connect timing.
+    fn round_trip(
+        &self,
+        message: &IpcMessage,
+        timeout: Duration,
+        on_timeout: CliError,
+    ) -> Result<(IpcMessage, Instant)> {
+        let started = Instant::now();
+        let kept = self.connection.borrow_mut().take();
+        let mut stream = match kept {
+            Some(stream) => stream,
+            None => self.connect()?,
+        };
+        let connected = Instant::now();
+        let mut timings = self.timings.get();
+        timings.connect += connected - started;
+        self.timings.set(timings);
+
+        let response = round_trip_on(&mut stream, message, timeout, on_timeout)?;
+        if message.keep_alive && response.keep_alive {
+            *self.connection.borrow_mut() = Some(stream);
+        }
+        Ok((response, connected))
+    }
+
+    /// Connect to the daemon, starting it first if allowed and needed
+    fn connect(&self) -> Result<IpcStream> {
+        let timeout = Duration::from_millis(self.config.connection_timeout_ms);
+        let socket_path = self.config.ipc_socket_path.as_path();
+        match connect_to_daemon(socket_path, timeout) {
+            Err(CliError::DaemonNotRunning(_)) if self.autostart => {
+                crate::daemon::ensure_daemon_running(&self.config)?;
+                connect_to_daemon(socket_path, timeout)
+            }
+            Err(CliError::DaemonNotRunning(reason)) if !self.config.autostart => {
+                Err(CliError::DaemonNotRunning(format!(
+                    "{} (autostart is off; start it with `browser daemon --foreground` or its service)",
+                    reason
+                )))
+            }
+            result => result,
+        }
+    }
+}
+
+/// Cancels the command an `IpcClient` is waiting on, from another thread
+pub struct Canceller {
+    config: Config,
+    in_flight: Arc<Mutex<Option<CommandId>>>,
+}
+
+impl Canceller {
+    /// Ask the daemon to cancel the command in flight, if there is one
+    ///
+    /// Returns the command's ID and whether the daemon still had it, or
+    /// `None` if no command was waiting for a response.
+    pub fn cancel(&self) -> Result<Option<(CommandId, bool)>> {
+        let Some(command_id) = lock(&self.in_flight).clone() else {
+            return Ok(None);
+        };
+        let cancelled = IpcClient::new(self.config.clone()).cancel(&command_id)?;
+        Ok(Some((command_id, cancelled)))
+    }
+}
+
+#[cfg(unix)]
+fn connect_to_daemon(socket_path: &Path, timeout: Duration) -> Result<IpcStream> {
+    if !socket_path.exists() {
+        return Err(CliError::DaemonNotRunning(format!(
+            "socket not found at {}",
+            socket_path.display()
+        )));
+    }
+
+    verify_socket(socket_path)?;
+
+    // `UnixStream::connect` has no timeout and blocks while the daemon's
+    // backlog is full, so connect on a thread and stop waiting for it
+    let (sender, receiver) = mpsc::channel();
+    let path = socket_path.to_path_buf();
+    thread::spawn(move || {
+        let _ = sender.send(UnixStream::connect(path));
+    });
+    let stream = match receiver.recv_timeout(timeout) {
+        Ok(stream) => stream.map_err(|err| match err.kind() {
+            // The socket outlived its daemon, or was removed after the check above
+            std::io::ErrorKind::ConnectionRefused | std::io::ErrorKind::NotFound => {
+                CliError::DaemonNotRunning(format!(
+                    "nothing is listening on {}",
+                    socket_path.display()
+                ))
+            }
+            _ => CliError::ConnectionFailed(err.to_string()),
+        })?,
+        Err(_) => return Err(CliError::ConnectionTimeout),
+    };
+
+    verify_peer(&stream, socket_path)?;
+
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+
+    Ok(stream)
+}
+
+// =============================================================================
+// Socket Ownership
+// =============================================================================
+
+/// Check that the socket belongs to the current user and only they can use it
+///
+/// Otherwise another local user could be listening in the daemon's place, or
+/// be able to drive the user's logged-in browser through it.
+#[cfg(unix)]
+pub(crate) fn verify_socket(socket_path: &Path) -> Result<()> {
+    use std::os::unix::fs::{FileTypeExt, MetadataExt};
+
+    let metadata = std::fs::metadata(socket_path)?;
+    if !metadata.file_type().is_socket() {
+        return Err(CliError::ConnectionFailed(format!(
+            "{} is not a socket",
+            socket_path.display()
+        )));
+    }
+
+    let uid = current_uid();
+    if metadata.uid() != uid {
+        return Err(CliError::ConnectionFailed(format!(
+            "refusing to connect: {} is owned by uid {}, not uid {}",
+            socket_path.display(),
+            metadata.uid(),
+            uid
+        )));
+    }
+    if metadata.mode() & 0o022 != 0 {
+        return Err(CliError::ConnectionFailed(format!(
+            "refusing to connect: other users can write to {} (mode {:o}); run `chmod 600 {}`",
+            socket_path.display(),
+            metadata.mode() & 0o777,
+            socket_path.display()
+        )));
+    }
+    Ok(())
+}
+
+/// Check that the process on the other end runs as the current user
+#[cfg(unix)]
+fn verify_peer(stream: &UnixStream, socket_path: &Path) -> Result<()> {
+    let uid = current_uid();
+    match peer_uid(stream)? {
+        Some(peer) if peer != uid => Err(CliError::ConnectionFailed(format!(
+            "refusing to talk to the daemon at {}: it runs as uid {}, not uid {}",
+            socket_path.display(),
+            peer,
+            uid
+        ))),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(unix)]
+fn current_uid() -> u32 {
+    // SAFETY: geteuid has no preconditions and cannot fail
+    unsafe { libc::geteuid() }
+}
+
+/// UID of the peer process, where the platform reports it
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn peer_uid(stream: &UnixStream) -> Result<Option<u32>> {
+    use std::os::unix::io::AsRawFd;
+
+    let mut cred = libc::ucred {
+        pid: 0,
+        uid: 0,
+        gid: 0,
+    };
+    let mut len = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+    // SAFETY: `cred` and `len` are valid for writes and sized for SO_PEERCRED
+    let ret = unsafe {
+        libc::getsockopt(
+            stream.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            (&mut cred as *mut libc::ucred).cast(),
+            &mut len,
+        )
+    };
+    if ret != 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    Ok(Some(cred.uid))
+}
+
+/// UID of the peer process, where the platform reports it
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd",
+    target_os = "dragonfly"
+))]
+fn peer_uid(stream: &UnixStream) -> Result<Option<u32>> {
+    use std::os::unix::io::AsRawFd;
+
+    let mut uid: libc::uid_t = 0;
+    let mut gid: libc::gid_t = 0;
+    // SAFETY: `uid` and `gid` are valid for writes
+    if unsafe { libc::getpeereid(stream.as_raw_fd(), &mut uid, &mut gid) } != 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    Ok(Some(uid))
+}
+
+/// UID of the peer process, where the platform reports it
+#[cfg(all(
+    unix,
+    not(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "freebsd",
+        target_os = "openbsd",
+        target_os = "netbsd",
+        target_os = "dragonfly"
+    ))
+))]
+fn peer_uid(_stream: &UnixStream) -> Result<Option<u32>> {
+    Ok(None)
+}
+
+#[cfg(windows)]
+fn connect_to_daemon(pipe_path: &Path, _timeout: Duration) -> Result<IpcStream> {
+    // Windows named pipe path format: \\.\pipe\pipe-name
+    // The config should provide the full pipe path
+    let pipe_path_str = pipe_path.to_string_lossy();
+
+    // Convert Unix-style socket path to Windows named pipe if needed
+    let pipe_name = if pipe_path_str.starts_with(r"\\.\pipe\") {
+        pipe_path_str.to_string()
+    } else {
+        // Extract the filename and create a named pipe path
+        let name = pipe_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("browser-daemon");
+        format!(r"\\.\pipe\{}", name)
+    };
+
+    let stream = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .custom_flags(0) // FILE_FLAG_OVERLAPPED can be added if async needed
+        .open(&pipe_name)
+        .map_err(|err| {
+            if err.kind() == std::io::ErrorKind::NotFound {
+                CliError::DaemonNotRunning(format!("pipe not found at {}", pipe_name))
+            } else {
+                CliError::ConnectionFailed(err.to_string())
+            }
+        })?;
+
+    Ok(stream)
+}
+
+/// Send `message` on `stream` and read the reply within `timeout`
+fn round_trip_on(
+    stream: &mut IpcStream,
+    message: &IpcMessage,
+    timeout: Duration,
+    on_timeout: CliError,
+) -> Result<IpcMessage> {
+    #[cfg(unix)]
+    {
+        stream.set_read_timeout(Some(timeout))?;
+        stream.set_write_timeout(Some(timeout))?;
+    }
+    #[cfg(windows)]
+    let _ = timeout;
+
+    let bytes = serialize_message(message)?;
+    let response_bytes = send_bytes(stream, &bytes)
+        .and_then(|()| read_message(stream))
+        .map_err(|err| timed_out_as(err, on_timeout))?;
+    deserialize_message(&response_bytes)
+}
+
+/// Lock a mutex, ignoring poisoning: the guarded value is always valid
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Error for a daemon too old to understand a control message
+///
+/// Such daemons reply with an "Unknown message type" command response.
+fn unsupported(message_type: &str) -> CliError {
+    CliError::ProtocolError(format!(
+        "the daemon does not support `{}`; it predates this CLI",
+        message_type
+    ))
+}
+
+/// Report a socket timeout as `timeout` instead of an I/O error
+fn timed_out_as(err: CliError, timeout: CliError) -> CliError {
+    match err {
+        CliError::IoError(io)
+            if matches!(
+                io.kind(),
+                std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+            ) =>
+        {
+            timeout
+        }
+        other => other,
+    }
+}
+
+fn serialize_message(message: &IpcMessage) -> Result<Vec<u8>> {
+    let mut json = serde_json::to_vec(message)?;
+    json.push(MESSAGE_DELIMITER);
+    Ok(json)
+}
+fn deserialize_message(data: &[u8]) -> Result<IpcMessage> {
+    let message: IpcMessage = serde_json::from_slice(data)?;
+    Ok(message)
+}
+
+// =============================================================================
+fn send_bytes<W: Write>(stream: &mut W, data: &[u8]) -> Result<()> {
+    stream.write_all(data)?;
+    stream.flush()?;
+    Ok(())
+}
+
+fn read_message<R: Read>(stream: &mut R) -> Result<Vec<u8>> {
+    let mut reader = BufReader::new(stream);
+    let mut buf = Vec::new();
+    let bytes = reader.read_until(MESSAGE_DELIMITER, &mut buf)?;
+
+    if bytes == 0 {
+        return Err(CliError::ProtocolError("empty response".to_string()));
+    }
+
+    if buf.last().copied() != Some(MESSAGE_DELIMITER) {
+        return Err(CliError::ProtocolError(
+            "missing message delimiter".to_string(),
+        ));
+    }
+
+    buf.pop();
+    Ok(buf)
+}
+
+/// Compare the protocol versions in `pong` with this CLI's
+///
+/// Only the extension serving `session` matters to a command. Components
+/// that predate the version handshake are not checked.
+fn check_versions(pong: &PongData, session: &str, policy: VersionMismatch) -> Result<()> {
+    let mut mismatches = Vec::new();
+    if !protocol_compatible(&pong.protocol_version) {
+        mismatches.push(format!(
+            "the daemon (v{}) speaks protocol {} but this CLI (v{}) speaks {}; \
+             run `browser daemonctl restart` to start a daemon matching the CLI",
+            pong.version,
+            pong.protocol_version,
+            env!("CARGO_PKG_VERSION"),
+            PROTOCOL_VERSION
+        ));
+    }
+    for extension in pong
+        .extensions
+        .iter()
+        .filter(|extension| extension.session == session || extension.session_id == session)
+    {
+        if let Some(protocol) = &extension.protocol_version
+            && !protocol_compatible(protocol)
+        {
+            mismatches.push(format!(
+                "the extension in session {} (v{}) speaks protocol {} but this CLI speaks {}; \
+                 update the browser extension",
+                extension.session,
+                extension.version.as_deref().unwrap_or("unknown"),
+                protocol,
+                PROTOCOL_VERSION
+            ));
+        }
+    }
+
+    for message in mismatches {
+        match policy {
+            VersionMismatch::Warn => eprintln!("Warning: {}", message),
+            VersionMismatch::Refuse => {
+                return Err(CliError::ProtocolError(format!(
+                    "{} (set version_mismatch = \"warn\" to send commands anyway)",
+                    message
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn create_client() -> Result<IpcClient> {
+    let config = crate::config::load_config(None)?;
+    Ok(IpcClient::new(config))
+}
+pub fn create_client_with_config(config: Config) -> IpcClient {
+    IpcClient::new(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn serialize_message_appends_newline_delimiter() {
+        let message = IpcMessage {
+            message_type: IpcMessageType::Command,
+            payload: Some(json!({"id": "cmd-1"})),
+            keep_alive: false,
+        };
+
+        let bytes = serialize_message(&message).expect("serialize message");
+
+        assert!(!bytes.is_empty());
+        assert_eq!(bytes.last().copied(), Some(MESSAGE_DELIMITER));
+
+        let mut trimmed = bytes.clone();
+        trimmed.pop();
+        let parsed: serde_json::Value = serde_json::from_slice(&trimmed).expect("parse json");
+        assert_eq!(
+            parsed,
+            json!({"type": "command", "payload": {"id": "cmd-1"}})
+        );
+    }
+
+    #[test]
+    fn deserialize_message_round_trips_json() {
+        let raw = br#"{"type":"pong","payload":null}"#;
+        let message = deserialize_message(raw).expect("deserialize message");
+
+        let value = serde_json::to_value(&message).expect("message to value");
+        assert_eq!(value, json!({"type": "pong", "payload": null}));
+    }
+
+    #[test]
+    fn check_versions_compares_protocol_majors() {
+        let pong = |daemon: &str, extension: &str| PongData {
+            protocol_version: daemon.to_string(),
+            version: "0.2.4".to_string(),
+            extensions: vec![crate::types::ExtensionVersion {
+                session: "work".to_string(),
+                session_id: "session-1".to_string(),
+                version: Some("0.1.1".to_string()),
+                protocol_version: Some(extension.to_string()),
+            }],
+        };
+
+        // Minor versions may differ
+        assert!(check_versions(&pong("1.0", "1.7"), "work", VersionMismatch::Refuse).is_ok());
+
+        let err = check_versions(&pong("2.0", "1.1"), "work", VersionMismatch::Refuse).unwrap_err();
+        assert!(matches!(err, CliError::ProtocolError(_)));
+        assert!(
+            err.to_string()
+                .contains("the daemon (v0.2.4) speaks protocol 2.0")
+        );
+        assert!(check_versions(&pong("2.0", "1.1"), "work", VersionMismatch::Warn).is_ok());
+
+        // Only the extension serving the command's session is checked
+        let err =
+            check_versions(&pong("1.1", "2.0"), "session-1", VersionMismatch::Refuse).unwrap_err();
+        assert!(err.to_string().contains("extension in session work"));
+        assert!(check_versions(&pong("1.1", "2.0"), "other", VersionMismatch::Refuse).is_ok());
+    }
+
+    #[test]
+    fn create_client_returns_client_with_default_config() {
+        let client = create_client().expect("load config");
+        // Just verify that it creates a client without panicking
+        assert_eq!(client.config.connection_timeout_ms, 5000);
+        assert_eq!(client.config.command_timeout_ms, 30000);
+    }
+}
+
+#[cfg(all(test, unix))]
+mod unix_tests {
+    use super::*;
+    use serde_json::json;
+    use std::fs;
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::PathBuf;
+    use std::thread;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn read_message_reads_until_delimiter() {
+        let (mut writer, mut reader) = UnixStream::pair().expect("create unix pair");
+        let payload = b"{\"type\":\"ping\",\"payload\":null}\n";
+
+        let handle = thread::spawn(move || {
+            writer.write_all(payload).expect("write payload");
+            writer.flush().expect("flush payload");
+        });
+
+        let read = read_message(&mut reader).expect("read message");
+        handle.join().expect("writer thread");
+
+        assert_eq!(read, b"{\"type\":\"ping\",\"payload\":null}");
+    }
+
+    #[test]
+    fn connect_to_daemon_returns_error_when_missing() {
+        let socket_path = unique_socket_path("missing");
+        let result = connect_to_daemon(&socket_path, Duration::from_millis(50));
+
+        assert!(matches!(result, Err(CliError::DaemonNotRunning(_))));
+    }
+
+    #[test]
+    fn connect_to_daemon_reports_stale_socket_as_not_running() {
+        let socket_path = unique_socket_path("stale");
+        cleanup_socket(&socket_path);
+        drop(bind_listener(&socket_path));
+        assert!(socket_path.exists());
+
+        let result = connect_to_daemon(&socket_path, Duration::from_millis(50));
+        assert!(matches!(result, Err(CliError::DaemonNotRunning(_))));
+
+        cleanup_socket(&socket_path);
+    }
+
+    #[test]
+    fn connect_to_daemon_connects_to_listener() {
+        let socket_path = unique_socket_path("listener");
+        cleanup_socket(&socket_path);
+
+        let listener = bind_listener(&socket_path);
+
+        let handle = thread::spawn(move || {
+            let (_stream, _addr) = listener.accept().expect("accept connection");
+        });
+
+        let result = connect_to_daemon(&socket_path, Duration::from_millis(50));
+        assert!(result.is_ok());
+
+        handle.join().expect("listener thread");
+        cleanup_socket(&socket_path);
+    }
+
+    #[test]
+    fn ping_sends_ping_and_expects_pong() {
+        let socket_path = unique_socket_path("ping");
+        cleanup_socket(&socket_path);
+
+        let listener = bind_listener(&socket_path);
+
+        let handle = thread::spawn(move || {
+            let (stream, _addr) = listener.accept().expect("accept connection");
+            let mut reader = BufReader::new(stream);
+            let mut buf = String::new();
+            reader.read_line(&mut buf).expect("read line");
+
+            let incoming: serde_json::Value =
+                serde_json::from_str(buf.trim_end()).expect("parse json");
+            assert_eq!(incoming["type"], "ping");
+            assert!(incoming.get("payload").unwrap().is_null());
+
+            let response = json!({"type": "pong", "payload": null});
+            let response_bytes = serde_json::to_vec(&response).expect("serialize response");
+            let mut stream = reader.into_inner();
+            stream.write_all(&response_bytes).expect("write response");
+            stream.write_all(b"\n").expect("write delimiter");
+        });
+
+        let config = Config {
+            ipc_socket_path: socket_path.clone(),
+            default_session: "default".to_string(),
+            connection_timeout_ms: 100,
+            command_timeout_ms: 100,
+            ..Default::default()
+        };
+        let client = IpcClient::new(config);
+        let result = client.ping().expect("ping result");
+        assert!(result);
+
+        handle.join().expect("listener thread");
+        cleanup_socket(&socket_path);
+    }
+
+    #[test]
+    fn send_command_round_trips_response() {
+        let socket_path = unique_socket_path("command");
+        cleanup_socket(&socket_path);
+
+        let listener = bind_listener(&socket_path);
+
+        let handle = thread::spawn(move || {
+            let (stream, _addr) = listener.accept().expect("accept connection");
+            let mut reader = BufReader::new(stream);
+            answer_handshake(&mut reader);
+            let mut buf = String::new();
+            reader.read_line(&mut buf).expect("read line");
+
+            let incoming: serde_json::Value =
+                serde_json::from_str(buf.trim_end()).expect("parse json");
+            assert_eq!(incoming["type"], "command");
+            let payload = incoming.get("payload").expect("payload");
+            assert_eq!(payload["sessionId"], "session-1");
+            assert_eq!(payload["type"], "navigate");
+            assert_eq!(payload["id"], "cmd-1");
+
+            let response = json!({
+                "type": "response",
+                "payload": {"id": "cmd-1", "success": true}
+            });
+            let response_bytes = serde_json::to_vec(&response).expect("serialize response");
+            let mut stream = reader.into_inner();
+            stream.write_all(&response_bytes).expect("write response");
+            stream.write_all(b"\n").expect("write delimiter");
+        });
+
+        let config = Config {
+            ipc_socket_path: socket_path.clone(),
+            default_session: "default".to_string(),
+            connection_timeout_ms: 100,
+            command_timeout_ms: 100,
+            ..Default::default()
+        };
+        let client = IpcClient::new(config);
+        let command = Command {
+            id: "cmd-1".to_string(),
+            session_id: "session-1".to_string(),
+            profile: None,
+            command_type: crate::types::CommandType::Navigate,
+            params: Some(json!({"url": "https://example.com"})),
+            timestamp: "2026-01-01T00:00:00Z".to_string(),
+            deadline: None,
+        };
+
+        let response = client.send_command(command).expect("send command");
+        assert_eq!(response.id, "cmd-1");
+        assert!(response.success);
+
+        handle.join().expect("listener thread");
+        cleanup_socket(&socket_path);
+    }
+
+    #[test]
+    fn send_command_times_out_when_daemon_does_not_answer() {
+        let socket_path = unique_socket_path("silent");
+        cleanup_socket(&socket_path);
+
+        let listener = bind_listener(&socket_path);
+        let handle = thread::spawn(move || {
+            let (stream, _addr) = listener.accept().expect("accept connection");
+            let mut reader = BufReader::new(stream);
+            answer_handshake(&mut reader);
+            let mut buf = String::new();
+            reader.read_line(&mut buf).expect("read line");
+            let incoming: serde_json::Value =
+                serde_json::from_str(buf.trim_end()).expect("parse json");
+            assert_eq!(incoming["payload"]["deadline"], "2026-01-01T00:00:00.05Z");
+            // Hold the connection open without answering
+            thread::sleep(Duration::from_millis(200));
+        });
+
+        let config = Config {
+            ipc_socket_path: socket_path.clone(),
+            connection_timeout_ms: 100,
+            command_timeout_ms: 50,
+            ..Default::default()
+        };
+        let client = IpcClient::new(config);
+        let command = Command {
+            id: "cmd-1".to_string(),
+            session_id: "default".to_string(),
+            profile: None,
+            command_type: crate::types::CommandType::Snapshot,
+            params: None,
+            timestamp: "2026-01-01T00:00:00Z".to_string(),
+            deadline: Some("2026-01-01T00:00:00.05Z".to_string()),
+        };
+
+        let result = client.send_command(command);
+        assert!(matches!(result, Err(CliError::CommandTimeout)));
+
+        handle.join().expect("listener thread");
+        cleanup_socket(&socket_path);
+    }
+
+    #[test]
+    fn ping_times_out_as_connection_timeout() {
+        let socket_path = unique_socket_path("silent-ping");
+        cleanup_socket(&socket_path);
+
+        let listener = bind_listener(&socket_path);
+        let handle = thread::spawn(move || {
+            let (_stream, _addr) = listener.accept().expect("accept connection");
+            thread::sleep(Duration::from_millis(200));
+        });
+
+        let config = Config {
+            ipc_socket_path: socket_path.clone(),
+            connection_timeout_ms: 50,
+            ..Default::default()
+        };
+        let result = IpcClient::new(config).ping();
+        assert!(matches!(result, Err(CliError::ConnectionTimeout)));
+
+        handle.join().expect("listener thread");
+        cleanup_socket(&socket_path);
+    }
+
+    #[test]
+    fn status_reads_daemon_state() {
+        let socket_path = unique_socket_path("status");
+        cleanup_socket(&socket_path);
+
+        let listener = bind_listener(&socket_path);
+        let reply_path = socket_path.to_string_lossy().into_owned();
+        let handle = thread::spawn(move || {
+            let (stream, _addr) = listener.accept().expect("accept connection");
+            let mut reader = BufReader::new(stream);
+            let mut buf = String::new();
+            reader.read_line(&mut buf).expect("read line");
+            let incoming: serde_json::Value =
+                serde_json::from_str(buf.trim_end()).expect("parse json");
+            assert_eq!(incoming, json!({"type": "status", "payload": null}));
+
+            let response = json!({
+                "type": "status",
+                "payload": {
+                    "pid": 4242,
+                    "uptimeMs": 90000,
+                    "version": "0.2.4",
+                    "socket": reply_path,
+                    "sessions": 2
+                }
+            });
+            let mut stream = reader.into_inner();
+            stream
+                .write_all(format!("{}\n", response).as_bytes())
+                .expect("write response");
+        });
+
+        let config = Config {
+            ipc_socket_path: socket_path.clone(),
+            connection_timeout_ms: 100,
+            ..Default::default()
+        };
+        let status = IpcClient::new(config).status().expect("status");
+        assert_eq!(status.pid, 4242);
+        assert_eq!(status.uptime_ms, 90000);
+        assert_eq!(status.sessions, 2);
+
+        handle.join().expect("listener thread");
+        cleanup_socket(&socket_path);
+    }
+
+    #[test]
+    fn shutdown_reports_daemons_that_do_not_support_it() {
+        let socket_path = unique_socket_path("old-daemon");
+        cleanup_socket(&socket_path);
+
+        let listener = bind_listener(&socket_path);
+        let handle = thread::spawn(move || {
+            let (stream, _addr) = listener.accept().expect("accept connection");
+            let mut reader = BufReader::new(stream);
+            let mut buf = String::new();
+            reader.read_line(&mut buf).expect("read line");
+
+            let response = json!({
+                "type": "response",
+                "payload": {"id": "unknown", "success": false, "error": "Unknown message type: shutdown"}
+            });
+            let mut stream = reader.into_inner();
+            stream
+                .write_all(format!("{}\n", response).as_bytes())
+                .expect("write response");
+        });
+
+        let config = Config {
+            ipc_socket_path: socket_path.clone(),
+            connection_timeout_ms: 100,
+            ..Default::default()
+        };
+        let result = IpcClient::new(config).shutdown();
+        assert!(matches!(result, Err(CliError::ProtocolError(_))));
+
+        handle.join().expect("listener thread");
+        cleanup_socket(&socket_path);
+    }
+
+    #[test]
+    fn cancel_sends_command_id_and_reads_ack() {
+        let socket_path = unique_socket_path("cancel");
+        cleanup_socket(&socket_path);
+
+        let listener = bind_listener(&socket_path);
+        let handle = thread::spawn(move || {
+            let (stream, _addr) = listener.accept().expect("accept connection");
+            let mut reader = BufReader::new(stream);
+            let mut buf = String::new();
+            reader.read_line(&mut buf).expect("read line");
+            let incoming: serde_json::Value =
+                serde_json::from_str(buf.trim_end()).expect("parse json");
+            assert_eq!(
+                incoming,
+                json!({"type": "cancel", "payload": {"commandId": "cmd-1"}})
+            );
+
+            let response = json!({
+                "type": "cancel_ack",
+                "payload": {"commandId": "cmd-1", "cancelled": false}
+            });
+            let mut stream = reader.into_inner();
+            stream
+                .write_all(format!("{}\n", response).as_bytes())
+                .expect("write response");
+        });
+
+        let config = Config {
+            ipc_socket_path: socket_path.clone(),
+            connection_timeout_ms: 100,
+            ..Default::default()
+        };
+        let cancelled = IpcClient::new(config).cancel("cmd-1").expect("cancel");
+        assert!(!cancelled);
+
+        handle.join().expect("listener thread");
+        cleanup_socket(&socket_path);
+    }
+
+    #[test]
+    fn canceller_cancels_the_command_in_flight_on_a_second_connection() {
+        let socket_path = unique_socket_path("cancel-in-flight");
+        cleanup_socket(&socket_path);
+
+        let listener = bind_listener(&socket_path);
+        let handle = thread::spawn(move || {
+            let (command_stream, _addr) = listener.accept().expect("accept command connection");
+            let mut command_reader = BufReader::new(command_stream);
+            answer_handshake(&mut command_reader);
+            let mut buf = String::new();
+            command_reader.read_line(&mut buf).expect("read command");
+
+            // The command is left unanswered until it is cancelled
+            let (stream, _addr) = listener.accept().expect("accept cancel connection");
+            let mut reader = BufReader::new(stream);
+            let mut buf = String::new();
+            reader.read_line(&mut buf).expect("read cancel");
+            let incoming: serde_json::Value =
+                serde_json::from_str(buf.trim_end()).expect("parse json");
+            assert_eq!(incoming["type"], "cancel");
+            assert_eq!(incoming["payload"]["commandId"], "cmd-1");
+            let ack = json!({
+                "type": "cancel_ack",
+                "payload": {"commandId": "cmd-1", "cancelled": true}
+            });
+            reader
+                .get_mut()
+                .write_all(format!("{}\n", ack).as_bytes())
+                .expect("write ack");
+
+            let response = json!({
+                "type": "response",
+                "payload": {"id": "cmd-1", "success": false, "error": "Command cancelled"}
+            });
+            command_reader
+                .get_mut()
+                .write_all(format!("{}\n", response).as_bytes())
+                .expect("write response");
+        });
+
+        let config = Config {
+            ipc_socket_path: socket_path.clone(),
+            connection_timeout_ms: 1000,
+            command_timeout_ms: 2000,
+            ..Default::default()
+        };
+        let client = IpcClient::new(config);
+        let canceller = client.canceller();
+        assert!(canceller.cancel().expect("nothing in flight").is_none());
+
+        let sender = thread::spawn(move || {
+            let command = Command {
+                id: "cmd-1".to_string(),
+                session_id: "default".to_string(),
+                profile: None,
+                command_type: crate::types::CommandType::Snapshot,
+                params: None,
+                timestamp: "2026-01-01T00:00:00Z".to_string(),
+                deadline: None,
+            };
+            client.send_command(command)
+        });
+
+        let deadline = Instant::now() + Duration::from_secs(2);
+        let cancelled = loop {
+            if let Some(cancelled) = canceller.cancel().expect("cancel") {
+                break cancelled;
+            }
+            assert!(Instant::now() < deadline, "command never went in flight");
+            thread::sleep(Duration::from_millis(5));
+        };
+        assert_eq!(cancelled, ("cmd-1".to_string(), true));
+
+        let response = sender.join().expect("sender thread").expect("response");
+        assert!(!response.success);
+        assert_eq!(response.error.as_deref(), Some("Command cancelled"));
+        assert!(canceller.cancel().expect("nothing in flight").is_none());
+
+        handle.join().expect("listener thread");
+        cleanup_socket(&socket_path);
+    }
+
+    #[test]
+    fn handshake_and_command_share_a_kept_alive_connection() {
+        let socket_path = unique_socket_path("keep-alive");
+        cleanup_socket(&socket_path);
+
+        let listener = bind_listener(&socket_path);
+        let handle = thread::spawn(move || {
+            let (stream, _addr) = listener.accept().expect("accept connection");
+            let mut reader = BufReader::new(stream.try_clone().expect("clone stream"));
+            let mut stream = stream;
+            for reply in [
+                json!({
+                    "type": "pong",
+                    "payload": {"protocolVersion": PROTOCOL_VERSION, "version": "0.2.4", "extensions": []},
+                    "keepAlive": true
+                }),
+                json!({
+                    "type": "response",
+                    "payload": {"id": "cmd-1", "success": true},
+                    "keepAlive": true
+                }),
+            ] {
+                let mut buf = String::new();
+                reader.read_line(&mut buf).expect("read line");
+                let incoming: serde_json::Value =
+                    serde_json::from_str(buf.trim_end()).expect("parse json");
+                assert_eq!(incoming["keepAlive"], true);
+                stream
+                    .write_all(format!("{}\n", reply).as_bytes())
+                    .expect("write response");
+            }
+        });
+
+        let config = Config {
+            ipc_socket_path: socket_path.clone(),
+            connection_timeout_ms: 100,
+            command_timeout_ms: 100,
+            ..Default::default()
+        };
+        let client = IpcClient::new(config);
+        let command = Command {
+            id: "cmd-1".to_string(),
+            session_id: "default".to_string(),
+            profile: None,
+            command_type: crate::types::CommandType::Snapshot,
+            params: None,
+            timestamp: "2026-01-01T00:00:00Z".to_string(),
+            deadline: None,
+        };
+        // A second connection would never be accepted, so this would time out
+        let response = client.send_command(command).expect("send command");
+        assert!(response.success);
+
+        handle.join().expect("listener thread");
+        cleanup_socket(&socket_path);
+    }
+
+    #[test]
+    fn handshake_is_not_counted_as_connect_time() {
+        let socket_path = unique_socket_path("timings");
+        cleanup_socket(&socket_path);
+
+        let listener = bind_listener(&socket_path);
+        let handle = thread::spawn(move || {
+            let (stream, _addr) = listener.accept().expect("accept connection");
+            let mut reader = BufReader::new(stream.try_clone().expect("clone stream"));
+            let mut stream = stream;
+            for (delay, reply) in [
+                (
+                    300,
+                    json!({
+                        "type": "pong",
+                        "payload": {"protocolVersion": PROTOCOL_VERSION, "version": "0.2.4", "extensions": []},
+                        "keepAlive": true
+                    }),
+                ),
+                (
+                    0,
+                    json!({
+                        "type": "response",
+                        "payload": {"id": "cmd-1", "success": true},
+                        "keepAlive": true
+                    }),
+                ),
+            ] {
+                let mut buf = String::new();
+                reader.read_line(&mut buf).expect("read line");
+                thread::sleep(Duration::from_millis(delay));
+                stream
+                    .write_all(format!("{}\n", reply).as_bytes())
+                    .expect("write response");
+            }
+        });
+
+        let config = Config {
+            ipc_socket_path: socket_path.clone(),
+            connection_timeout_ms: 2000,
+            command_timeout_ms: 2000,
+            ..Default::default()
+        };
+        let client = IpcClient::new(config);
+        let command = Command {
+            id: "cmd-1".to_string(),
+            session_id: "default".to_string(),
+            profile: None,
+            command_type: crate::types::CommandType::Snapshot,
+            params: None,
+            timestamp: "2026-01-01T00:00:00Z".to_string(),
+            deadline: None,
+        };
+        client.send_command(command).expect("send command");
+
+        // The slow pong is neither connecting nor the command itself
+        let timings = client.timings();
+        assert!(
+            timings.connect < Duration::from_millis(200),
+            "{:?}",
+            timings
+        );
+        assert!(timings.daemon < Duration::from_millis(200), "{:?}", timings);
+
+        handle.join().expect("listener thread");
+        cleanup_socket(&socket_path);
+    }
+
+    #[test]
+    fn reconnects_when_the_daemon_closes_the_connection() {
+        let socket_path = unique_socket_path("no-keep-alive");
+        cleanup_socket(&socket_path);
+
+        let listener = bind_listener(&socket_path);
+        let handle = thread::spawn(move || {
+            // Daemons that predate keep-alive answer once, without echoing it
+            for _ in 0..2 {
+                let (stream, _addr) = listener.accept().expect("accept connection");
+                let mut reader = BufReader::new(stream);
+                let mut buf = String::new();
+                reader.read_line(&mut buf).expect("read line");
+                let mut stream = reader.into_inner();
+                stream
+                    .write_all(b"{\"type\":\"pong\",\"payload\":null}\n")
+                    .expect("write response");
+            }
+        });
+
+        let config = Config {
+            ipc_socket_path: socket_path.clone(),
+            connection_timeout_ms: 100,
+            ..Default::default()
+        };
+        let client = IpcClient::new(config);
+        assert!(client.ping().expect("first ping"));
+        assert!(client.ping().expect("second ping"));
+
+        handle.join().expect("listener thread");
+        cleanup_socket(&socket_path);
+    }
+
+    #[test]
+    fn connect_to_daemon_refuses_sockets_others_can_write() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let socket_path = unique_socket_path("open");
+        cleanup_socket(&socket_path);
+        let _listener = bind_listener(&socket_path);
+        fs::set_permissions(&socket_path, fs::Permissions::from_mode(0o666)).expect("chmod");
+
+        let result = connect_to_daemon(&socket_path, Duration::from_millis(50));
+        match result {
+            Err(CliError::ConnectionFailed(message)) => {
+                assert!(message.contains("other users can write"), "{}", message)
+            }
+            other => panic!("expected ConnectionFailed, got {:?}", other.map(|_| ())),
+        }
+
+        cleanup_socket(&socket_path);
+    }
+
+    #[test]
+    fn connect_to_daemon_refuses_files_that_are_not_sockets() {
+        let socket_path = unique_socket_path("plain-file");
+        fs::write(&socket_path, "").expect("write file");
+
+        let result = connect_to_daemon(&socket_path, Duration::from_millis(50));
+        assert!(matches!(result, Err(CliError::ConnectionFailed(_))));
+
+        cleanup_socket(&socket_path);
+    }
+
+    #[test]
+    fn peer_uid_reports_current_user() {
+        let (stream, _other) = UnixStream::pair().expect("create unix pair");
+        let uid = peer_uid(&stream).expect("peer uid");
+        assert!(uid.is_none_or(|uid| uid == current_uid()));
+    }
+
+    /// Read the version check the client sends before its first command and
+    /// answer it as a current daemon would
+    pub(super) fn answer_handshake(reader: &mut BufReader<UnixStream>) {
+        let mut buf = String::new();
+        reader.read_line(&mut buf).expect("read ping");
+        let incoming: serde_json::Value = serde_json::from_str(buf.trim_end()).expect("parse json");
+        assert_eq!(incoming["type"], "ping");
+
+        let pong = json!({
+            "type": "pong",
+            "payload": {"protocolVersion": PROTOCOL_VERSION, "version": "0.2.4", "extensions": []},
+            "keepAlive": true
+        });
+        reader
+            .get_mut()
+            .write_all(format!("{}\n", pong).as_bytes())
+            .expect("write pong");
+    }
+
+    /// Bind a listener that only the current user can connect to, whatever the umask
+    pub(super) fn bind_listener(path: &PathBuf) -> UnixListener {
+        use std::os::unix::fs::PermissionsExt;
+
+        let listener = UnixListener::bind(path).expect("bind listener");
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600)).expect("chmod socket");
+        listener
+    }
+
+    pub(super) fn unique_socket_path(prefix: &str) -> PathBuf {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("time")
+            .as_nanos();
+        std::env::temp_dir().join(format!("browser-cli-{}-{}.sock", prefix, nanos))
+    }
+
+    pub(super) fn cleanup_socket(path: &PathBuf) {
+        let _ = fs::remove_file(path);
+    }
+}
@@ -0,0 +1,661 @@
+//! Element locators
+//!
+//! Parses the locator grammar accepted anywhere a `<REF>` is taken:
+//!
+//! - `e12` - a raw ref from a snapshot
+//! - `role=button[name="Save"]` - role with optional accessible name
+//! - `text="Sign in"` - accessible name of any element
+//! - `label="Email"` - form control by its label
+//! - `css=#login` - CSS selector
+//! - `testid=submit` - `data-testid` attribute
+//! - `... >> nth=2` - chaining; each step searches inside the previous one
+//!
+//! Quoted values match exactly; unquoted values match case-insensitively as
+//! a substring, as in Playwright.
+
+use crate::error::{CliError, Result};
+use crate::snapshot::{Snapshot, SnapshotNode};
+use serde_json::json;
+
+// =============================================================================
+// Constants
+// =============================================================================
+
+/// Separator between chained selectors
+const CHAIN_SEPARATOR: &str = ">>";
+
+/// Roles that `label=` can resolve to
+const LABELLED_ROLES: &[&str] = &[
+    "textbox",
+    "searchbox",
+    "checkbox",
+    "radio",
+    "switch",
+    "slider",
+    "spinbutton",
+    "combobox",
+    "listbox",
+];
+
+// =============================================================================
+// Locator Types
+// =============================================================================
+
+/// A string to match against an accessible name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextMatch {
+    pub value: String,
+    /// Quoted values match exactly, unquoted ones as a case-insensitive substring
+    pub exact: bool,
+}
+
+impl TextMatch {
+    fn matches(&self, candidate: &str) -> bool {
+        if self.exact {
+            candidate == self.value
+        } else {
+            candidate
+                .to_lowercase()
+                .contains(&self.value.to_lowercase())
+        }
+    }
+}
+
+/// One step of a locator chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Selector {
+    Role {
+        role: String,
+        name: Option<TextMatch>,
+    },
+    Text(TextMatch),
+    Label(TextMatch),
+    Css(String),
+    TestId(String),
+    Nth(usize),
+}
+
+/// A parsed element locator
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Locator {
+    /// A ref taken verbatim from a snapshot
+    Ref(String),
+    /// A chain of selectors resolved to a ref before dispatch
+    Chain(Vec<Selector>),
+}
+
+impl Locator {
+    /// Resolve the locator against a parsed snapshot
+    ///
+    /// Returns `None` when nothing matches or when the chain contains
+    /// selectors (CSS, test IDs) that only the page can evaluate.
+    pub fn resolve_in_snapshot(&self, snapshot: &Snapshot) -> Option<String> {
+        let selectors = match self {
+            Locator::Ref(r#ref) => return Some(r#ref.clone()),
+            Locator::Chain(selectors) => selectors,
+        };
+
+        let mut scopes: Vec<&SnapshotNode> = Vec::new();
+        let mut first = true;
+
+        for selector in selectors {
+            if let Selector::Nth(index) = selector {
+                scopes = scopes.get(*index).map(|n| vec![*n]).unwrap_or_default();
+                continue;
+            }
+
+            let mut matches = Vec::new();
+            if first {
+                for node in &snapshot.nodes {
+                    collect_matches(node, selector, &mut matches)?;
+                }
+            } else {
+                for scope in &scopes {
+                    for child in &scope.children {
+                        collect_matches(child, selector, &mut matches)?;
+                    }
+                }
+            }
+            scopes = matches;
+            first = false;
+        }
+
+        scopes.into_iter().find_map(|node| node.r#ref.clone())
+    }
+
+    /// Parameters for the extension's `find` command, when it can evaluate
+    /// the locator on its own
+    ///
+    /// `find` has no notion of scoping or accessible names, so only single
+    /// selectors (plus `nth=` on CSS and test IDs) translate. Text and label
+    /// values carry `exact` so `find` matches them the way the snapshot does.
+    pub fn find_params(&self) -> Option<serde_json::Value> {
+        let Locator::Chain(selectors) = self else {
+            return None;
+        };
+
+        let params = match selectors.as_slice() {
+            [Selector::Css(css)] => json!({"locator": "first", "value": css}),
+            [Selector::Css(css), Selector::Nth(n)] => {
+                json!({"locator": "nth", "value": css, "text": n.to_string()})
+            }
+            [Selector::TestId(id)] => json!({"locator": "testid", "value": id}),
+            [Selector::TestId(id), Selector::Nth(n)] => json!({
+                "locator": "nth",
+                "value": format!("[data-testid=\"{}\"]", id.replace('"', "\\\"")),
+                "text": n.to_string(),
+            }),
+            [Selector::Role { role, name: None }] => json!({"locator": "role", "value": role}),
+            [Selector::Text(text)] => {
+                json!({"locator": "text", "value": text.value, "exact": text.exact})
+            }
+            [Selector::Label(text)] => {
+                json!({"locator": "label", "value": text.value, "exact": text.exact})
+            }
+            _ => return None,
+        };
+
+        Some(params)
+    }
+}
+
+/// Collect nodes in document order that match a selector
+///
+/// Returns `None` if the selector cannot be evaluated against a snapshot.
+fn collect_matches<'a>(
+    node: &'a SnapshotNode,
+    selector: &Selector,
+    out: &mut Vec<&'a SnapshotNode>,
+) -> Option<()> {
+    if matches_node(node, selector)? {
+        out.push(node);
+    }
+    for child in &node.children {
+        collect_matches(child, selector, out)?;
+    }
+    Some(())
+}
+
+fn matches_node(node: &SnapshotNode, selector: &Selector) -> Option<bool> {
+    let name_matches = |text: &TextMatch| node.name.as_deref().is_some_and(|n| text.matches(n));
+
+    let matched = match selector {
+        Selector::Role { role, name } => {
+            let role_matches = if role == "heading" {
+                node.heading_level().is_some()
+            } else {
+                node.role == *role
+            };
+            role_matches && name.as_ref().is_none_or(name_matches)
+        }
+        Selector::Text(text) => node.r#ref.is_some() && name_matches(text),
+        Selector::Label(text) => LABELLED_ROLES.contains(&node.role.as_str()) && name_matches(text),
+        Selector::Css(_) | Selector::TestId(_) => return None,
+        Selector::Nth(_) => false,
+    };
+
+    Some(matched)
+}
+
+// =============================================================================
+// Parsing
+// =============================================================================
+
+/// Parse a locator string
+pub fn parse(input: &str) -> Result<Locator> {
+    if input.trim().is_empty() {
+        return Err(CliError::InvalidArguments(
+            "Element reference cannot be empty".to_string(),
+        ));
+    }
+
+    let parts = split_chain(input);
+    let is_engine = |part: &str| {
+        part.split_once('=')
+            .is_some_and(|(engine, _)| engine_known(engine.trim()))
+    };
+
+    // A lone token without an engine prefix is a raw ref
+    if parts.len() == 1 && !is_engine(parts[0].1) {
+        let (start, part) = parts[0];
+        if let Some(offset) = part.find(char::is_whitespace) {
+            return Err(parse_error(
+                input,
+                start + offset,
+                "element refs cannot contain whitespace",
+            ));
+        }
+        return Ok(Locator::Ref(part.to_string()));
+    }
+
+    let mut selectors = Vec::new();
+    for (offset, part) in parts {
+        selectors.push(parse_selector(input, offset, part)?);
+    }
+
+    if matches!(selectors.first(), Some(Selector::Nth(_))) {
+        return Err(parse_error(input, 0, "nth= must follow another selector"));
+    }
+
+    Ok(Locator::Chain(selectors))
+}
+
+fn engine_known(engine: &str) -> bool {
+    matches!(engine, "role" | "text" | "label" | "css" | "testid" | "nth")
+}
+
+/// Split on `>>` outside quotes, returning each trimmed part with its offset
+fn split_chain(input: &str) -> Vec<(usize, &str)> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut in_quotes = false;
+    let mut escaped = false;
+    let bytes = input.as_bytes();
+
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        if escaped {
+            escaped = false;
+        } else if b == b'\\' && in_quotes {
+            escaped = true;
+        } else if b == b'"' {
+            in_quotes = !in_quotes;
+        } else if !in_quotes && bytes[i..].starts_with(CHAIN_SEPARATOR.as_bytes()) {
+            parts.push(trimmed_part(input, start, i));
+            i += CHAIN_SEPARATOR.len();
+            start = i;
+            continue;
+        }
+        i += 1;
+    }
+    parts.push(trimmed_part(input, start, input.len()));
+
+    parts
+}
+
+fn trimmed_part(input: &str, start: usize, end: usize) -> (usize, &str) {
+    let raw = &input[start..end];
+    let leading = raw.len() - raw.trim_start().len();
+    (start + leading, raw.trim())
+}
+
+fn parse_selector(input: &str, offset: usize, part: &str) -> Result<Selector> {
+    if part.is_empty() {
+        return Err(parse_error(input, offset, "expected a selector"));
+    }
+
+    let Some((engine, body)) = part.split_once('=') else {
+        return Err(parse_error(
+            input,
+            offset,
+            "expected <engine>=<value> (role, text, label, css, testid, nth)",
+        ));
+    };
+    let body_offset = offset + engine.len() + 1;
+
+    match engine.trim() {
+        "role" => parse_role(input, body_offset, body),
+        "text" => Ok(Selector::Text(parse_text(input, body_offset, body)?)),
+        "label" => Ok(Selector::Label(parse_text(input, body_offset, body)?)),
+        "css" => Ok(Selector::Css(parse_text(input, body_offset, body)?.value)),
+        "testid" => Ok(Selector::TestId(
+            parse_text(input, body_offset, body)?.value,
+        )),
+        "nth" => body
+            .trim()
+            .parse::<usize>()
+            .map(Selector::Nth)
+            .map_err(|_| parse_error(input, body_offset, "nth= expects a non-negative integer")),
+        _ => Err(parse_error(
+            input,
+            offset,
+            "unknown selector engine (expected role, text, label, css, testid or nth)",
+        )),
+    }
+}
+
+/// Parse `button` or `button[name="Save"]`
+fn parse_role(input: &str, offset: usize, body: &str) -> Result<Selector> {
+    let (role, attrs) = match body.find('[') {
+        Some(i) => (&body[..i], Some(i)),
+        None => (body, None),
+    };
+
+    let role = role.trim();
+    if role.is_empty() || !role.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(parse_error(input, offset, "expected a role name"));
+    }
+
+    let Some(open) = attrs else {
+        return Ok(Selector::Role {
+            role: role.to_string(),
+            name: None,
+        });
+    };
+
+    let attr_offset = offset + open + 1;
+    let attr = &body[open + 1..];
+    let Some(value) = attr.strip_prefix("name=") else {
+        return Err(parse_error(
+            input,
+            attr_offset,
+            "expected name=<value> inside []",
+        ));
+    };
+    let value_offset = attr_offset + "name=".len();
+
+    let Some(close) = find_closing_bracket(value) else {
+        // Report an unterminated quote before the missing bracket it hides
+        parse_text(input, value_offset, value)?;
+        return Err(parse_error(input, offset + body.len(), "expected ]"));
+    };
+    if !value[close + 1..].trim().is_empty() {
+        return Err(parse_error(
+            input,
+            value_offset + close + 1,
+            "unexpected input after ]",
+        ));
+    }
+
+    Ok(Selector::Role {
+        role: role.to_string(),
+        name: Some(parse_text(input, value_offset, &value[..close])?),
+    })
+}
+
+/// Find the `]` that closes an attribute, skipping quoted text
+fn find_closing_bracket(value: &str) -> Option<usize> {
+    let mut in_quotes = false;
+    let mut escaped = false;
+    for (i, c) in value.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            ']' if !in_quotes => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Parse a quoted (`"Sign in"`) or bare (`Sign in`) value
+fn parse_text(input: &str, offset: usize, body: &str) -> Result<TextMatch> {
+    let leading = body.len() - body.trim_start().len();
+    let body = body.trim();
+    let offset = offset + leading;
+
+    let Some(quoted) = body.strip_prefix('"') else {
+        if body.is_empty() {
+            return Err(parse_error(input, offset, "expected a value"));
+        }
+        return Ok(TextMatch {
+            value: body.to_string(),
+            exact: false,
+        });
+    };
+
+    let mut value = String::new();
+    let mut chars = quoted.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some((_, escaped)) => value.push(escaped),
+                None => break,
+            },
+            '"' => {
+                let rest = &quoted[i + 1..];
+                if !rest.trim().is_empty() {
+                    return Err(parse_error(
+                        input,
+                        offset + 1 + i + 1,
+                        "unexpected input after closing quote",
+                    ));
+                }
+                return Ok(TextMatch { value, exact: true });
+            }
+            other => value.push(other),
+        }
+    }
+
+    Err(parse_error(input, offset, "unterminated quoted string"))
+}
+
+/// Build an `InvalidArguments` error with a caret under the offending column
+fn parse_error(input: &str, offset: usize, reason: &str) -> CliError {
+    let column = input[..offset.min(input.len())].chars().count();
+    CliError::InvalidArguments(format!(
+        "{} in locator\n  {}\n  {}^",
+        reason,
+        input,
+        " ".repeat(column)
+    ))
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::parse_tree;
+
+    fn exact(value: &str) -> TextMatch {
+        TextMatch {
+            value: value.to_string(),
+            exact: true,
+        }
+    }
+
+    fn snapshot() -> Snapshot {
+        Snapshot {
+            title: "Login".to_string(),
+            url: "https://example.com/login".to_string(),
+            nodes: parse_tree(
+                "- RootWebArea \"Login\" [ref=e1]
+  - navigation
+    - link \"Home\" [ref=e2]
+    - link \"Sign in\" [ref=e3]
+  - main
+    - heading1 \"Sign in\"
+    - form
+      - textbox \"Email\" [ref=e4]
+      - button \"Save draft\" [ref=e5]
+      - button \"Save\" [ref=e6]",
+            ),
+        }
+    }
+
+    #[test]
+    fn parse_plain_ref() {
+        assert_eq!(parse("e12").unwrap(), Locator::Ref("e12".to_string()));
+        assert_eq!(
+            parse("submit-btn").unwrap(),
+            Locator::Ref("submit-btn".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_rejects_empty_input() {
+        assert!(matches!(parse("  "), Err(CliError::InvalidArguments(_))));
+    }
+
+    #[test]
+    fn parse_role_with_name() {
+        assert_eq!(
+            parse("role=button[name=\"Save\"]").unwrap(),
+            Locator::Chain(vec![Selector::Role {
+                role: "button".to_string(),
+                name: Some(exact("Save")),
+            }])
+        );
+    }
+
+    #[test]
+    fn parse_engines() {
+        assert_eq!(
+            parse("text=\"Sign in\"").unwrap(),
+            Locator::Chain(vec![Selector::Text(exact("Sign in"))])
+        );
+        assert_eq!(
+            parse("label=email").unwrap(),
+            Locator::Chain(vec![Selector::Label(TextMatch {
+                value: "email".to_string(),
+                exact: false,
+            })])
+        );
+        assert_eq!(
+            parse("css=#login").unwrap(),
+            Locator::Chain(vec![Selector::Css("#login".to_string())])
+        );
+        assert_eq!(
+            parse("testid=submit").unwrap(),
+            Locator::Chain(vec![Selector::TestId("submit".to_string())])
+        );
+    }
+
+    #[test]
+    fn parse_chain_with_nth() {
+        assert_eq!(
+            parse("role=navigation >> text=\"a >> b\" >> nth=2").unwrap(),
+            Locator::Chain(vec![
+                Selector::Role {
+                    role: "navigation".to_string(),
+                    name: None,
+                },
+                Selector::Text(exact("a >> b")),
+                Selector::Nth(2),
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_error_points_at_unterminated_quote() {
+        let err = parse("role=button[name=\"Save]").unwrap_err();
+        let CliError::InvalidArguments(message) = err else {
+            panic!("expected InvalidArguments");
+        };
+        assert_eq!(
+            message,
+            "unterminated quoted string in locator\n  role=button[name=\"Save]\n                   ^"
+        );
+    }
+
+    #[test]
+    fn parse_error_points_at_bad_nth() {
+        let CliError::InvalidArguments(message) = parse("css=li >> nth=x").unwrap_err() else {
+            panic!("expected InvalidArguments");
+        };
+        assert_eq!(
+            message,
+            "nth= expects a non-negative integer in locator\n  css=li >> nth=x\n                ^"
+        );
+    }
+
+    #[test]
+    fn parse_error_points_at_unknown_engine() {
+        let CliError::InvalidArguments(message) = parse("css=form >> xpath=//a").unwrap_err()
+        else {
+            panic!("expected InvalidArguments");
+        };
+        assert!(message.ends_with("\n  css=form >> xpath=//a\n              ^"));
+    }
+
+    #[test]
+    fn parse_rejects_leading_nth() {
+        assert!(matches!(parse("nth=1"), Err(CliError::InvalidArguments(_))));
+    }
+
+    #[test]
+    fn resolve_role_with_exact_name() {
+        let locator = parse("role=button[name=\"Save\"]").unwrap();
+        assert_eq!(
+            locator.resolve_in_snapshot(&snapshot()),
+            Some("e6".to_string())
+        );
+    }
+
+    #[test]
+    fn resolve_role_with_substring_name() {
+        let locator = parse("role=button[name=save]").unwrap();
+        assert_eq!(
+            locator.resolve_in_snapshot(&snapshot()),
+            Some("e5".to_string())
+        );
+    }
+
+    #[test]
+    fn resolve_text_skips_nodes_without_ref() {
+        let locator = parse("text=\"Sign in\"").unwrap();
+        assert_eq!(
+            locator.resolve_in_snapshot(&snapshot()),
+            Some("e3".to_string())
+        );
+    }
+
+    #[test]
+    fn resolve_label_matches_form_controls() {
+        let locator = parse("label=\"Email\"").unwrap();
+        assert_eq!(
+            locator.resolve_in_snapshot(&snapshot()),
+            Some("e4".to_string())
+        );
+    }
+
+    #[test]
+    fn resolve_chain_searches_descendants() {
+        let locator = parse("role=navigation >> role=link >> nth=1").unwrap();
+        assert_eq!(
+            locator.resolve_in_snapshot(&snapshot()),
+            Some("e3".to_string())
+        );
+    }
+
+    #[test]
+    fn resolve_returns_none_for_css() {
+        let locator = parse("css=#login").unwrap();
+        assert_eq!(locator.resolve_in_snapshot(&snapshot()), None);
+    }
+
+    #[test]
+    fn find_params_translates_single_selectors() {
+        assert_eq!(
+            parse("css=#login").unwrap().find_params(),
+            Some(json!({"locator": "first", "value": "#login"}))
+        );
+        assert_eq!(
+            parse("css=li >> nth=2").unwrap().find_params(),
+            Some(json!({"locator": "nth", "value": "li", "text": "2"}))
+        );
+        assert_eq!(
+            parse("testid=submit").unwrap().find_params(),
+            Some(json!({"locator": "testid", "value": "submit"}))
+        );
+        assert_eq!(
+            parse("label=\"Email\"").unwrap().find_params(),
+            Some(json!({"locator": "label", "value": "Email", "exact": true}))
+        );
+        assert_eq!(
+            parse("text=sign in").unwrap().find_params(),
+            Some(json!({"locator": "text", "value": "sign in", "exact": false}))
+        );
+    }
+
+    #[test]
+    fn find_params_rejects_scoped_chains() {
+        assert_eq!(
+            parse("role=button[name=\"Save\"]").unwrap().find_params(),
+            None
+        );
+        assert_eq!(parse("role=form >> text=Save").unwrap().find_params(), None);
+        assert_eq!(parse("e12").unwrap().find_params(), None);
+    }
+
+    #[test]
+    fn resolve_returns_none_without_match() {
+        let locator = parse("role=button[name=\"Delete\"]").unwrap();
+        assert_eq!(locator.resolve_in_snapshot(&snapshot()), None);
+    }
+}
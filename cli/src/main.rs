@@ -1,40 +1,79 @@
+pub mod aliases;
 pub mod cli;
 pub mod commands;
 pub mod config;
 pub mod daemon;
 pub mod error;
 pub mod ipc;
+pub mod locator;
 pub mod output;
+pub mod query;
+pub mod snapshot;
 pub mod types;
 pub mod utils;
 
 use clap::Parser;
-use cli::{Cli, Commands, TabCommands};
+use cli::{
+    AliasCommands, Cli, Commands, ConfigCommands, ContextCommands, DaemonArgs, DaemonCommands,
+    DaemonctlCommands, HistoryCommands, TabCommands,
+};
 use commands::Execute;
 use commands::plugins::daemon as daemon_plugin;
-use config::{Config, ENV_PROFILE, ENV_SESSION_NAME};
-use error::{CliError, Result};
+use config::{Config, Origin};
+use error::{CliError, Failure};
 use ipc::IpcClient;
 use output::OutputFormatter;
+use snapshot::SnapshotFormat;
 use std::process::ExitCode;
 use std::str::FromStr;
-use types::ScrollDirection;
+use std::time::Instant;
+use types::{DurationBreakdown, OutputFormat, ResponseMeta, ScrollDirection};
+
+/// Exit code after Ctrl-C, as shells report for SIGINT
+const EXIT_INTERRUPTED: i32 = 130;
 
 #[tokio::main]
 async fn main() -> ExitCode {
     let cli = Cli::parse();
+    let config = match config::load_config(cli.context.as_deref()) {
+        // Doctor reports a broken config itself, so it runs on the defaults
+        Err(_) if matches!(cli.command, Commands::Doctor) => Ok(Config::from_env()),
+        // The config commands must keep working so a broken file can be fixed
+        Err(error) if matches!(cli.command, Commands::Config(_) | Commands::Version(_)) => {
+            eprintln!("Warning: {}; using defaults", error);
+            Ok(Config::from_env())
+        }
+        config => config,
+    };
+
+    // Flags override the configured output format
+    let output = cli
+        .output
+        .or(config.as_ref().ok().map(|config| config.output))
+        .unwrap_or_default();
+    let formatter = OutputFormatter::new(output).with_color(cli.color);
 
-    match run(cli).await {
+    let result = match config {
+        Ok(config) => run(cli, config, output).await,
+        Err(error) => Err(error.into()),
+    };
+    match result {
         Ok(()) => ExitCode::from(0_u8),
-        Err(e) => {
-            eprintln!("Error: {}", e);
-            ExitCode::from(e.exit_code() as u8)
+        Err(failure) => {
+            if !failure.reported {
+                formatter.print_failure(&failure);
+            }
+            ExitCode::from(failure.error.exit_code() as u8)
         }
     }
 }
 
-pub async fn run(cli: Cli) -> Result<()> {
-    if matches!(cli.command, Commands::Version) {
+pub async fn run(cli: Cli, mut config: Config, output: OutputFormat) -> Result<(), Failure> {
+    let started = Instant::now();
+
+    if let Commands::Version(args) = &cli.command
+        && !args.all
+    {
         println!(
             "browser v{} (https://github.com/stakpak/tab)",
             env!("CARGO_PKG_VERSION")
@@ -42,38 +81,145 @@ pub async fn run(cli: Cli) -> Result<()> {
         return Ok(());
     }
 
+    if let Some(timeout) = cli.timeout {
+        config.command_timeout_ms = timeout.as_millis() as u64;
+        config.set_origin("command_timeout_ms", Origin::Flag("--timeout"));
+    }
+    if let Some(timeout) = cli.connect_timeout {
+        config.connection_timeout_ms = timeout.as_millis() as u64;
+        config.set_origin("connection_timeout_ms", Origin::Flag("--connect-timeout"));
+    }
+
+    if cli.no_autostart {
+        config.autostart = false;
+        config.set_origin("autostart", Origin::Flag("--no-autostart"));
+    }
+
     if matches!(cli.command, Commands::Ping) {
-        let config = config::load_config();
         let client = IpcClient::new(config);
         let is_running = client.ping()?;
         if is_running {
             println!("Daemon is running");
             return Ok(());
         } else {
-            return Err(CliError::DaemonNotRunning(
-                "Daemon is not responding".to_string(),
-            ));
+            return Err(CliError::DaemonNotRunning("Daemon is not responding".to_string()).into());
         }
     }
 
-    if let Commands::Daemon { args } = cli.command {
-        return daemon_plugin::run_daemon(args)
+    if let Commands::Daemon(DaemonArgs {
+        command: None,
+        foreground,
+        args,
+    }) = cli.command
+    {
+        let args = if foreground {
+            daemon::prepare_foreground(&config, args)?
+        } else {
+            args
+        };
+        return daemon_plugin::run_daemon(args, &config.plugins.daemon)
             .await
-            .map_err(CliError::CommandFailed);
+            .map_err(|e| CliError::CommandFailed(e).into());
     }
 
-    let config = config::load_config();
-    let session_id = resolve_session_id(&config, cli.session.as_deref());
-    let profile = resolve_profile(cli.profile.as_deref());
+    let follow_up = if cli.then_snapshot || cli.then_diff {
+        if !matches!(
+            cli.command,
+            Commands::Navigate(_) | Commands::Click(_) | Commands::Type(_) | Commands::Scroll(_)
+        ) {
+            return Err(CliError::InvalidArguments(
+                "--then-snapshot and --then-diff only apply to navigate, click, type and scroll"
+                    .to_string(),
+            )
+            .into());
+        }
+        Some(commands::FollowUpSnapshot::new(cli.then_diff, cli.settle))
+    } else {
+        None
+    };
+
+    let query = cli.query.as_deref().map(query::Query::parse).transpose()?;
+
+    if let Some(id) = &cli.request_id
+        && (id.trim().is_empty() || id.contains(char::is_control))
+    {
+        return Err(CliError::InvalidArguments(
+            "--request-id must be non-empty and contain no control characters".to_string(),
+        )
+        .into());
+    }
+
+    // History search covers every session unless one is named explicitly
+    let history_session = cli.session.clone();
+    // `context create` saves the flags as given, not the effective settings
+    let context_flags = (cli.session.clone(), cli.profile.clone());
+    if let Some(session) = cli.session {
+        config.default_session = session;
+        config.set_origin("session", Origin::Flag("--session"));
+    }
+    if let Some(profile) = cli.profile {
+        config.profile = Some(profile);
+        config.set_origin("profile", Origin::Flag("--profile"));
+    }
+    if cli.output.is_some() {
+        config.output = output;
+        config.set_origin("output", Origin::Flag("--output"));
+    }
+    let session_id = config.default_session.clone();
+    let profile = config.profile.clone();
 
-    daemon::ensure_daemon_running(&config)?;
+    // Commands go straight to the daemon, which is started only if connecting
+    // fails and autostart is on. Alias, history, config and context commands
+    // never connect; daemonctl manages the daemon itself, doctor and
+    // `version --all` only report on it, and install-service leaves it to systemd
+    let autostart = config.autostart;
     let client = IpcClient::new(config);
+    let client = if !autostart
+        || matches!(
+            cli.command,
+            Commands::Daemonctl(_) | Commands::Doctor | Commands::Version(_) | Commands::Daemon(_)
+        ) {
+        client
+    } else {
+        client.with_autostart()
+    };
+
+    let ctx =
+        commands::CommandContext::new(client, session_id, profile).with_request_id(cli.request_id);
+
+    // Ctrl-C cancels the command in flight, so the daemon does not go on
+    // running it after nobody is waiting for the response
+    let canceller = ctx.client.canceller();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_err() {
+            return;
+        }
+        match tokio::task::spawn_blocking(move || canceller.cancel()).await {
+            Ok(Err(err)) => eprintln!("Warning: could not cancel the command: {}", err),
+            Ok(Ok(Some((id, false)))) => {
+                eprintln!("Warning: command {} had already finished", id)
+            }
+            _ => {}
+        }
+        std::process::exit(EXIT_INTERRUPTED);
+    });
+
+    let snapshot_format = match &cli.command {
+        Commands::Snapshot(args) => args.format,
+        Commands::History(HistoryCommands::Show(args)) => args.format,
+        _ => SnapshotFormat::default(),
+    };
 
-    let ctx = commands::CommandContext::new(client, session_id, profile);
+    // Built before dispatch so a bad template fails before the command runs
+    let formatter = OutputFormatter::new(output)
+        .with_color(cli.color)
+        .with_snapshot_format(snapshot_format)
+        .with_template(cli.template.as_deref())?;
 
+    let is_doctor = matches!(cli.command, Commands::Doctor);
     let response = match cli.command {
         Commands::Navigate(args) => commands::NavigateCommand::new(args.url).execute(&ctx)?,
-        Commands::Snapshot => commands::SnapshotCommand::default().execute(&ctx)?,
+        Commands::Snapshot(_) => commands::SnapshotCommand::default().execute(&ctx)?,
         Commands::Click(args) => commands::ClickCommand::new(args.r#ref).execute(&ctx)?,
         Commands::Type(args) => commands::TypeCommand::new(args.r#ref, args.text).execute(&ctx)?,
         Commands::Scroll(args) => {
@@ -88,40 +234,119 @@ pub async fn run(cli: Cli) -> Result<()> {
             }
             TabCommands::List => commands::TabListCommand::default().execute(&ctx)?,
         },
+        Commands::Alias(alias_cmd) => match alias_cmd {
+            AliasCommands::Set(args) => {
+                commands::AliasSetCommand::new(args.name, args.locator, args.domain)
+                    .execute(&ctx)?
+            }
+            AliasCommands::List => commands::AliasListCommand::default().execute(&ctx)?,
+            AliasCommands::Rm(args) => {
+                commands::AliasRmCommand::new(args.name, args.domain).execute(&ctx)?
+            }
+        },
+        Commands::History(history_cmd) => match history_cmd {
+            HistoryCommands::Search(args) => {
+                commands::HistorySearchCommand::new(args.text, history_session, args.limit)
+                    .execute(&ctx)?
+            }
+            HistoryCommands::Show(args) => {
+                commands::HistoryShowCommand::new(args.id).execute(&ctx)?
+            }
+        },
+        Commands::Config(config_cmd) => match config_cmd {
+            ConfigCommands::Get(args) => commands::ConfigGetCommand::new(args.key).execute(&ctx)?,
+            ConfigCommands::Set(args) => {
+                commands::ConfigSetCommand::new(args.key, args.value).execute(&ctx)?
+            }
+            ConfigCommands::List => commands::ConfigListCommand.execute(&ctx)?,
+            ConfigCommands::Show(args) => {
+                commands::ConfigShowCommand::new(args.origin).execute(&ctx)?
+            }
+            ConfigCommands::Path => commands::ConfigPathCommand.execute(&ctx)?,
+        },
+        Commands::Context(context_cmd) => match context_cmd {
+            ContextCommands::Create(args) => {
+                let (session, profile) = context_flags;
+                commands::ContextCreateCommand::new(args.name, session, profile, args.socket)
+                    .execute(&ctx)?
+            }
+            ContextCommands::Use(args) => {
+                commands::ContextUseCommand::new(args.name).execute(&ctx)?
+            }
+            ContextCommands::List => commands::ContextListCommand.execute(&ctx)?,
+            ContextCommands::Rm(args) => {
+                commands::ContextRmCommand::new(args.name).execute(&ctx)?
+            }
+        },
+        Commands::Daemonctl(daemonctl_cmd) => match daemonctl_cmd {
+            DaemonctlCommands::Status => commands::DaemonStatusCommand.execute(&ctx)?,
+            DaemonctlCommands::Stop => commands::DaemonStopCommand.execute(&ctx)?,
+            DaemonctlCommands::Restart => commands::DaemonRestartCommand.execute(&ctx)?,
+            DaemonctlCommands::Logs(args) => {
+                commands::DaemonLogsCommand::new(args.lines, args.follow).execute(&ctx)?
+            }
+        },
+        Commands::Doctor => commands::DoctorCommand::new(cli.context.clone()).execute(&ctx)?,
+        Commands::Version(_) => commands::VersionCommand.execute(&ctx)?,
         Commands::Back => commands::BackCommand::default().execute(&ctx)?,
         Commands::Forward => commands::ForwardCommand::default().execute(&ctx)?,
-        Commands::Ping | Commands::Version | Commands::Daemon { .. } => unreachable!(),
+        Commands::Daemon(DaemonArgs {
+            command: Some(DaemonCommands::InstallService(args)),
+            ..
+        }) => commands::InstallServiceCommand::new(args.user, args.force).execute(&ctx)?,
+        Commands::Ping | Commands::Daemon(_) => unreachable!(),
     };
 
-    let formatter = OutputFormatter::new(cli.output);
-    formatter.print_response(&response)?;
-    if response.success {
-        Ok(())
+    // Doctor prints its report either way, then fails if any check failed
+    let failed_checks = if is_doctor {
+        commands::doctor::failed_checks(&response)
     } else {
-        Err(CliError::CommandFailed(
-            response
-                .error
-                .unwrap_or_else(|| "Unknown error".to_string()),
-        ))
-    }
-}
+        0
+    };
 
-fn resolve_session_id(config: &Config, session_id: Option<&str>) -> String {
-    if let Some(session) = session_id {
-        return session.to_string();
-    }
+    let mut response = match follow_up {
+        Some(follow_up) => follow_up.apply(&ctx, response)?,
+        None => response,
+    };
 
-    if let Ok(session) = std::env::var(ENV_SESSION_NAME) {
-        return session;
+    if let Some(query) = &query
+        && response.success
+    {
+        let data = response.data.take().unwrap_or(serde_json::Value::Null);
+        response.data = Some(query.evaluate(&data));
     }
 
-    config.default_session.clone()
-}
+    let meta = cli.timing.then(|| {
+        let timings = ctx.client.timings();
+        ResponseMeta {
+            command_id: response.id.clone(),
+            session: ctx.session_id.clone(),
+            duration_ms: DurationBreakdown {
+                connect: timings.connect.as_millis() as u64,
+                daemon: timings.daemon.as_millis() as u64,
+                total: started.elapsed().as_millis() as u64,
+            },
+        }
+    });
 
-fn resolve_profile(profile: Option<&str>) -> Option<String> {
-    if let Some(profile) = profile {
-        return Some(profile.to_string());
+    formatter.print_response(&response, meta.as_ref())?;
+    if !response.success {
+        Err(Failure {
+            error: CliError::CommandFailed(
+                response
+                    .error
+                    .unwrap_or_else(|| "Unknown error".to_string()),
+            ),
+            command_id: Some(response.id),
+            reported: false,
+        })
+    } else if failed_checks > 0 {
+        Err(Failure {
+            error: CliError::CommandFailed(format!("doctor checks failed: {}", failed_checks)),
+            command_id: Some(response.id),
+            reported: true,
+        })
+    } else {
+        Ok(())
     }
-
-    std::env::var(ENV_PROFILE).ok()
 }
@@ -0,0 +1,1536 @@
+//! Output formatting for the Vibe CLI
+//!
+//! Handles formatting command responses for terminal display.
+//! Supports human-readable, JSON, NDJSON, YAML, table and template output.
+
+mod table;
+mod template;
+mod term;
+mod yaml;
+
+pub use template::Template;
+pub use term::Terminal;
+
+use crate::error::{CliError, Failure, Result};
+use crate::snapshot::{self, SnapshotFormat};
+use crate::types::{
+    ActionSnapshotData, AliasListData, ArchiveSearchData, ArchivedSnapshot, CheckStatus,
+    ColorChoice, CommandResponse, ComponentVersion, ConfigListData, ConfigPathData,
+    ContextListData, DaemonLogsData, DaemonStatusData, DaemonStopData, DoctorData, ErrorEnvelope,
+    ErrorInfo, OutputFormat, ResponseMeta, ServiceInstallData, SnapshotData, TabListData,
+    VersionData, protocol_compatible,
+};
+use term::Style;
+
+// =============================================================================
+// Output Format
+// =============================================================================
+
+/// Formats command responses for display
+pub struct OutputFormatter {
+    format: OutputFormat,
+    snapshot_format: SnapshotFormat,
+    template: Option<Template>,
+    stdout: Terminal,
+    stderr: Terminal,
+}
+
+impl OutputFormatter {
+    /// Create a new formatter with the given format
+    pub fn new(format: OutputFormat) -> Self {
+        Self {
+            format,
+            snapshot_format: SnapshotFormat::default(),
+            template: None,
+            stdout: Terminal::plain(),
+            stderr: Terminal::plain(),
+        }
+    }
+
+    /// Detect color and width support of stdout and stderr
+    pub fn with_color(mut self, choice: ColorChoice) -> Self {
+        self.stdout = Terminal::stdout(choice);
+        self.stderr = Terminal::stderr(choice);
+        self
+    }
+
+    /// Set the template for template output
+    ///
+    /// A template switches the format to `template`; the template format
+    /// without one is an error.
+    pub fn with_template(mut self, template: Option<&str>) -> Result<Self> {
+        match template {
+            Some(source) => {
+                self.template = Some(Template::parse(source).map_err(CliError::InvalidArguments)?);
+                self.format = OutputFormat::Template;
+            }
+            None if matches!(self.format, OutputFormat::Template) => {
+                return Err(CliError::InvalidArguments(
+                    "--output template requires --template".to_string(),
+                ));
+            }
+            None => {}
+        }
+        Ok(self)
+    }
+
+    /// Set the export format used for snapshot responses
+    pub fn with_snapshot_format(mut self, snapshot_format: SnapshotFormat) -> Self {
+        self.snapshot_format = snapshot_format;
+        self
+    }
+
+    /// Format and print a command response
+    ///
+    /// With `meta`, human output gets a footer line and other formats get a
+    /// `meta` field in the data.
+    pub fn print_response(
+        &self,
+        response: &CommandResponse,
+        meta: Option<&ResponseMeta>,
+    ) -> Result<()> {
+        if response.success {
+            let output = match meta {
+                Some(meta) => self.format_success_with_meta(response, meta),
+                None => self.format_success(response),
+            };
+            if !output.is_empty() {
+                print_success(&output);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Report a failed invocation
+    ///
+    /// Structured modes (JSON, NDJSON, YAML) print an error envelope on
+    /// stdout so consumers always get a machine-readable result; other modes
+    /// print the message on stderr.
+    pub fn print_failure(&self, failure: &Failure) {
+        let envelope = serde_json::to_value(error_envelope(failure));
+        let output = match (self.format, envelope) {
+            (OutputFormat::Json, Ok(envelope)) => serde_json::to_string_pretty(&envelope).ok(),
+            (OutputFormat::Ndjson, Ok(envelope)) => serde_json::to_string(&envelope).ok(),
+            (OutputFormat::Yaml, Ok(envelope)) => Some(yaml::render(&envelope)),
+            _ => None,
+        };
+
+        match output {
+            Some(output) => print_success(&output),
+            None => print_error(
+                &self
+                    .stderr
+                    .paint(&format!("Error: {}", failure.error), Style::Red),
+            ),
+        }
+    }
+
+    /// Format a success response along with its invocation details
+    fn format_success_with_meta(&self, response: &CommandResponse, meta: &ResponseMeta) -> String {
+        match self.format {
+            OutputFormat::Quiet => String::new(),
+            OutputFormat::Human => format!(
+                "{}\n{}",
+                self.format_success(response),
+                self.stdout.paint(&format_meta(meta), Style::Dim)
+            ),
+            _ => {
+                let response = CommandResponse {
+                    data: Some(with_meta(response.data.clone(), meta)),
+                    ..response.clone()
+                };
+                self.format_success(&response)
+            }
+        }
+    }
+
+    /// Format a success response
+    fn format_success(&self, response: &CommandResponse) -> String {
+        match self.format {
+            OutputFormat::Human => {
+                format_human_success(&response.data, self.snapshot_format, &self.stdout)
+            }
+            OutputFormat::Json => {
+                if let Some(data) = &response.data {
+                    let data = export_snapshot(data, self.snapshot_format);
+                    serde_json::to_string_pretty(&data).unwrap_or_default()
+                } else {
+                    "{}".to_string()
+                }
+            }
+            OutputFormat::Ndjson => {
+                if let Some(data) = &response.data {
+                    let data = export_snapshot(data, self.snapshot_format);
+                    serde_json::to_string(&data).unwrap_or_default()
+                } else {
+                    "{}".to_string()
+                }
+            }
+            OutputFormat::Yaml => {
+                if let Some(data) = &response.data {
+                    yaml::render(&export_snapshot(data, self.snapshot_format))
+                } else {
+                    "{}".to_string()
+                }
+            }
+            OutputFormat::Table => response
+                .data
+                .as_ref()
+                // Snapshot trees do not fit in columns; show them as text
+                .filter(|data| serde_json::from_value::<SnapshotData>((*data).clone()).is_err())
+                .and_then(table::render)
+                .unwrap_or_else(|| {
+                    format_human_success(&response.data, self.snapshot_format, &self.stdout)
+                }),
+            OutputFormat::Template => match &self.template {
+                Some(template) => {
+                    let data = response.data.clone().unwrap_or(serde_json::Value::Null);
+                    template.render(&data)
+                }
+                None => format_human_success(&response.data, self.snapshot_format, &self.stdout),
+            },
+            OutputFormat::Quiet => String::new(),
+        }
+    }
+}
+
+// =============================================================================
+// Specialized Formatters
+// =============================================================================
+
+/// Build the JSON error envelope for a failure
+fn error_envelope(failure: &Failure) -> ErrorEnvelope {
+    ErrorEnvelope {
+        success: false,
+        error: ErrorInfo {
+            kind: failure.error.kind().to_string(),
+            message: failure.error.to_string(),
+            exit_code: failure.error.exit_code(),
+            command_id: failure.command_id.clone(),
+        },
+    }
+}
+
+/// Add a `meta` field to response data
+///
+/// Objects get the field directly; other data is wrapped as `{data, meta}`.
+fn with_meta(data: Option<serde_json::Value>, meta: &ResponseMeta) -> serde_json::Value {
+    let meta = serde_json::to_value(meta).unwrap_or_default();
+    match data {
+        Some(serde_json::Value::Object(mut map)) => {
+            map.insert("meta".to_string(), meta);
+            serde_json::Value::Object(map)
+        }
+        None => serde_json::json!({ "meta": meta }),
+        Some(data) => serde_json::json!({ "data": data, "meta": meta }),
+    }
+}
+
+/// Footer line with the command ID and timings
+fn format_meta(meta: &ResponseMeta) -> String {
+    format!(
+        "[command {}, session {}, connect {}ms, daemon {}ms, total {}ms]",
+        meta.command_id,
+        meta.session,
+        meta.duration_ms.connect,
+        meta.duration_ms.daemon,
+        meta.duration_ms.total
+    )
+}
+
+/// Normalize a URL by stripping trailing slashes
+fn normalize_url(url: &str) -> &str {
+    url.trim_end_matches('/')
+}
+
+/// Replace the snapshot text of a snapshot payload with its export rendering
+///
+/// Non-snapshot payloads and the default text format are returned unchanged.
+fn export_snapshot(data: &serde_json::Value, format: SnapshotFormat) -> serde_json::Value {
+    if format == SnapshotFormat::Text {
+        return data.clone();
+    }
+
+    if serde_json::from_value::<ActionSnapshotData>(data.clone()).is_ok() {
+        let mut data = data.clone();
+        data["snapshot"] = export_snapshot(&data["snapshot"], format);
+        return data;
+    }
+
+    let Ok(snapshot) = serde_json::from_value::<SnapshotData>(data.clone()) else {
+        return data.clone();
+    };
+
+    let mut data = data.clone();
+    data["snapshot"] = serde_json::Value::String(snapshot::render(&snapshot, format));
+    data
+}
+
+/// Format the data payload as human-readable plain text
+fn format_human_success(
+    data: &Option<serde_json::Value>,
+    snapshot_format: SnapshotFormat,
+    term: &Terminal,
+) -> String {
+    let Some(data) = data else {
+        return "Success".to_string();
+    };
+
+    // Try action with follow-up snapshot format: { action, snapshot, diff }
+    if let Ok(combined) = serde_json::from_value::<ActionSnapshotData>(data.clone()) {
+        return format_action_snapshot(combined, snapshot_format, term);
+    }
+
+    // Try archived snapshot format: { id, session, timestamp, snapshot, title, url }
+    if let Ok(entry) = serde_json::from_value::<ArchivedSnapshot>(data.clone()) {
+        let snapshot = SnapshotData {
+            snapshot: entry.snapshot,
+            title: entry.title,
+            url: entry.url,
+        };
+        let body = match snapshot_format {
+            SnapshotFormat::Text => format_snapshot(&snapshot, term),
+            other => snapshot::render(&snapshot, other),
+        };
+        return format!(
+            "Archived #{} (session {}, {})\n{}",
+            entry.id, entry.session, entry.timestamp, body
+        );
+    }
+
+    // Try snapshot format: { snapshot, title, url }
+    if let Ok(snapshot) = serde_json::from_value::<SnapshotData>(data.clone()) {
+        return match snapshot_format {
+            SnapshotFormat::Text => format_snapshot(&snapshot, term),
+            other => snapshot::render(&snapshot, other),
+        };
+    }
+
+    // Try tab list format: { tabs, active_tab_id }
+    if let Ok(tab_list) = serde_json::from_value::<TabListData>(data.clone()) {
+        return format_tab_list(&tab_list, term);
+    }
+
+    // Try history search format: { query, matches }
+    if let Ok(search) = serde_json::from_value::<ArchiveSearchData>(data.clone()) {
+        return format_archive_search(&search);
+    }
+
+    // Try alias list format: { session, aliases }
+    if let Ok(alias_list) = serde_json::from_value::<AliasListData>(data.clone()) {
+        return format_alias_list(&alias_list);
+    }
+
+    // Try config list format: { path, entries }
+    if let Ok(config_list) = serde_json::from_value::<ConfigListData>(data.clone()) {
+        return format_config_list(&config_list);
+    }
+
+    // Try context list format: { active, contexts: [...] }
+    if let Ok(context_list) = serde_json::from_value::<ContextListData>(data.clone()) {
+        return format_context_list(&context_list);
+    }
+
+    // Try doctor format: { checks }
+    if let Ok(doctor) = serde_json::from_value::<DoctorData>(data.clone()) {
+        return format_doctor(&doctor, term);
+    }
+
+    // Try version format: { cli, daemon, extensions }
+    if let Ok(versions) = serde_json::from_value::<VersionData>(data.clone()) {
+        return format_versions(&versions);
+    }
+
+    // Try daemon status format: { pid, uptime_ms, version, socket, sessions }
+    if let Ok(status) = serde_json::from_value::<DaemonStatusData>(data.clone()) {
+        return format_daemon_status(&status);
+    }
+
+    // Try daemon stop format: { stopped, pid, signal }
+    if let Ok(stop) = serde_json::from_value::<DaemonStopData>(data.clone()) {
+        return format_daemon_stop(&stop);
+    }
+
+    // Try daemon logs format: { path, lines }
+    if let Ok(logs) = serde_json::from_value::<DaemonLogsData>(data.clone()) {
+        return logs.lines.join("\n");
+    }
+
+    // Try service install format: { units, next_steps }
+    if let Ok(install) = serde_json::from_value::<ServiceInstallData>(data.clone()) {
+        return format_service_install(&install);
+    }
+
+    // Try config path format: { path, exists }
+    if let Ok(config_path) = serde_json::from_value::<ConfigPathData>(data.clone()) {
+        return config_path.path;
+    }
+
+    // Generic: if it's just { "executed": true } or similar simple object, show "Success"
+    if let Some(obj) = data.as_object() {
+        if obj.len() == 1
+            && let Some(val) = obj.get("executed")
+            && val.as_bool() == Some(true)
+        {
+            return "Success".to_string();
+        }
+
+        // Otherwise display as key-value pairs
+        let mut output = String::new();
+        for (key, value) in obj {
+            let display_value = match value {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            output.push_str(&format!("{}: {}\n", key, display_value));
+        }
+        return output.trim_end().to_string();
+    }
+
+    // Fallback for non-object data; lists of scalars print one per line
+    match data {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Array(items)
+            if items
+                .iter()
+                .all(|item| !item.is_object() && !item.is_array()) =>
+        {
+            items
+                .iter()
+                .map(|item| match item {
+                    serde_json::Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+        other => other.to_string(),
+    }
+}
+
+/// Format snapshot data for human-readable output
+pub fn format_snapshot(data: &SnapshotData, term: &Terminal) -> String {
+    let mut output = snapshot_header(data, term);
+    output.push_str(&term.highlight_snapshot(&data.snapshot));
+    output
+}
+
+/// Title and URL lines above a snapshot or diff
+fn snapshot_header(data: &SnapshotData, term: &Terminal) -> String {
+    format!(
+        "{} {}\n{} {}\n\n",
+        term.paint("Title:", Style::Bold),
+        data.title,
+        term.paint("URL:", Style::Bold),
+        normalize_url(&data.url)
+    )
+}
+
+/// Format an action followed by its snapshot or diff
+fn format_action_snapshot(
+    data: ActionSnapshotData,
+    snapshot_format: SnapshotFormat,
+    term: &Terminal,
+) -> String {
+    let action = format_human_success(&Some(data.action), snapshot_format, term);
+
+    let Some(diff) = data.diff else {
+        let snapshot = match snapshot_format {
+            SnapshotFormat::Text => format_snapshot(&data.snapshot, term),
+            other => snapshot::render(&data.snapshot, other),
+        };
+        return format!("{}\n\n{}", action, snapshot);
+    };
+
+    let mut output = format!("{}\n\n{}", action, snapshot_header(&data.snapshot, term));
+    if diff.is_empty() {
+        output.push_str("No changes since previous snapshot");
+    } else {
+        output.push_str("Changes since previous snapshot:\n");
+        let lines: Vec<String> = diff
+            .iter()
+            .map(|line| match line.chars().next() {
+                Some('+') => term.paint(line, Style::Green),
+                Some('-') => term.paint(line, Style::Red),
+                _ => line.clone(),
+            })
+            .collect();
+        output.push_str(&lines.join("\n"));
+    }
+    output
+}
+
+/// Format tab list for human-readable output
+///
+/// On a terminal, titles and URLs are shortened so each tab fits on one line.
+pub fn format_tab_list(data: &TabListData, term: &Terminal) -> String {
+    let mut output = String::new();
+    output.push_str("Open tabs:\n");
+
+    for tab in &data.tabs {
+        let marker = if tab.active { "* " } else { "  " };
+        let prefix = format!("{}[{}] ", marker, tab.id);
+        let (title, url) = match term.width {
+            // Leave room for the prefix and the trailing space
+            Some(width) => term::fit_title_and_url(
+                &tab.title,
+                &tab.url,
+                width.saturating_sub(prefix.chars().count() + 1),
+            ),
+            None => (tab.title.clone(), tab.url.clone()),
+        };
+        let prefix = if tab.active {
+            term.paint(&prefix, Style::Green)
+        } else {
+            prefix
+        };
+        output.push_str(&format!(
+            "{}{} {} \n",
+            prefix,
+            title,
+            term.paint(&url, Style::Dim)
+        ));
+    }
+
+    output
+}
+
+/// Format history search results for human-readable output
+pub fn format_archive_search(data: &ArchiveSearchData) -> String {
+    if data.matches.is_empty() {
+        return format!("No archived snapshots match \"{}\"", data.query);
+    }
+
+    let mut output = String::new();
+    for hit in &data.matches {
+        output.push_str(&format!(
+            "#{} {} [{}] {}\n    {}\n",
+            hit.id,
+            hit.timestamp,
+            hit.session,
+            hit.title,
+            normalize_url(&hit.url)
+        ));
+        for excerpt in &hit.excerpts {
+            output.push_str(&format!("    > {}\n", excerpt));
+        }
+    }
+
+    output.trim_end().to_string()
+}
+
+/// Format alias list for human-readable output
+pub fn format_alias_list(data: &AliasListData) -> String {
+    if data.aliases.is_empty() {
+        return format!("No aliases for session {}", data.session);
+    }
+
+    let width = data
+        .aliases
+        .iter()
+        .map(|alias| alias.name.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let mut output = format!("Aliases for session {}:\n", data.session);
+    for alias in &data.aliases {
+        let scope = match (&alias.domain, &alias.page, &alias.file) {
+            (Some(domain), _, _) => format!("  (on {})", domain),
+            (None, Some(page), _) => format!("  (page {})", page),
+            (None, None, Some(file)) => format!("  (from {})", file),
+            (None, None, None) => String::new(),
+        };
+        output.push_str(&format!(
+            "  @{:<width$}  {}{}\n",
+            alias.name,
+            alias.locator,
+            scope,
+            width = width
+        ));
+    }
+
+    output.trim_end().to_string()
+}
+
+/// Format daemon status for human-readable output
+pub fn format_daemon_status(data: &DaemonStatusData) -> String {
+    format!(
+        "Daemon running (pid {}, version {})\n  socket:   {}\n  uptime:   {}\n  sessions: {}",
+        data.pid,
+        data.version,
+        data.socket,
+        format_uptime(data.uptime_ms),
+        data.sessions
+    )
+}
+
+/// One line per check, each problem followed by its remedy, then a count
+pub fn format_doctor(data: &DoctorData, term: &Terminal) -> String {
+    let mut lines = Vec::new();
+    let (mut passed, mut warned, mut failed) = (0, 0, 0);
+    for check in &data.checks {
+        let (label, style) = match check.status {
+            CheckStatus::Pass => {
+                passed += 1;
+                ("pass", Style::Green)
+            }
+            CheckStatus::Warn => {
+                warned += 1;
+                ("warn", Style::Yellow)
+            }
+            CheckStatus::Fail => {
+                failed += 1;
+                ("fail", Style::Red)
+            }
+        };
+        lines.push(format!(
+            "{}  {:<10} {}",
+            term.paint(label, style),
+            check.name,
+            check.message
+        ));
+        if let Some(remedy) = &check.remedy {
+            lines.push(term.paint(&format!("      -> {}", remedy), Style::Dim));
+        }
+    }
+    lines.push(String::new());
+    lines.push(format!(
+        "{} passed, {} {}, {} failed",
+        passed,
+        warned,
+        if warned == 1 { "warning" } else { "warnings" },
+        failed
+    ));
+    lines.join("\n")
+}
+
+/// One line per component, flagging protocol versions the CLI cannot work with
+pub fn format_versions(data: &VersionData) -> String {
+    let mut lines = vec![format_component("cli", &data.cli, None)];
+    match &data.daemon {
+        Some(daemon) => lines.push(format_component("daemon", daemon, None)),
+        None => lines.push(format!("{:<10} not running", "daemon")),
+    }
+    if data.daemon.is_some() && data.extensions.is_empty() {
+        lines.push(format!("{:<10} none connected", "extension"));
+    }
+    for extension in &data.extensions {
+        let version = ComponentVersion {
+            version: extension.version.clone(),
+            protocol_version: extension.protocol_version.clone(),
+        };
+        lines.push(format_component(
+            "extension",
+            &version,
+            Some(&extension.session),
+        ));
+    }
+    lines.join("\n")
+}
+
+fn format_component(name: &str, component: &ComponentVersion, session: Option<&str>) -> String {
+    let mut line = format!(
+        "{:<10} {}",
+        name,
+        component.version.as_deref().unwrap_or("unknown")
+    );
+    match &component.protocol_version {
+        Some(protocol) => {
+            line.push_str(&format!("  protocol {}", protocol));
+            if !protocol_compatible(protocol) {
+                line.push_str(" (mismatch)");
+            }
+        }
+        None => line.push_str("  predates version reporting"),
+    }
+    if let Some(session) = session {
+        line.push_str(&format!("  session {}", session));
+    }
+    line
+}
+
+pub fn format_daemon_stop(data: &DaemonStopData) -> String {
+    if !data.stopped {
+        return "Daemon is not running".to_string();
+    }
+    let mut output = "Daemon stopped".to_string();
+    if let Some(signal) = &data.signal {
+        output.push_str(&format!(" with {}", signal));
+    }
+    if let Some(pid) = data.pid {
+        output.push_str(&format!(" (pid {})", pid));
+    }
+    output
+}
+
+/// Format the units written by daemon install-service and how to enable them
+pub fn format_service_install(data: &ServiceInstallData) -> String {
+    let mut lines: Vec<String> = data
+        .units
+        .iter()
+        .map(|unit| format!("Wrote {}", unit))
+        .collect();
+    lines.push(String::new());
+    lines.push("Enable with:".to_string());
+    lines.extend(data.next_steps.iter().map(|step| format!("  {}", step)));
+    lines.join("\n")
+}
+
+/// Uptime in its two largest units, e.g. "2h 13m"
+fn format_uptime(ms: u64) -> String {
+    let secs = ms / 1000;
+    let (days, hours, minutes, seconds) =
+        (secs / 86400, secs / 3600 % 24, secs / 60 % 60, secs % 60);
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m {}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+/// Format context list for human-readable output, marking the active context
+pub fn format_context_list(data: &ContextListData) -> String {
+    if data.contexts.is_empty() {
+        return "No contexts".to_string();
+    }
+
+    let width = data
+        .contexts
+        .iter()
+        .map(|context| context.name.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let mut output = String::new();
+    for context in &data.contexts {
+        let marker = if context.active { "* " } else { "  " };
+        let settings = [
+            ("session", &context.session),
+            ("profile", &context.profile),
+            ("socket", &context.socket_path),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| value.as_ref().map(|value| format!("{}={}", name, value)))
+        .collect::<Vec<_>>()
+        .join("  ");
+        output.push_str(&format!(
+            "{}{:<width$}  {}\n",
+            marker,
+            context.name,
+            settings,
+            width = width
+        ));
+    }
+
+    output.trim_end().to_string()
+}
+
+/// Format config list for human-readable output, as TOML-style lines
+pub fn format_config_list(data: &ConfigListData) -> String {
+    let mut output = format!("# {}\n", data.path);
+    if let Some(project) = &data.project {
+        output.push_str(&format!("# {}\n", project));
+    }
+
+    let lines: Vec<String> = data
+        .entries
+        .iter()
+        .map(|entry| match &entry.value {
+            serde_json::Value::Null => format!("{} = (unset)", entry.key),
+            value => format!("{} = {}", entry.key, value),
+        })
+        .collect();
+    let width = lines.iter().map(|line| line.len()).max().unwrap_or(0);
+
+    for (line, entry) in lines.iter().zip(&data.entries) {
+        match &entry.origin {
+            Some(origin) => output.push_str(&format!("{:<width$}  # {}\n", line, origin)),
+            None => output.push_str(&format!("{}\n", line)),
+        }
+    }
+    output.trim_end().to_string()
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn output_formatter_new_stores_format() {
+        let formatter = OutputFormatter::new(OutputFormat::Json);
+        assert!(matches!(formatter.format, OutputFormat::Json));
+    }
+
+    #[test]
+    fn format_success_human_with_executed_data() {
+        let formatter = OutputFormatter::new(OutputFormat::Human);
+        let response = CommandResponse {
+            id: "cmd-1".to_string(),
+            success: true,
+            data: Some(json!({"executed": true})),
+            error: None,
+        };
+
+        let output = formatter.format_success(&response);
+        assert_eq!(output, "Success");
+    }
+
+    #[test]
+    fn format_success_human_with_generic_data() {
+        let formatter = OutputFormatter::new(OutputFormat::Human);
+        let response = CommandResponse {
+            id: "cmd-1".to_string(),
+            success: true,
+            data: Some(json!({"result": "test"})),
+            error: None,
+        };
+
+        let output = formatter.format_success(&response);
+        assert!(output.contains("result: test"));
+    }
+
+    #[test]
+    fn format_success_human_without_data() {
+        let formatter = OutputFormatter::new(OutputFormat::Human);
+        let response = CommandResponse {
+            id: "cmd-1".to_string(),
+            success: true,
+            data: None,
+            error: None,
+        };
+
+        let output = formatter.format_success(&response);
+        assert_eq!(output, "Success");
+    }
+
+    #[test]
+    fn format_success_json() {
+        let formatter = OutputFormatter::new(OutputFormat::Json);
+        let response = CommandResponse {
+            id: "cmd-1".to_string(),
+            success: true,
+            data: Some(json!({"result": "test"})),
+            error: None,
+        };
+
+        let output = formatter.format_success(&response);
+        assert!(output.contains("\"result\""));
+        assert!(output.contains("\"test\""));
+        // Json format outputs only the data, not the full response envelope
+        assert!(!output.contains("\"success\""));
+    }
+
+    #[test]
+    fn format_success_json_without_data() {
+        let formatter = OutputFormatter::new(OutputFormat::Json);
+        let response = CommandResponse {
+            id: "cmd-1".to_string(),
+            success: true,
+            data: None,
+            error: None,
+        };
+
+        let output = formatter.format_success(&response);
+        assert_eq!(output, "{}");
+    }
+
+    #[test]
+    fn format_success_quiet_returns_empty() {
+        let formatter = OutputFormatter::new(OutputFormat::Quiet);
+        let response = CommandResponse {
+            id: "cmd-1".to_string(),
+            success: true,
+            data: Some(json!({"result": "test"})),
+            error: None,
+        };
+
+        let output = formatter.format_success(&response);
+        assert_eq!(output, "");
+    }
+
+    #[test]
+    fn format_snapshot_displays_tree() {
+        let data = SnapshotData {
+            snapshot: "- RootWebArea \"Example\" [ref=e1]\n  - link \"Home\" [ref=e2]".to_string(),
+            title: "Example".to_string(),
+            url: "https://example.com/".to_string(),
+        };
+
+        let output = format_snapshot(&data, &Terminal::plain());
+        assert!(output.contains("Title: Example"));
+        assert!(output.contains("URL: https://example.com"));
+        assert!(!output.contains("URL: https://example.com/"));
+        assert!(output.contains("RootWebArea"));
+        assert!(output.contains("link \"Home\""));
+    }
+
+    #[test]
+    fn format_snapshot_normalizes_url() {
+        let data = SnapshotData {
+            snapshot: "- RootWebArea".to_string(),
+            title: "Test".to_string(),
+            url: "https://example.com/path/".to_string(),
+        };
+
+        let output = format_snapshot(&data, &Terminal::plain());
+        assert!(output.contains("URL: https://example.com/path"));
+    }
+
+    #[test]
+    fn format_tab_list_displays_tabs() {
+        let data = TabListData {
+            tabs: vec![
+                crate::types::TabInfo {
+                    id: 1,
+                    url: "https://example.com".to_string(),
+                    title: "Example".to_string(),
+                    active: true,
+                },
+                crate::types::TabInfo {
+                    id: 2,
+                    url: "https://test.com".to_string(),
+                    title: "Test".to_string(),
+                    active: false,
+                },
+            ],
+            active_tab_id: 1,
+        };
+
+        let output = format_tab_list(&data, &Terminal::plain());
+        assert!(output.contains("Open tabs"));
+        assert!(output.contains("* [1] Example"));
+        assert!(output.contains("  [2] Test"));
+        assert!(output.contains("https://example.com"));
+        assert!(output.contains("https://test.com"));
+    }
+
+    #[test]
+    fn format_tab_list_truncates_to_terminal_width() {
+        let data = TabListData {
+            tabs: vec![crate::types::TabInfo {
+                id: 1,
+                url: "https://example.com/a/very/long/path".to_string(),
+                title: "A very long page title".to_string(),
+                active: true,
+            }],
+            active_tab_id: 1,
+        };
+        let term = Terminal {
+            color: false,
+            width: Some(28),
+        };
+
+        let output = format_tab_list(&data, &term);
+        assert_eq!(output, "Open tabs:\n* [1] A very lo… https://e… \n");
+    }
+
+    #[test]
+    fn format_snapshot_colors_only_on_terminals() {
+        let term = Terminal {
+            color: true,
+            width: None,
+        };
+        let data = SnapshotData {
+            snapshot: "- button \"Go\" [ref=e1]".to_string(),
+            title: "T".to_string(),
+            url: "https://a.io".to_string(),
+        };
+        let colored = format_snapshot(&data, &term);
+        assert!(colored.contains("\x1b[33m[ref=e1]\x1b[0m"));
+        assert_eq!(
+            format_snapshot(&data, &Terminal::plain()),
+            "Title: T\nURL: https://a.io\n\n- button \"Go\" [ref=e1]"
+        );
+    }
+
+    #[test]
+    fn format_human_snapshot_via_formatter() {
+        let formatter = OutputFormatter::new(OutputFormat::Human);
+        let response = CommandResponse {
+            id: "cmd-1".to_string(),
+            success: true,
+            data: Some(json!({
+                "snapshot": "- RootWebArea \"Google\" [ref=e1]",
+                "title": "Google",
+                "url": "https://www.google.com/"
+            })),
+            error: None,
+        };
+
+        let output = formatter.format_success(&response);
+        assert!(output.contains("Title: Google"));
+        assert!(output.contains("URL: https://www.google.com"));
+        assert!(output.contains("RootWebArea"));
+    }
+
+    #[test]
+    fn format_human_tab_list_via_formatter() {
+        let formatter = OutputFormatter::new(OutputFormat::Human);
+        let response = CommandResponse {
+            id: "cmd-1".to_string(),
+            success: true,
+            data: Some(json!({
+                "activeTabId": 1408441702_i64,
+                "tabs": [
+                    {"active": false, "id": 1408441701_i64, "title": "Google Images", "url": "https://www.google.com/imghp?hl=en&authuser=0&ogbl"},
+                    {"active": true, "id": 1408441702_i64, "title": "Google", "url": "https://www.google.com/"}
+                ]
+            })),
+            error: None,
+        };
+
+        let output = formatter.format_success(&response);
+        assert!(
+            output.contains("Open tabs"),
+            "Expected tab list format, got: {}",
+            output
+        );
+        assert!(output.contains("* [1408441702] Google"));
+        assert!(output.contains("  [1408441701] Google Images"));
+    }
+
+    #[test]
+    fn format_snapshot_markdown_via_formatter() {
+        let formatter = OutputFormatter::new(OutputFormat::Human)
+            .with_snapshot_format(SnapshotFormat::Markdown);
+        let response = CommandResponse {
+            id: "cmd-1".to_string(),
+            success: true,
+            data: Some(json!({
+                "snapshot": "- RootWebArea \"Google\" [ref=e1]\n  - heading1 \"Search\"\n  - link \"Images\" [ref=e2]",
+                "title": "Google",
+                "url": "https://www.google.com/"
+            })),
+            error: None,
+        };
+
+        let output = formatter.format_success(&response);
+        assert!(output.starts_with("# Google"));
+        assert!(output.contains("# Search"));
+        assert!(output.contains("[Images](e2)"));
+        assert!(!output.contains("Title:"));
+    }
+
+    #[test]
+    fn format_snapshot_json_replaces_snapshot_text() {
+        let formatter =
+            OutputFormatter::new(OutputFormat::Json).with_snapshot_format(SnapshotFormat::Yaml);
+        let response = CommandResponse {
+            id: "cmd-1".to_string(),
+            success: true,
+            data: Some(json!({
+                "snapshot": "- RootWebArea \"Google\" [ref=e1]\n  - heading1 \"Search\"",
+                "title": "Google",
+                "url": "https://www.google.com/"
+            })),
+            error: None,
+        };
+
+        let output = formatter.format_success(&response);
+        let value: serde_json::Value = serde_json::from_str(&output).expect("parse json");
+        assert_eq!(
+            value["snapshot"],
+            "- RootWebArea \"Google\" [ref=e1]:\n  - heading \"Search\" [level=1]"
+        );
+        assert_eq!(value["title"], "Google");
+    }
+
+    #[test]
+    fn format_alias_list_aligns_names_and_scopes() {
+        let data = AliasListData {
+            session: "default".to_string(),
+            aliases: vec![
+                crate::types::AliasInfo {
+                    name: "submit".to_string(),
+                    locator: "role=button[name=\"Save\"]".to_string(),
+                    domain: None,
+                    page: None,
+                    file: None,
+                },
+                crate::types::AliasInfo {
+                    name: "login.email".to_string(),
+                    locator: "label=\"Email\"".to_string(),
+                    domain: None,
+                    page: Some("https://example.com/login*".to_string()),
+                    file: None,
+                },
+                crate::types::AliasInfo {
+                    name: "home".to_string(),
+                    locator: "e2".to_string(),
+                    domain: Some("example.com".to_string()),
+                    page: None,
+                    file: None,
+                },
+            ],
+        };
+
+        let output = format_alias_list(&data);
+        assert_eq!(
+            output,
+            "Aliases for session default:\n  @submit       role=button[name=\"Save\"]\n  @login.email  label=\"Email\"  (page https://example.com/login*)\n  @home         e2  (on example.com)"
+        );
+    }
+
+    #[test]
+    fn format_human_success_combines_action_and_diff() {
+        let data = serde_json::json!({
+            "action": {"executed": true},
+            "snapshot": {
+                "snapshot": "- main\n  - alert \"Saved\"",
+                "title": "Editor",
+                "url": "https://example.com/edit/"
+            },
+            "diff": ["+   - alert \"Saved\""]
+        });
+
+        let output = format_human_success(&Some(data), SnapshotFormat::Text, &Terminal::plain());
+        assert_eq!(
+            output,
+            "Success\n\nTitle: Editor\nURL: https://example.com/edit\n\nChanges since previous snapshot:\n+   - alert \"Saved\""
+        );
+    }
+
+    #[test]
+    fn export_snapshot_renders_nested_follow_up_snapshot() {
+        let data = serde_json::json!({
+            "action": {"executed": true},
+            "snapshot": {
+                "snapshot": "- heading1 \"Hello\"",
+                "title": "Hello",
+                "url": "https://example.com/"
+            }
+        });
+
+        let exported = export_snapshot(&data, SnapshotFormat::Markdown);
+        assert_eq!(exported["action"], serde_json::json!({"executed": true}));
+        assert_eq!(
+            exported["snapshot"]["snapshot"],
+            "# Hello\n\n<https://example.com/>"
+        );
+    }
+
+    #[test]
+    fn format_human_success_labels_archived_snapshot() {
+        let data = serde_json::json!({
+            "id": 7,
+            "session": "work",
+            "timestamp": "2026-01-01T00:00:00Z",
+            "title": "Cart",
+            "url": "https://example.com/cart/",
+            "snapshot": "- main"
+        });
+
+        let output = format_human_success(&Some(data), SnapshotFormat::Text, &Terminal::plain());
+        assert_eq!(
+            output,
+            "Archived #7 (session work, 2026-01-01T00:00:00Z)\nTitle: Cart\nURL: https://example.com/cart\n\n- main"
+        );
+    }
+
+    #[test]
+    fn format_archive_search_lists_hits_with_excerpts() {
+        let data = ArchiveSearchData {
+            query: "checkout".to_string(),
+            matches: vec![crate::types::ArchiveMatch {
+                id: 3,
+                session: "default".to_string(),
+                timestamp: "2026-01-01T00:00:00Z".to_string(),
+                title: "Cart".to_string(),
+                url: "https://example.com/cart".to_string(),
+                excerpts: vec!["- button \"Checkout\" [ref=e4]".to_string()],
+            }],
+        };
+
+        assert_eq!(
+            format_archive_search(&data),
+            "#3 2026-01-01T00:00:00Z [default] Cart\n    https://example.com/cart\n    > - button \"Checkout\" [ref=e4]"
+        );
+    }
+
+    #[test]
+    fn format_context_list_marks_active_context() {
+        let data = ContextListData {
+            active: Some("staging".to_string()),
+            contexts: vec![
+                crate::types::ContextInfo {
+                    name: "local".to_string(),
+                    active: false,
+                    session: Some("dev".to_string()),
+                    profile: None,
+                    socket_path: None,
+                },
+                crate::types::ContextInfo {
+                    name: "staging".to_string(),
+                    active: true,
+                    session: Some("stg".to_string()),
+                    profile: Some("/profiles/stg".to_string()),
+                    socket_path: Some("/run/browser-stg.sock".to_string()),
+                },
+            ],
+        };
+
+        assert_eq!(
+            format_context_list(&data),
+            "  local    session=dev\n* staging  session=stg  profile=/profiles/stg  socket=/run/browser-stg.sock"
+        );
+    }
+
+    #[test]
+    fn format_alias_list_empty() {
+        let data = AliasListData {
+            session: "work".to_string(),
+            aliases: Vec::new(),
+        };
+        assert_eq!(format_alias_list(&data), "No aliases for session work");
+    }
+
+    #[test]
+    fn error_envelope_includes_kind_exit_code_and_command_id() {
+        let failure = Failure {
+            error: crate::error::CliError::CommandFailed("Element not found".to_string()),
+            command_id: Some("cmd-1".to_string()),
+            reported: false,
+        };
+
+        let envelope = serde_json::to_value(error_envelope(&failure)).unwrap();
+        assert_eq!(
+            envelope,
+            json!({
+                "success": false,
+                "error": {
+                    "kind": "command_failed",
+                    "message": "command failed: Element not found",
+                    "exit_code": 1,
+                    "command_id": "cmd-1"
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn error_envelope_without_command_has_null_id() {
+        let failure = Failure::from(crate::error::CliError::DaemonNotRunning(
+            "Daemon is not responding".to_string(),
+        ));
+
+        let envelope = serde_json::to_value(error_envelope(&failure)).unwrap();
+        assert_eq!(envelope["error"]["kind"], "daemon_not_running");
+        assert_eq!(envelope["error"]["exit_code"], 2);
+        assert!(envelope["error"]["command_id"].is_null());
+    }
+
+    #[test]
+    fn format_human_success_prints_daemon_status_and_stop() {
+        let status = json!({
+            "pid": 4242,
+            "uptime_ms": 7_980_000,
+            "version": "0.2.4",
+            "socket": "/run/user/1000/browser/daemon.sock",
+            "sessions": 3
+        });
+        let output = format_human_success(&Some(status), SnapshotFormat::Text, &Terminal::plain());
+        assert_eq!(
+            output,
+            "Daemon running (pid 4242, version 0.2.4)\n  socket:   /run/user/1000/browser/daemon.sock\n  uptime:   2h 13m\n  sessions: 3"
+        );
+
+        let stop = json!({"stopped": true, "pid": 4242, "signal": "SIGTERM"});
+        let output = format_human_success(&Some(stop), SnapshotFormat::Text, &Terminal::plain());
+        assert_eq!(output, "Daemon stopped with SIGTERM (pid 4242)");
+
+        let stop = json!({"stopped": false});
+        let output = format_human_success(&Some(stop), SnapshotFormat::Text, &Terminal::plain());
+        assert_eq!(output, "Daemon is not running");
+    }
+
+    #[test]
+    fn format_human_success_prints_doctor_checks_with_remedies() {
+        let doctor = json!({
+            "checks": [
+                {"name": "config", "status": "pass", "message": "/home/me/.config/browser/config.toml"},
+                {"name": "daemon", "status": "warn", "message": "not running", "remedy": "it starts with the next command"},
+                {"name": "plugin", "status": "fail", "message": "/opt/browser-daemon is not executable", "remedy": "run `chmod +x /opt/browser-daemon`"}
+            ]
+        });
+        let output = format_human_success(&Some(doctor), SnapshotFormat::Text, &Terminal::plain());
+        assert_eq!(
+            output,
+            "pass  config     /home/me/.config/browser/config.toml\n\
+             warn  daemon     not running\n\
+             \x20     -> it starts with the next command\n\
+             fail  plugin     /opt/browser-daemon is not executable\n\
+             \x20     -> run `chmod +x /opt/browser-daemon`\n\
+             \n\
+             1 passed, 1 warning, 1 failed"
+        );
+    }
+
+    #[test]
+    fn format_human_success_prints_versions_and_flags_mismatches() {
+        let versions = json!({
+            "cli": {"version": "0.2.4", "protocol_version": "1.1"},
+            "daemon": {"version": null, "protocol_version": null},
+            "extensions": [
+                {"session": "default", "session_id": "s-1", "version": "0.1.1", "protocol_version": "1.1"},
+                {"session": "work", "session_id": "s-2", "version": "0.3.0", "protocol_version": "2.0"}
+            ]
+        });
+        let output =
+            format_human_success(&Some(versions), SnapshotFormat::Text, &Terminal::plain());
+        assert_eq!(
+            output,
+            "cli        0.2.4  protocol 1.1\n\
+             daemon     unknown  predates version reporting\n\
+             extension  0.1.1  protocol 1.1  session default\n\
+             extension  0.3.0  protocol 2.0 (mismatch)  session work"
+        );
+
+        let versions = json!({
+            "cli": {"version": "0.2.4", "protocol_version": "1.1"},
+            "daemon": null,
+            "extensions": []
+        });
+        let output =
+            format_human_success(&Some(versions), SnapshotFormat::Text, &Terminal::plain());
+        assert_eq!(
+            output,
+            "cli        0.2.4  protocol 1.1\ndaemon     not running"
+        );
+    }
+
+    #[test]
+    fn format_human_success_prints_scalar_lists_one_per_line() {
+        let data = json!(["https://example.com", 2, true]);
+        let output = format_human_success(&Some(data), SnapshotFormat::Text, &Terminal::plain());
+        assert_eq!(output, "https://example.com\n2\ntrue");
+    }
+
+    #[test]
+    fn format_success_ndjson_is_one_line() {
+        let formatter = OutputFormatter::new(OutputFormat::Ndjson);
+        let response = CommandResponse {
+            id: "cmd-1".to_string(),
+            success: true,
+            data: Some(json!({"result": "test", "nested": {"a": 1}})),
+            error: None,
+        };
+
+        let output = formatter.format_success(&response);
+        assert_eq!(output, r#"{"nested":{"a":1},"result":"test"}"#);
+    }
+
+    #[test]
+    fn format_success_yaml() {
+        let formatter = OutputFormatter::new(OutputFormat::Yaml);
+        let response = CommandResponse {
+            id: "cmd-1".to_string(),
+            success: true,
+            data: Some(json!({"title": "Example", "url": "https://example.com"})),
+            error: None,
+        };
+
+        let output = formatter.format_success(&response);
+        assert_eq!(output, "title: Example\nurl: https://example.com");
+    }
+
+    #[test]
+    fn format_success_table_for_tab_list() {
+        let formatter = OutputFormatter::new(OutputFormat::Table);
+        let response = CommandResponse {
+            id: "cmd-1".to_string(),
+            success: true,
+            data: Some(json!({
+                "tabs": [{"id": 1, "url": "https://example.com", "title": "Example", "active": true}],
+                "activeTabId": 1
+            })),
+            error: None,
+        };
+
+        let output = formatter.format_success(&response);
+        assert_eq!(
+            output,
+            "ACTIVE  ID  TITLE    URL\ntrue    1   Example  https://example.com"
+        );
+    }
+
+    #[test]
+    fn format_success_table_keeps_snapshot_as_text() {
+        let formatter = OutputFormatter::new(OutputFormat::Table);
+        let response = CommandResponse {
+            id: "cmd-1".to_string(),
+            success: true,
+            data: Some(json!({"snapshot": "- main", "title": "T", "url": "https://t.test"})),
+            error: None,
+        };
+
+        let output = formatter.format_success(&response);
+        assert_eq!(output, "Title: T\nURL: https://t.test\n\n- main");
+    }
+
+    #[test]
+    fn format_success_template() {
+        let formatter = OutputFormatter::new(OutputFormat::Human)
+            .with_template(Some("{{title}} {{url}}"))
+            .unwrap();
+        let response = CommandResponse {
+            id: "cmd-1".to_string(),
+            success: true,
+            data: Some(json!({"title": "Example", "url": "https://example.com"})),
+            error: None,
+        };
+
+        let output = formatter.format_success(&response);
+        assert_eq!(output, "Example https://example.com");
+    }
+
+    #[test]
+    fn with_template_rejects_missing_and_invalid_templates() {
+        let missing = OutputFormatter::new(OutputFormat::Template).with_template(None);
+        assert!(matches!(missing, Err(CliError::InvalidArguments(_))));
+
+        let invalid = OutputFormatter::new(OutputFormat::Human).with_template(Some("{{#each x}}"));
+        assert!(matches!(invalid, Err(CliError::InvalidArguments(_))));
+    }
+
+    #[test]
+    fn format_config_list_prints_key_value_lines() {
+        let data = ConfigListData {
+            path: "/home/u/.config/browser/config.toml".to_string(),
+            project: None,
+            entries: vec![
+                crate::types::ConfigEntry {
+                    key: "session".to_string(),
+                    value: json!("work"),
+                    origin: None,
+                },
+                crate::types::ConfigEntry {
+                    key: "archive.enabled".to_string(),
+                    value: json!(true),
+                    origin: None,
+                },
+            ],
+        };
+
+        assert_eq!(
+            format_config_list(&data),
+            "# /home/u/.config/browser/config.toml\nsession = \"work\"\narchive.enabled = true"
+        );
+    }
+
+    #[test]
+    fn format_config_list_aligns_origins() {
+        let data = ConfigListData {
+            path: "/home/u/.config/browser/config.toml".to_string(),
+            project: Some("/src/shop/.browser.toml".to_string()),
+            entries: vec![
+                crate::types::ConfigEntry {
+                    key: "session".to_string(),
+                    value: json!("shop"),
+                    origin: Some("/src/shop/.browser.toml".to_string()),
+                },
+                crate::types::ConfigEntry {
+                    key: "base_url".to_string(),
+                    value: serde_json::Value::Null,
+                    origin: Some("default".to_string()),
+                },
+            ],
+        };
+
+        assert_eq!(
+            format_config_list(&data),
+            "# /home/u/.config/browser/config.toml\n# /src/shop/.browser.toml\nsession = \"shop\"    # /src/shop/.browser.toml\nbase_url = (unset)  # default"
+        );
+    }
+
+    fn sample_meta() -> ResponseMeta {
+        ResponseMeta {
+            command_id: "trace-42".to_string(),
+            session: "default".to_string(),
+            duration_ms: crate::types::DurationBreakdown {
+                connect: 1,
+                daemon: 40,
+                total: 45,
+            },
+        }
+    }
+
+    #[test]
+    fn format_success_with_meta_adds_footer_in_human_mode() {
+        let formatter = OutputFormatter::new(OutputFormat::Human);
+        let response = CommandResponse {
+            id: "trace-42".to_string(),
+            success: true,
+            data: None,
+            error: None,
+        };
+
+        let output = formatter.format_success_with_meta(&response, &sample_meta());
+        assert_eq!(
+            output,
+            "Success\n[command trace-42, session default, connect 1ms, daemon 40ms, total 45ms]"
+        );
+    }
+
+    #[test]
+    fn format_success_with_meta_adds_meta_field_in_json() {
+        let formatter = OutputFormatter::new(OutputFormat::Ndjson);
+        let response = CommandResponse {
+            id: "trace-42".to_string(),
+            success: true,
+            data: Some(json!({"url": "https://example.com"})),
+            error: None,
+        };
+
+        let output = formatter.format_success_with_meta(&response, &sample_meta());
+        assert_eq!(
+            output,
+            r#"{"meta":{"command_id":"trace-42","duration_ms":{"connect":1,"daemon":40,"total":45},"session":"default"},"url":"https://example.com"}"#
+        );
+    }
+
+    #[test]
+    fn with_meta_wraps_non_object_data() {
+        let wrapped = with_meta(Some(json!(["a", "b"])), &sample_meta());
+        assert_eq!(wrapped["data"], json!(["a", "b"]));
+        assert_eq!(wrapped["meta"]["command_id"], "trace-42");
+    }
+
+    #[test]
+    fn print_json_serializes_data() {
+        let data = json!({"test": "value"});
+        let result = print_json(&data);
+        assert!(result.is_ok());
+    }
+}
+
+// =============================================================================
+// Helper Functions
+// =============================================================================
+
+/// Print a success message to stdout
+pub fn print_success(message: &str) {
+    println!("{}", message);
+}
+
+/// Print an error message to stderr
+pub fn print_error(message: &str) {
+    eprintln!("{}", message);
+}
+
+/// Print JSON data to stdout
+pub fn print_json<T: serde::Serialize>(data: &T) -> Result<()> {
+    let json = serde_json::to_string_pretty(data)?;
+    println!("{}", json);
+    Ok(())
+}
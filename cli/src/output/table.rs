@@ -0,0 +1,176 @@
+//! Table output
+//!
+//! Lays out response data as aligned columns. A payload holding a list of
+//! objects (tabs, aliases, search matches) becomes one row per object with a
+//! column per field; any other object becomes a two-column KEY/VALUE table.
+
+use serde_json::{Map, Value};
+
+/// Separator between columns
+const COLUMN_GAP: &str = "  ";
+
+/// Render response data as a table, or `None` if it has no tabular shape
+pub fn render(data: &Value) -> Option<String> {
+    let Value::Object(map) = data else {
+        return rows_table(data);
+    };
+
+    // Prefer the first list of objects in the payload
+    if let Some(table) = map.values().find_map(rows_table) {
+        return Some(table);
+    }
+
+    Some(key_value_table(map))
+}
+
+/// One row per object in an array, columns in order of first appearance
+fn rows_table(value: &Value) -> Option<String> {
+    let Value::Array(items) = value else {
+        return None;
+    };
+    if items.is_empty() || !items.iter().all(Value::is_object) {
+        return None;
+    }
+
+    let mut columns: Vec<&str> = Vec::new();
+    for item in items {
+        if let Value::Object(map) = item {
+            for key in map.keys() {
+                if !columns.contains(&key.as_str()) {
+                    columns.push(key);
+                }
+            }
+        }
+    }
+
+    let header = columns.iter().map(|c| header_name(c)).collect();
+    let rows = items
+        .iter()
+        .map(|item| {
+            columns
+                .iter()
+                .map(|column| item.get(*column).map(cell).unwrap_or_default())
+                .collect()
+        })
+        .collect();
+
+    Some(layout(header, rows))
+}
+
+fn key_value_table(map: &Map<String, Value>) -> String {
+    let rows = map
+        .iter()
+        .map(|(key, value)| vec![key.clone(), cell(value)])
+        .collect();
+    layout(vec!["KEY".to_string(), "VALUE".to_string()], rows)
+}
+
+/// Align cells into columns; the last column is not padded
+fn layout(header: Vec<String>, rows: Vec<Vec<String>>) -> String {
+    let mut widths: Vec<usize> = header.iter().map(|h| h.chars().count()).collect();
+    for row in &rows {
+        for (width, value) in widths.iter_mut().zip(row) {
+            *width = (*width).max(value.chars().count());
+        }
+    }
+
+    std::iter::once(&header)
+        .chain(&rows)
+        .map(|row| {
+            let last = row.len().saturating_sub(1);
+            row.iter()
+                .enumerate()
+                .map(|(i, value)| {
+                    if i == last {
+                        value.clone()
+                    } else {
+                        format!("{:<width$}", value, width = widths[i])
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(COLUMN_GAP)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Column header for a field name: `activeTabId` becomes `ACTIVE_TAB_ID`
+fn header_name(key: &str) -> String {
+    let mut name = String::new();
+    for (i, c) in key.chars().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            name.push('_');
+        }
+        name.push(c.to_ascii_uppercase());
+    }
+    name
+}
+
+/// Single-line cell text for a value
+fn cell(value: &Value) -> String {
+    let text = match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Array(items) if items.iter().all(|item| !item.is_object()) => {
+            items.iter().map(cell).collect::<Vec<_>>().join(", ")
+        }
+        other => other.to_string(),
+    };
+    text.replace(['\n', '\t'], " ")
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn list_of_objects_becomes_rows() {
+        let data = json!({
+            "activeTabId": 2,
+            "tabs": [
+                {"id": 1, "title": "Home", "url": "https://example.com", "active": false},
+                {"id": 12, "title": "Docs", "url": "https://example.com/docs", "active": true}
+            ]
+        });
+
+        assert_eq!(
+            render(&data).unwrap(),
+            "ACTIVE  ID  TITLE  URL\nfalse   1   Home   https://example.com\ntrue    12  Docs   https://example.com/docs"
+        );
+    }
+
+    #[test]
+    fn missing_fields_are_blank() {
+        let data = json!([{"name": "a", "domain": "example.com"}, {"name": "bb"}]);
+        assert_eq!(
+            render(&data).unwrap(),
+            "DOMAIN       NAME\nexample.com  a\n             bb"
+        );
+    }
+
+    #[test]
+    fn plain_object_becomes_key_value_table() {
+        let data = json!({"title": "Example", "url": "https://example.com", "tags": ["a", "b"]});
+        assert_eq!(
+            render(&data).unwrap(),
+            "KEY    VALUE\ntags   a, b\ntitle  Example\nurl    https://example.com"
+        );
+    }
+
+    #[test]
+    fn scalars_have_no_table() {
+        assert_eq!(render(&json!("text")), None);
+        assert_eq!(render(&json!([1, 2])), None);
+    }
+
+    #[test]
+    fn header_name_splits_camel_case() {
+        assert_eq!(header_name("activeTabId"), "ACTIVE_TAB_ID");
+        assert_eq!(header_name("url"), "URL");
+    }
+}
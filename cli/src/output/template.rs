@@ -0,0 +1,316 @@
+//! Template output
+//!
+//! A small mustache-like engine for `--template`:
+//!
+//! - `{{path}}` inserts a field; paths are dot-separated and may index
+//!   arrays (`tabs.0.url`). `{{.}}` is the current item.
+//! - `{{#each path}}...{{/each}}` repeats its body for every array element,
+//!   with the element as the current item and `{{@index}}` as its position.
+//! - `{{#if path}}...{{else}}...{{/if}}` renders its body when the value is
+//!   not null, false, zero or empty.
+//!
+//! Inside `each`, paths are looked up on the current item first and then on
+//! the enclosing items. Missing fields render as nothing. Strings are
+//! inserted as-is and other values as compact JSON. `\n`, `\t` and `\\` in
+//! literal text are unescaped so templates can be written on one shell line.
+
+use crate::query::truthy;
+use serde_json::Value;
+
+/// A parsed template
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Field(String),
+    Each(String, Vec<Node>),
+    If(String, Vec<Node>, Vec<Node>),
+}
+
+/// Block being parsed, with the nodes collected so far
+enum Block {
+    Root,
+    Each(String),
+    If(String, Option<Vec<Node>>),
+}
+
+impl Template {
+    /// Parse a template string
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut stack: Vec<(Block, Vec<Node>)> = vec![(Block::Root, Vec::new())];
+        let mut rest = source;
+
+        while !rest.is_empty() {
+            let Some(start) = rest.find("{{") else {
+                push_text(&mut stack, rest);
+                break;
+            };
+            push_text(&mut stack, &rest[..start]);
+
+            let tag_start = &rest[start + 2..];
+            let Some(end) = tag_start.find("}}") else {
+                return Err(format!(
+                    "unclosed tag at offset {} in template",
+                    source.len() - rest.len() + start
+                ));
+            };
+            let tag = tag_start[..end].trim();
+            rest = &tag_start[end + 2..];
+
+            if let Some(path) = tag.strip_prefix("#each") {
+                stack.push((Block::Each(block_path(path, "each")?), Vec::new()));
+            } else if let Some(path) = tag.strip_prefix("#if") {
+                stack.push((Block::If(block_path(path, "if")?, None), Vec::new()));
+            } else if tag == "else" {
+                let Some((Block::If(_, then @ None), nodes)) = stack.last_mut() else {
+                    return Err("{{else}} outside {{#if}} in template".to_string());
+                };
+                *then = Some(std::mem::take(nodes));
+            } else if let Some(name) = tag.strip_prefix('/') {
+                let (block, nodes) = stack.pop().unwrap_or((Block::Root, Vec::new()));
+                let node = match (block, name.trim()) {
+                    (Block::Each(path), "each") => Node::Each(path, nodes),
+                    (Block::If(path, Some(then)), "if") => Node::If(path, then, nodes),
+                    (Block::If(path, None), "if") => Node::If(path, nodes, Vec::new()),
+                    _ => return Err(format!("unexpected {{{{/{}}}}} in template", name.trim())),
+                };
+                if let Some((_, parent)) = stack.last_mut() {
+                    parent.push(node);
+                }
+            } else if tag.is_empty() || tag.starts_with(['#', '/']) {
+                return Err(format!("invalid tag {{{{{}}}}} in template", tag));
+            } else {
+                if let Some((_, nodes)) = stack.last_mut() {
+                    nodes.push(Node::Field(tag.to_string()));
+                }
+            }
+        }
+
+        match stack.pop() {
+            Some((Block::Root, nodes)) if stack.is_empty() => Ok(Self { nodes }),
+            Some((Block::Each(_), _)) => Err("missing {{/each}} in template".to_string()),
+            _ => Err("missing {{/if}} in template".to_string()),
+        }
+    }
+
+    /// Render the template against response data
+    pub fn render(&self, data: &Value) -> String {
+        let mut out = String::new();
+        render_nodes(&self.nodes, &mut vec![Scope::new(data)], &mut out);
+        out
+    }
+}
+
+/// A value in scope, with its position when it is an `each` element
+struct Scope<'a> {
+    value: &'a Value,
+    index: Option<usize>,
+}
+
+impl<'a> Scope<'a> {
+    fn new(value: &'a Value) -> Self {
+        Self { value, index: None }
+    }
+}
+
+fn render_nodes<'a>(nodes: &'a [Node], scopes: &mut Vec<Scope<'a>>, out: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Field(path) => {
+                if path == "@index" {
+                    if let Some(index) = scopes.last().and_then(|scope| scope.index) {
+                        out.push_str(&index.to_string());
+                    }
+                } else if let Some(value) = lookup(scopes, path) {
+                    out.push_str(&display(value));
+                }
+            }
+            Node::Each(path, body) => {
+                if let Some(Value::Array(items)) = lookup(scopes, path) {
+                    for (index, item) in items.iter().enumerate() {
+                        scopes.push(Scope {
+                            value: item,
+                            index: Some(index),
+                        });
+                        render_nodes(body, scopes, out);
+                        scopes.pop();
+                    }
+                }
+            }
+            Node::If(path, then, otherwise) => {
+                let branch = if lookup(scopes, path).is_some_and(truthy) {
+                    then
+                } else {
+                    otherwise
+                };
+                render_nodes(branch, scopes, out);
+            }
+        }
+    }
+}
+
+/// Resolve a path against the innermost scope that has its first segment
+fn lookup<'a>(scopes: &[Scope<'a>], path: &str) -> Option<&'a Value> {
+    if path == "." || path == "this" {
+        return scopes.last().map(|scope| scope.value);
+    }
+
+    let mut segments = path.split('.');
+    let first = segments.next()?;
+    let mut value = scopes
+        .iter()
+        .rev()
+        .find_map(|scope| child(scope.value, first))?;
+    for segment in segments {
+        value = child(value, segment)?;
+    }
+    Some(value)
+}
+
+fn child<'a>(value: &'a Value, segment: &str) -> Option<&'a Value> {
+    match value {
+        Value::Object(map) => map.get(segment),
+        Value::Array(items) => items.get(segment.parse::<usize>().ok()?),
+        _ => None,
+    }
+}
+
+fn display(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+fn block_path(path: &str, block: &str) -> Result<String, String> {
+    let path = path.trim();
+    if path.is_empty() {
+        return Err(format!("{{{{#{}}}}} needs a path in template", block));
+    }
+    Ok(path.to_string())
+}
+
+/// Append literal text, unescaping `\n`, `\t` and `\\`
+fn push_text(stack: &mut [(Block, Vec<Node>)], text: &str) {
+    if text.is_empty() {
+        return;
+    }
+
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some('\\') => unescaped.push('\\'),
+            Some(other) => {
+                unescaped.push('\\');
+                unescaped.push(other);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+
+    if let Some((_, nodes)) = stack.last_mut() {
+        nodes.push(Node::Text(unescaped));
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn render(source: &str, data: &Value) -> String {
+        Template::parse(source).expect("parse").render(data)
+    }
+
+    #[test]
+    fn inserts_fields() {
+        let data = json!({"title": "Example", "url": "https://example.com", "count": 3});
+        assert_eq!(
+            render("{{title}} {{ url }} ({{count}})", &data),
+            "Example https://example.com (3)"
+        );
+    }
+
+    #[test]
+    fn nested_paths_and_indices() {
+        let data = json!({"tabs": [{"url": "a"}, {"url": "b"}]});
+        assert_eq!(render("{{tabs.1.url}}", &data), "b");
+        assert_eq!(render("[{{tabs.5.url}}]", &data), "[]");
+    }
+
+    #[test]
+    fn each_iterates_with_index_and_outer_scope() {
+        let data =
+            json!({"activeTabId": 2, "tabs": [{"id": 1, "url": "a"}, {"id": 2, "url": "b"}]});
+        assert_eq!(
+            render(
+                "{{#each tabs}}{{@index}}:{{id}}={{url}}/{{activeTabId}}\\n{{/each}}",
+                &data
+            ),
+            "0:1=a/2\n1:2=b/2\n"
+        );
+    }
+
+    #[test]
+    fn if_else_uses_truthiness() {
+        let data = json!({"tabs": [{"url": "a", "active": true}, {"url": "b", "active": false}]});
+        assert_eq!(
+            render(
+                "{{#each tabs}}{{#if active}}*{{else}} {{/if}}{{url}} {{/each}}",
+                &data
+            ),
+            "*a  b "
+        );
+    }
+
+    #[test]
+    fn dot_is_current_item() {
+        let data = json!({"lines": ["x", "y"]});
+        assert_eq!(render("{{#each lines}}{{.}};{{/each}}", &data), "x;y;");
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            Template::parse("{{title").unwrap_err(),
+            "unclosed tag at offset 0 in template"
+        );
+        assert_eq!(
+            Template::parse("{{#each tabs}}x").unwrap_err(),
+            "missing {{/each}} in template"
+        );
+        assert_eq!(
+            Template::parse("{{/if}}").unwrap_err(),
+            "unexpected {{/if}} in template"
+        );
+        assert_eq!(
+            Template::parse("{{#if a}}{{/each}}").unwrap_err(),
+            "unexpected {{/each}} in template"
+        );
+        assert_eq!(
+            Template::parse("{{else}}").unwrap_err(),
+            "{{else}} outside {{#if}} in template"
+        );
+        assert_eq!(
+            Template::parse("{{#each}}{{/each}}").unwrap_err(),
+            "{{#each}} needs a path in template"
+        );
+    }
+}
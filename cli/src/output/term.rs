@@ -0,0 +1,265 @@
+//! Terminal detection and styling for human output
+//!
+//! Color and width are only used when the stream is a terminal (or color is
+//! forced with `--color always`), so piped output is byte-identical to plain
+//! mode. `NO_COLOR` disables color unless it is explicitly requested.
+
+use std::io::IsTerminal;
+
+use crate::types::ColorChoice;
+
+/// Environment variable that disables color (https://no-color.org)
+const ENV_NO_COLOR: &str = "NO_COLOR";
+
+/// Fallback for the terminal width when the size cannot be queried
+const ENV_COLUMNS: &str = "COLUMNS";
+
+/// Text styles used by the human formatter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    Bold,
+    Dim,
+    Red,
+    Green,
+    Yellow,
+    Cyan,
+}
+
+impl Style {
+    fn code(self) -> &'static str {
+        match self {
+            Style::Bold => "1",
+            Style::Dim => "2",
+            Style::Red => "31",
+            Style::Green => "32",
+            Style::Yellow => "33",
+            Style::Cyan => "36",
+        }
+    }
+}
+
+/// Capabilities of the stream being written to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Terminal {
+    /// Whether to emit ANSI color codes
+    pub color: bool,
+    /// Width in columns, when the stream is a terminal
+    pub width: Option<usize>,
+}
+
+impl Terminal {
+    /// A stream with no color and no width limit, as used for pipes
+    pub fn plain() -> Self {
+        Self::default()
+    }
+
+    /// Detect the capabilities of stdout
+    pub fn stdout(choice: ColorChoice) -> Self {
+        let is_tty = std::io::stdout().is_terminal();
+        Self {
+            color: color_enabled(choice, is_tty, no_color()),
+            width: if is_tty { terminal_width() } else { None },
+        }
+    }
+
+    /// Detect the capabilities of stderr; only color applies
+    pub fn stderr(choice: ColorChoice) -> Self {
+        Self {
+            color: color_enabled(choice, std::io::stderr().is_terminal(), no_color()),
+            width: None,
+        }
+    }
+
+    /// Wrap text in a style when color is enabled
+    pub fn paint(&self, text: &str, style: Style) -> String {
+        if self.color && !text.is_empty() {
+            format!("\x1b[{}m{}\x1b[0m", style.code(), text)
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// Highlight roles and refs in snapshot text
+    pub fn highlight_snapshot(&self, snapshot: &str) -> String {
+        if !self.color {
+            return snapshot.to_string();
+        }
+
+        snapshot
+            .split('\n')
+            .map(|line| self.highlight_snapshot_line(line))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Highlight one `- role "name" [ref=eN]` line
+    fn highlight_snapshot_line(&self, line: &str) -> String {
+        let body = line.trim_start_matches(' ');
+        let indent = &line[..line.len() - body.len()];
+        let Some(item) = body.strip_prefix("- ") else {
+            return line.to_string();
+        };
+
+        let role_end = item.find([' ', ':']).unwrap_or(item.len());
+        let (role, rest) = item.split_at(role_end);
+        let rest = match rest.find("[ref=") {
+            Some(start) => {
+                let end = rest[start..]
+                    .find(']')
+                    .map(|i| start + i + 1)
+                    .unwrap_or(rest.len());
+                format!(
+                    "{}{}{}",
+                    &rest[..start],
+                    self.paint(&rest[start..end], Style::Yellow),
+                    &rest[end..]
+                )
+            }
+            None => rest.to_string(),
+        };
+
+        format!("{}- {}{}", indent, self.paint(role, Style::Cyan), rest)
+    }
+}
+
+/// Decide whether to color a stream
+fn color_enabled(choice: ColorChoice, is_tty: bool, no_color: bool) -> bool {
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => is_tty && !no_color,
+    }
+}
+
+/// Whether `NO_COLOR` is set to a non-empty value
+fn no_color() -> bool {
+    std::env::var_os(ENV_NO_COLOR).is_some_and(|value| !value.is_empty())
+}
+
+/// Width of the terminal attached to stdout
+fn terminal_width() -> Option<usize> {
+    ioctl_width().or_else(|| {
+        std::env::var(ENV_COLUMNS)
+            .ok()
+            .and_then(|value| value.trim().parse().ok())
+            .filter(|&width| width > 0)
+    })
+}
+
+#[cfg(unix)]
+fn ioctl_width() -> Option<usize> {
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    // SAFETY: TIOCGWINSZ only writes into the winsize struct we pass
+    let result = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) };
+    (result == 0 && size.ws_col > 0).then_some(size.ws_col as usize)
+}
+
+#[cfg(not(unix))]
+fn ioctl_width() -> Option<usize> {
+    None
+}
+
+/// Shorten text to at most `max` characters, ending with an ellipsis
+pub fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    if max == 0 {
+        return String::new();
+    }
+
+    let mut short: String = text.chars().take(max - 1).collect();
+    short.push('…');
+    short
+}
+
+/// Fit a title and URL, separated by a space, into `width` characters
+///
+/// The title keeps up to half of the width and the URL up to the rest; room
+/// one side does not need goes to the other.
+pub fn fit_title_and_url(title: &str, url: &str, width: usize) -> (String, String) {
+    let title_len = title.chars().count();
+    let url_len = url.chars().count();
+    let room = width.saturating_sub(1);
+    if title_len + url_len <= room {
+        return (title.to_string(), url.to_string());
+    }
+
+    let title_floor = title_len.min(room / 2);
+    let url_max = url_len.min(room - title_floor);
+    let title_max = room - url_max;
+    (truncate(title, title_max), truncate(url, url_max))
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_follows_choice_tty_and_no_color() {
+        assert!(color_enabled(ColorChoice::Auto, true, false));
+        assert!(!color_enabled(ColorChoice::Auto, false, false));
+        assert!(!color_enabled(ColorChoice::Auto, true, true));
+        assert!(color_enabled(ColorChoice::Always, false, true));
+        assert!(!color_enabled(ColorChoice::Never, true, false));
+    }
+
+    #[test]
+    fn plain_terminal_leaves_text_unchanged() {
+        let term = Terminal::plain();
+        let snapshot = "- main\n  - button \"Save\" [ref=e1]";
+        assert_eq!(term.paint("x", Style::Red), "x");
+        assert_eq!(term.highlight_snapshot(snapshot), snapshot);
+    }
+
+    #[test]
+    fn highlights_roles_and_refs() {
+        let term = Terminal {
+            color: true,
+            width: None,
+        };
+        assert_eq!(
+            term.highlight_snapshot("- main\n  - button \"Save\" [ref=e1]\n  text"),
+            "- \x1b[36mmain\x1b[0m\n  - \x1b[36mbutton\x1b[0m \"Save\" \x1b[33m[ref=e1]\x1b[0m\n  text"
+        );
+        assert_eq!(
+            term.highlight_snapshot("- link: Home"),
+            "- \x1b[36mlink\x1b[0m: Home"
+        );
+    }
+
+    #[test]
+    fn truncate_adds_ellipsis() {
+        assert_eq!(truncate("example", 10), "example");
+        assert_eq!(truncate("example", 4), "exa…");
+        assert_eq!(truncate("example", 0), "");
+    }
+
+    #[test]
+    fn fit_title_and_url_shortens_title_first() {
+        assert_eq!(
+            fit_title_and_url("Title", "https://a.io", 40),
+            ("Title".to_string(), "https://a.io".to_string())
+        );
+        assert_eq!(
+            fit_title_and_url("A very long page title", "https://a.io", 24),
+            ("A very lon…".to_string(), "https://a.io".to_string())
+        );
+        assert_eq!(
+            fit_title_and_url("Docs", "https://example.com/a/very/long/path", 21),
+            ("Docs".to_string(), "https://example…".to_string())
+        );
+        assert_eq!(
+            fit_title_and_url(
+                "A very long page title",
+                "https://example.com/a/very/long/path",
+                21
+            ),
+            ("A very lo…".to_string(), "https://e…".to_string())
+        );
+    }
+}
@@ -0,0 +1,231 @@
+//! YAML output
+//!
+//! Emits response data as block-style YAML. Strings that YAML would read as
+//! another type are written as JSON-style double-quoted scalars, which are
+//! also valid YAML; multi-line strings become literal blocks.
+
+use serde_json::Value;
+
+/// Spaces per nesting level
+const INDENT_WIDTH: usize = 2;
+
+/// Render a JSON value as a YAML document (without a trailing newline)
+pub fn render(value: &Value) -> String {
+    let mut out = String::new();
+    match value {
+        Value::Object(map) if !map.is_empty() => write_mapping(value, 0, &mut out),
+        Value::Array(items) if !items.is_empty() => write_sequence(value, 0, &mut out),
+        other => {
+            out.push_str(&scalar(other, 0));
+            out.push('\n');
+        }
+    }
+    out.truncate(out.trim_end_matches('\n').len());
+    out
+}
+
+fn write_mapping(value: &Value, depth: usize, out: &mut String) {
+    let Value::Object(map) = value else {
+        return;
+    };
+    let indent = " ".repeat(depth * INDENT_WIDTH);
+
+    for (key, child) in map {
+        out.push_str(&format!("{}{}:", indent, string_scalar(key, depth)));
+        write_child(child, depth, out);
+    }
+}
+
+fn write_sequence(value: &Value, depth: usize, out: &mut String) {
+    let Value::Array(items) = value else {
+        return;
+    };
+    let indent = " ".repeat(depth * INDENT_WIDTH);
+
+    for item in items {
+        match item {
+            // Mappings start on the dash line, compact style
+            Value::Object(map) if !map.is_empty() => {
+                let mut nested = String::new();
+                write_mapping(item, depth + 1, &mut nested);
+                let nested = &nested[(depth + 1) * INDENT_WIDTH..];
+                out.push_str(&format!("{}- {}", indent, nested));
+            }
+            Value::Array(inner) if !inner.is_empty() => {
+                out.push_str(&format!("{}-\n", indent));
+                write_sequence(item, depth + 1, out);
+            }
+            other => out.push_str(&format!("{}- {}\n", indent, scalar(other, depth + 1))),
+        }
+    }
+}
+
+/// Write the value of a mapping entry, after its `key:`
+fn write_child(child: &Value, depth: usize, out: &mut String) {
+    match child {
+        Value::Object(map) if !map.is_empty() => {
+            out.push('\n');
+            write_mapping(child, depth + 1, out);
+        }
+        Value::Array(items) if !items.is_empty() => {
+            out.push('\n');
+            write_sequence(child, depth, out);
+        }
+        other => out.push_str(&format!(" {}\n", scalar(other, depth + 1))),
+    }
+}
+
+/// Render a scalar (or empty collection) inline
+fn scalar(value: &Value, depth: usize) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => string_scalar(s, depth),
+        Value::Array(_) => "[]".to_string(),
+        Value::Object(_) => "{}".to_string(),
+    }
+}
+
+/// Render a string, quoting or using a literal block when needed
+fn string_scalar(value: &str, depth: usize) -> String {
+    if value.contains('\n') {
+        if let Some(block) = literal_block(value, depth) {
+            return block;
+        }
+        return double_quoted(value);
+    }
+
+    if needs_quoting(value) {
+        double_quoted(value)
+    } else {
+        value.to_string()
+    }
+}
+
+/// Render a multi-line string as a `|` block, if it can round-trip as one
+fn literal_block(value: &str, depth: usize) -> Option<String> {
+    let body = value.strip_suffix('\n').unwrap_or(value);
+    let chomp = if body.len() == value.len() { "-" } else { "" };
+
+    // Leading indentation on the first line, trailing blank lines and
+    // trailing spaces would be lost or need indentation indicators
+    if body.starts_with([' ', '\t'])
+        || body.ends_with('\n')
+        || body.lines().any(|line| line.ends_with([' ', '\t']))
+        || body.contains(['\r', '\t'])
+    {
+        return None;
+    }
+
+    let indent = " ".repeat(depth.max(1) * INDENT_WIDTH);
+    let mut block = format!("|{}", chomp);
+    for line in body.split('\n') {
+        block.push('\n');
+        if !line.is_empty() {
+            block.push_str(&indent);
+            block.push_str(line);
+        }
+    }
+    Some(block)
+}
+
+fn double_quoted(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| format!("\"{}\"", value))
+}
+
+/// Whether a single-line string would be misread as a plain scalar
+fn needs_quoting(value: &str) -> bool {
+    const INDICATORS: &[char] = &[
+        '-', '?', ':', ',', '[', ']', '{', '}', '#', '&', '*', '!', '|', '>', '\'', '"', '%', '@',
+        '`',
+    ];
+    const RESERVED: &[&str] = &[
+        "null", "Null", "NULL", "~", "true", "True", "TRUE", "false", "False", "FALSE", "yes",
+        "Yes", "YES", "no", "No", "NO", "on", "On", "ON", "off", "Off", "OFF", ".nan", ".NaN",
+        ".inf", "-.inf", ".Inf", "-.Inf",
+    ];
+
+    value.is_empty()
+        || value.starts_with(INDICATORS)
+        || value.starts_with(char::is_whitespace)
+        || value.ends_with(char::is_whitespace)
+        || value.ends_with(':')
+        || value.contains(": ")
+        || value.contains(" #")
+        || value.contains(|c: char| c.is_control())
+        || RESERVED.contains(&value)
+        // Numbers, dates and timestamps in their many YAML spellings
+        || value.starts_with(|c: char| c.is_ascii_digit())
+        || value.parse::<f64>().is_ok()
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn renders_nested_mappings_and_sequences() {
+        let data = json!({
+            "activeTabId": 2,
+            "tabs": [
+                {"id": 1, "title": "Home", "active": false},
+                {"id": 2, "title": "Docs: API", "active": true}
+            ],
+            "empty": [],
+            "meta": {"session": "default", "note": null}
+        });
+
+        assert_eq!(
+            render(&data),
+            "activeTabId: 2\nempty: []\nmeta:\n  note: null\n  session: default\ntabs:\n- active: false\n  id: 1\n  title: Home\n- active: true\n  id: 2\n  title: \"Docs: API\""
+        );
+    }
+
+    #[test]
+    fn quotes_strings_that_look_like_other_types() {
+        let data = json!({
+            "a": "true",
+            "b": "42",
+            "c": "",
+            "d": "- item",
+            "e": "plain text",
+            "f": "2026-01-01T00:00:00Z"
+        });
+        assert_eq!(
+            render(&data),
+            "a: \"true\"\nb: \"42\"\nc: \"\"\nd: \"- item\"\ne: plain text\nf: \"2026-01-01T00:00:00Z\""
+        );
+    }
+
+    #[test]
+    fn multi_line_strings_use_literal_blocks() {
+        let data = json!({"snapshot": "- main\n  - button \"Save\" [ref=e1]", "title": "x"});
+        assert_eq!(
+            render(&data),
+            "snapshot: |-\n  - main\n    - button \"Save\" [ref=e1]\ntitle: x"
+        );
+    }
+
+    #[test]
+    fn indented_first_line_falls_back_to_quotes() {
+        let data = json!({"text": "  indented\nline"});
+        assert_eq!(render(&data), "text: \"  indented\\nline\"");
+    }
+
+    #[test]
+    fn sequence_of_sequences() {
+        assert_eq!(render(&json!([[1, 2], "x"])), "-\n  - 1\n  - 2\n- x");
+    }
+
+    #[test]
+    fn top_level_scalar() {
+        assert_eq!(render(&json!("hello")), "hello");
+        assert_eq!(render(&json!({})), "{}");
+    }
+}
@@ -0,0 +1,526 @@
+//! Response queries for `--query`
+//!
+//! Evaluates a small JSONPath/JMESPath-like expression against response data:
+//!
+//! - `title`, `.title`, `$.title` - object field
+//! - `tabs[0]`, `tabs[-1]` - array index, negative from the end
+//! - `tabs[*].url`, `tabs[].url` - every element (or object value)
+//! - `tabs[?active].url` - elements whose field is truthy
+//! - `tabs[?id==3]`, `tabs[?title!='Home']` - elements compared to a literal
+//!   with `==`, `!=`, `<`, `<=`, `>` or `>=`
+//! - `["odd key"]` - field names that are not plain identifiers
+//!
+//! After a wildcard or filter, the rest of the path applies to each element
+//! and missing fields are dropped, so the result is a list. Without one, a
+//! missing field yields `null`.
+
+use crate::error::{CliError, Result};
+use serde_json::Value;
+use std::cmp::Ordering;
+
+/// A parsed query
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Field(String),
+    Index(i64),
+    Wildcard,
+    Filter(Filter),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Filter {
+    path: Vec<String>,
+    comparison: Option<(Op, Value)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// Evaluation state: one value, or a projection over many
+enum Current {
+    Single(Value),
+    Projected(Vec<Value>),
+}
+
+impl Query {
+    /// Parse a query expression
+    pub fn parse(input: &str) -> Result<Self> {
+        Parser::new(input).parse()
+    }
+
+    /// Evaluate the query against response data
+    pub fn evaluate(&self, data: &Value) -> Value {
+        let mut current = Current::Single(data.clone());
+
+        for segment in &self.segments {
+            current = match current {
+                Current::Single(value) => match segment {
+                    Segment::Field(_) | Segment::Index(_) => {
+                        Current::Single(step(&value, segment).unwrap_or(Value::Null))
+                    }
+                    Segment::Wildcard => Current::Projected(elements(value)),
+                    Segment::Filter(filter) => Current::Projected(filtered(value, filter)),
+                },
+                Current::Projected(values) => Current::Projected(match segment {
+                    Segment::Field(_) | Segment::Index(_) => values
+                        .iter()
+                        .filter_map(|value| step(value, segment))
+                        .filter(|value| !value.is_null())
+                        .collect(),
+                    // Wildcards and filters over a projection flatten it
+                    Segment::Wildcard => values.into_iter().flat_map(elements).collect(),
+                    Segment::Filter(filter) => values
+                        .into_iter()
+                        .flat_map(|value| filtered(value, filter))
+                        .collect(),
+                }),
+            };
+        }
+
+        match current {
+            Current::Single(value) => value,
+            Current::Projected(values) => Value::Array(values),
+        }
+    }
+}
+
+/// Apply a field or index segment to one value
+fn step(value: &Value, segment: &Segment) -> Option<Value> {
+    match (segment, value) {
+        (Segment::Field(name), Value::Object(map)) => map.get(name).cloned(),
+        (Segment::Index(index), Value::Array(items)) => {
+            let len = items.len() as i64;
+            let index = if *index < 0 { len + index } else { *index };
+            usize::try_from(index)
+                .ok()
+                .and_then(|i| items.get(i))
+                .cloned()
+        }
+        _ => None,
+    }
+}
+
+/// Elements of an array, or values of an object
+fn elements(value: Value) -> Vec<Value> {
+    match value {
+        Value::Array(items) => items,
+        Value::Object(map) => map.into_iter().map(|(_, v)| v).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn filtered(value: Value, filter: &Filter) -> Vec<Value> {
+    elements(value)
+        .into_iter()
+        .filter(|item| filter.matches(item))
+        .collect()
+}
+
+impl Filter {
+    fn matches(&self, item: &Value) -> bool {
+        let mut value = Some(item);
+        for name in &self.path {
+            value = value.and_then(|v| v.get(name));
+        }
+        let value = value.unwrap_or(&Value::Null);
+
+        let Some((op, literal)) = &self.comparison else {
+            return truthy(value);
+        };
+
+        match op {
+            Op::Eq => value == literal,
+            Op::Ne => value != literal,
+            Op::Lt | Op::Le | Op::Gt | Op::Ge => {
+                let Some(ordering) = compare(value, literal) else {
+                    return false;
+                };
+                match op {
+                    Op::Lt => ordering == Ordering::Less,
+                    Op::Le => ordering != Ordering::Greater,
+                    Op::Gt => ordering == Ordering::Greater,
+                    _ => ordering != Ordering::Less,
+                }
+            }
+        }
+    }
+}
+
+fn compare(a: &Value, b: &Value) -> Option<Ordering> {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64()?.partial_cmp(&b.as_f64()?),
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        _ => None,
+    }
+}
+
+/// Whether a value counts as true: not null, false, zero or empty
+///
+/// Shared with the template engine, so `{{#if}}` agrees with `[?field]`.
+pub(crate) fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64().is_some_and(|n| n != 0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(map) => !map.is_empty(),
+    }
+}
+
+// =============================================================================
+// Parser
+// =============================================================================
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self { input, pos: 0 }
+    }
+
+    fn parse(mut self) -> Result<Query> {
+        let mut segments = Vec::new();
+
+        self.skip_whitespace();
+        if self.eat('$') {
+            // `$` alone is the whole document
+        } else if self.peek().is_some_and(is_name_char) {
+            segments.push(Segment::Field(self.name()?));
+        }
+
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None => break,
+                Some('.') => {
+                    self.pos += 1;
+                    if self.eat('*') {
+                        segments.push(Segment::Wildcard);
+                    } else if self.peek().is_none() || self.peek() == Some('[') {
+                        // A lone `.` is the whole document; `.[0]` reads as `[0]`
+                    } else {
+                        segments.push(Segment::Field(self.name()?));
+                    }
+                }
+                Some('[') => {
+                    self.pos += 1;
+                    segments.push(self.bracket()?);
+                }
+                Some(_) => return Err(self.error("expected '.' or '['")),
+            }
+        }
+
+        Ok(Query { segments })
+    }
+
+    /// Parse the inside of `[...]`, after the opening bracket
+    fn bracket(&mut self) -> Result<Segment> {
+        self.skip_whitespace();
+        let segment = match self.peek() {
+            Some(']') => Segment::Wildcard,
+            Some('*') => {
+                self.pos += 1;
+                Segment::Wildcard
+            }
+            Some('?') => {
+                self.pos += 1;
+                Segment::Filter(self.filter()?)
+            }
+            Some('\'' | '"') => Segment::Field(self.quoted()?),
+            Some(c) if c == '-' || c.is_ascii_digit() => {
+                let start = self.pos;
+                self.pos += 1;
+                while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                    self.pos += 1;
+                }
+                let index = self.input[start..self.pos]
+                    .parse()
+                    .map_err(|_| self.error_at(start, "invalid array index"))?;
+                Segment::Index(index)
+            }
+            _ => return Err(self.error("expected an index, '*', '?' or a quoted name")),
+        };
+
+        self.skip_whitespace();
+        if !self.eat(']') {
+            return Err(self.error("expected ']'"));
+        }
+        Ok(segment)
+    }
+
+    /// Parse `field.path [op literal]` inside a filter
+    fn filter(&mut self) -> Result<Filter> {
+        self.skip_whitespace();
+        let mut path = vec![self.name()?];
+        while self.eat('.') {
+            path.push(self.name()?);
+        }
+
+        self.skip_whitespace();
+        let op = if self.eat_str("==") {
+            Op::Eq
+        } else if self.eat_str("!=") {
+            Op::Ne
+        } else if self.eat_str("<=") {
+            Op::Le
+        } else if self.eat_str(">=") {
+            Op::Ge
+        } else if self.eat('<') {
+            Op::Lt
+        } else if self.eat('>') {
+            Op::Gt
+        } else {
+            return Ok(Filter {
+                path,
+                comparison: None,
+            });
+        };
+
+        self.skip_whitespace();
+        let literal = self.literal()?;
+        Ok(Filter {
+            path,
+            comparison: Some((op, literal)),
+        })
+    }
+
+    fn literal(&mut self) -> Result<Value> {
+        if matches!(self.peek(), Some('\'' | '"')) {
+            return Ok(Value::String(self.quoted()?));
+        }
+
+        let start = self.pos;
+        while self.peek().is_some_and(|c| !c.is_whitespace() && c != ']') {
+            self.pos += self.peek().map_or(1, char::len_utf8);
+        }
+        let word = &self.input[start..self.pos];
+        match word {
+            "true" => Ok(Value::Bool(true)),
+            "false" => Ok(Value::Bool(false)),
+            "null" => Ok(Value::Null),
+            _ => serde_json::from_str::<serde_json::Number>(word)
+                .map(Value::Number)
+                .map_err(|_| {
+                    self.error_at(
+                        start,
+                        "expected a quoted string, number, true, false or null",
+                    )
+                }),
+        }
+    }
+
+    fn name(&mut self) -> Result<String> {
+        if matches!(self.peek(), Some('\'' | '"')) {
+            return self.quoted();
+        }
+
+        let start = self.pos;
+        while self.peek().is_some_and(is_name_char) {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return Err(self.error("expected a field name"));
+        }
+        Ok(self.input[start..self.pos].to_string())
+    }
+
+    /// Parse a single- or double-quoted string with `\` escapes
+    fn quoted(&mut self) -> Result<String> {
+        let start = self.pos;
+        let quote = self.peek().unwrap_or('"');
+        self.pos += 1;
+
+        let mut value = String::new();
+        let mut chars = self.input[self.pos..].char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\\' => {
+                    if let Some((_, escaped)) = chars.next() {
+                        value.push(escaped);
+                    }
+                }
+                c if c == quote => {
+                    self.pos += i + 1;
+                    return Ok(value);
+                }
+                c => value.push(c),
+            }
+        }
+
+        Err(self.error_at(start, "unterminated quoted string"))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_str(&mut self, s: &str) -> bool {
+        if self.input[self.pos..].starts_with(s) {
+            self.pos += s.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += self.peek().map_or(1, char::len_utf8);
+        }
+    }
+
+    fn error(&self, reason: &str) -> CliError {
+        self.error_at(self.pos, reason)
+    }
+
+    /// Build an `InvalidArguments` error with a caret under the offending column
+    fn error_at(&self, offset: usize, reason: &str) -> CliError {
+        let column = self.input[..offset.min(self.input.len())].chars().count();
+        CliError::InvalidArguments(format!(
+            "{} in query\n  {}\n  {}^",
+            reason,
+            self.input,
+            " ".repeat(column)
+        ))
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tabs() -> Value {
+        json!({
+            "activeTabId": 2,
+            "tabs": [
+                {"id": 1, "title": "Home", "url": "https://example.com", "active": false},
+                {"id": 2, "title": "Docs", "url": "https://example.com/docs", "active": true},
+                {"id": 3, "title": "Blog", "url": "https://example.com/blog"}
+            ]
+        })
+    }
+
+    fn eval(query: &str, data: &Value) -> Value {
+        Query::parse(query).expect("parse").evaluate(data)
+    }
+
+    fn error(query: &str) -> String {
+        match Query::parse(query) {
+            Err(CliError::InvalidArguments(message)) => message,
+            other => panic!("expected InvalidArguments, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn field_access() {
+        assert_eq!(eval("activeTabId", &tabs()), json!(2));
+        assert_eq!(eval(".activeTabId", &tabs()), json!(2));
+        assert_eq!(eval("$.activeTabId", &tabs()), json!(2));
+        assert_eq!(eval("$", &tabs()), tabs());
+        assert_eq!(eval(".", &tabs()), tabs());
+        assert_eq!(eval("missing", &tabs()), Value::Null);
+    }
+
+    #[test]
+    fn indices() {
+        assert_eq!(eval("tabs[0].title", &tabs()), json!("Home"));
+        assert_eq!(eval("tabs[-1].title", &tabs()), json!("Blog"));
+        assert_eq!(eval("tabs[9].title", &tabs()), Value::Null);
+    }
+
+    #[test]
+    fn wildcard_projects_rest_of_path() {
+        assert_eq!(eval("tabs[*].id", &tabs()), json!([1, 2, 3]));
+        assert_eq!(eval("tabs[].id", &tabs()), json!([1, 2, 3]));
+        // Missing fields are dropped from projections
+        assert_eq!(eval("tabs[*].active", &tabs()), json!([false, true]));
+    }
+
+    #[test]
+    fn truthy_filter() {
+        assert_eq!(
+            eval("tabs[?active].url", &tabs()),
+            json!(["https://example.com/docs"])
+        );
+    }
+
+    #[test]
+    fn comparison_filters() {
+        assert_eq!(eval("tabs[?id==3].title", &tabs()), json!(["Blog"]));
+        assert_eq!(eval("tabs[?title != 'Home'].id", &tabs()), json!([2, 3]));
+        assert_eq!(eval("tabs[?id>=2].id", &tabs()), json!([2, 3]));
+        assert_eq!(eval("tabs[?id<2].id", &tabs()), json!([1]));
+        assert_eq!(eval("tabs[?active==null].id", &tabs()), json!([3]));
+    }
+
+    #[test]
+    fn object_wildcard_and_quoted_names() {
+        let data = json!({"by name": {"a": 1, "b": 2}});
+        assert_eq!(eval("[\"by name\"].*", &data), json!([1, 2]));
+        assert_eq!(eval("['by name'].b", &data), json!(2));
+    }
+
+    #[test]
+    fn wildcard_over_projection_flattens() {
+        let data = json!({"groups": [{"items": [1, 2]}, {"items": [3]}]});
+        assert_eq!(eval("groups[*].items[*]", &data), json!([1, 2, 3]));
+    }
+
+    #[test]
+    fn errors_point_at_column() {
+        assert_eq!(
+            error("tabs[?active"),
+            "expected ']' in query\n  tabs[?active\n              ^"
+        );
+        assert_eq!(
+            error("tabs[x]"),
+            "expected an index, '*', '?' or a quoted name in query\n  tabs[x]\n       ^"
+        );
+        assert_eq!(
+            error("tabs[?id==abc]"),
+            "expected a quoted string, number, true, false or null in query\n  tabs[?id==abc]\n            ^"
+        );
+        assert_eq!(
+            error("tabs..url"),
+            "expected a field name in query\n  tabs..url\n       ^"
+        );
+        assert_eq!(
+            error("title url"),
+            "expected '.' or '[' in query\n  title url\n        ^"
+        );
+    }
+}
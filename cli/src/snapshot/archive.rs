@@ -0,0 +1,599 @@
+//! Snapshot archive
+//!
+//! When enabled, every `snapshot` result is appended to a JSON Lines file in
+//! the data directory together with its session and timestamp, so the pages
+//! an agent saw can be searched after the run. Retention limits on count, age
+//! and size are checked on each append; only when one is exceeded is the file
+//! rewritten without the oldest snapshots.
+//!
+//! Appends from concurrent processes are serialized by an advisory lock on a
+//! file next to the archive, which survives the archive being rewritten. The
+//! archive holds page content, so it is kept private to the current user.
+
+use crate::config::ArchiveConfig;
+use crate::error::{CliError, Result};
+use crate::types::{ArchiveMatch, ArchivedSnapshot, SnapshotData};
+use crate::utils::dirs::get_data_dir;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use time::format_description::well_known::Rfc3339;
+use time::{Duration, OffsetDateTime};
+
+/// Archive file name under `<data dir>/archive`
+const ARCHIVE_FILE: &str = "snapshots.jsonl";
+
+/// Maximum number of matching lines reported per search hit
+const MAX_EXCERPTS: usize = 3;
+
+/// Maximum length of a single excerpt, in characters
+const MAX_EXCERPT_CHARS: usize = 160;
+
+/// Bytes read from the end of the archive at a time to find its last entry
+const TAIL_CHUNK: u64 = 64 * 1024;
+
+/// An archive file of snapshots, oldest first
+pub struct Archive {
+    path: PathBuf,
+}
+
+impl Archive {
+    /// Open the archive in the data directory
+    pub fn open() -> Result<Self> {
+        let dir = get_data_dir().map_err(CliError::CommandFailed)?;
+        Ok(Self::at(dir.join("archive").join(ARCHIVE_FILE)))
+    }
+
+    /// Open an archive at a specific path
+    pub fn at(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Path of the archive file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append a snapshot and apply the retention limits, returning its ID
+    pub fn append(
+        &self,
+        session: &str,
+        timestamp: &str,
+        data: &SnapshotData,
+        limits: &ArchiveConfig,
+    ) -> Result<u64> {
+        if let Some(dir) = self.path.parent() {
+            create_private_dir(dir)?;
+        }
+        let _lock = self.lock()?;
+
+        let mut file = private_file().read(true).append(true).open(&self.path)?;
+        let id = match self.last_id(&mut file)? {
+            Some(last) => last + 1,
+            None => 1,
+        };
+        let entry = ArchivedSnapshot {
+            id,
+            session: session.to_string(),
+            timestamp: timestamp.to_string(),
+            title: data.title.clone(),
+            url: data.url.clone(),
+            snapshot: data.snapshot.clone(),
+        };
+        file.write_all(encode(&entry)?.as_bytes())?;
+        let len = file.metadata()?.len();
+        drop(file);
+
+        let now = OffsetDateTime::now_utc();
+        if self.over_limits(id, len, limits, now)? {
+            let mut entries = self.entries()?;
+            prune(&mut entries, limits, now)?;
+            self.rewrite(&entries)?;
+        }
+
+        Ok(id)
+    }
+
+    /// Look up an archived snapshot by ID
+    pub fn get(&self, id: u64) -> Result<Option<ArchivedSnapshot>> {
+        Ok(self.entries()?.into_iter().find(|entry| entry.id == id))
+    }
+
+    /// Find snapshots whose title, URL or tree contains `query`, newest first
+    ///
+    /// Matching is case-insensitive. `session` restricts the search to one
+    /// session.
+    pub fn search(
+        &self,
+        query: &str,
+        session: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<ArchiveMatch>> {
+        let needle = query.to_lowercase();
+        let matches = self
+            .entries()?
+            .into_iter()
+            .rev()
+            .filter(|entry| session.is_none_or(|session| entry.session == session))
+            .filter_map(|entry| {
+                let excerpts = excerpts(&entry, &needle);
+                let in_header = entry.title.to_lowercase().contains(&needle)
+                    || entry.url.to_lowercase().contains(&needle);
+                (in_header || !excerpts.is_empty()).then_some(ArchiveMatch {
+                    id: entry.id,
+                    session: entry.session,
+                    timestamp: entry.timestamp,
+                    title: entry.title,
+                    url: entry.url,
+                    excerpts,
+                })
+            })
+            .take(limit)
+            .collect();
+
+        Ok(matches)
+    }
+
+    /// Read all entries, skipping lines that fail to parse
+    fn entries(&self) -> Result<Vec<ArchivedSnapshot>> {
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(contents
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+
+    /// ID of the last entry, read from the end of the file
+    ///
+    /// Falls back to reading every entry if the last line does not parse.
+    fn last_id(&self, file: &mut File) -> Result<Option<u64>> {
+        let len = file.metadata()?.len();
+        let mut chunk = TAIL_CHUNK;
+        let line = loop {
+            let start = len.saturating_sub(chunk);
+            file.seek(SeekFrom::Start(start))?;
+            let mut tail = Vec::new();
+            (&mut *file).take(len - start).read_to_end(&mut tail)?;
+
+            let body = tail.strip_suffix(b"\n").unwrap_or(&tail);
+            match body.iter().rposition(|&b| b == b'\n') {
+                Some(newline) => break body[newline + 1..].to_vec(),
+                None if start == 0 => break body.to_vec(),
+                None => chunk *= 2,
+            }
+        };
+
+        if line.is_empty() {
+            return Ok(None);
+        }
+        match serde_json::from_slice::<ArchivedSnapshot>(&line) {
+            Ok(entry) => Ok(Some(entry.id)),
+            Err(_) => Ok(self.entries()?.last().map(|entry| entry.id)),
+        }
+    }
+
+    /// Whether the archive, now `len` bytes with `last_id` as its newest
+    /// entry, breaks a retention limit
+    ///
+    /// Only the first entry is read: IDs are consecutive and entries are
+    /// stored oldest first.
+    fn over_limits(
+        &self,
+        last_id: u64,
+        len: u64,
+        limits: &ArchiveConfig,
+        now: OffsetDateTime,
+    ) -> Result<bool> {
+        if limits.max_bytes.is_some_and(|max_bytes| len > max_bytes) {
+            return Ok(true);
+        }
+        if limits.max_entries.is_none() && limits.max_age_days.is_none() {
+            return Ok(false);
+        }
+
+        let mut first = String::new();
+        BufReader::new(File::open(&self.path)?).read_line(&mut first)?;
+        // An unreadable first line is dropped by the rewrite
+        let Ok(first) = serde_json::from_str::<ArchivedSnapshot>(&first) else {
+            return Ok(true);
+        };
+
+        if let Some(max_entries) = limits.max_entries
+            && last_id.saturating_sub(first.id) >= max_entries as u64
+        {
+            return Ok(true);
+        }
+        if let Some(max_age_days) = limits.max_age_days
+            && let Ok(ts) = OffsetDateTime::parse(&first.timestamp, &Rfc3339)
+            && ts < now - Duration::days(max_age_days as i64)
+        {
+            return Ok(true);
+        }
+
+        Ok(false)
+    }
+
+    /// Take the advisory lock guarding appends, waiting for other processes
+    ///
+    /// The lock is released when the returned file is dropped.
+    fn lock(&self) -> Result<File> {
+        let mut name = self.path.as_os_str().to_owned();
+        name.push(".lock");
+        let file = private_file()
+            .write(true)
+            .truncate(false)
+            .open(PathBuf::from(name))?;
+        file.lock()?;
+        Ok(file)
+    }
+
+    /// Replace the archive contents, writing to a temporary file first
+    ///
+    /// Must be called with the lock held, or concurrent appends to the
+    /// replaced file are lost.
+    fn rewrite(&self, entries: &[ArchivedSnapshot]) -> Result<()> {
+        let mut contents = String::new();
+        for entry in entries {
+            contents.push_str(&encode(entry)?);
+        }
+
+        let tmp = self.path.with_extension("jsonl.tmp");
+        private_file()
+            .write(true)
+            .truncate(true)
+            .open(&tmp)?
+            .write_all(contents.as_bytes())?;
+        fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+/// Create the archive directory readable only by the current user
+fn create_private_dir(dir: &Path) -> Result<()> {
+    #[cfg(unix)]
+    return crate::utils::dirs::ensure_private_dir(dir).map_err(CliError::CommandFailed);
+
+    #[cfg(not(unix))]
+    {
+        fs::create_dir_all(dir)?;
+        Ok(())
+    }
+}
+
+/// Options creating a file readable only by the current user
+fn private_file() -> OpenOptions {
+    let mut options = OpenOptions::new();
+    options.create(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+}
+
+/// Archive a snapshot response if the archive is enabled
+pub fn record(session: &str, data: &SnapshotData, config: &ArchiveConfig) -> Result<()> {
+    if !config.enabled {
+        return Ok(());
+    }
+
+    let timestamp = crate::commands::utils::current_timestamp();
+    Archive::open()?.append(session, &timestamp, data, config)?;
+    Ok(())
+}
+
+/// Serialize an entry as one JSON line
+fn encode(entry: &ArchivedSnapshot) -> Result<String> {
+    let mut line = serde_json::to_string(entry)?;
+    line.push('\n');
+    Ok(line)
+}
+
+/// Drop the oldest entries until every limit holds, returning whether any were
+/// dropped
+fn prune(
+    entries: &mut Vec<ArchivedSnapshot>,
+    limits: &ArchiveConfig,
+    now: OffsetDateTime,
+) -> Result<bool> {
+    let before = entries.len();
+
+    if let Some(max_age_days) = limits.max_age_days {
+        let cutoff = now - Duration::days(max_age_days as i64);
+        // Entries with unreadable timestamps are kept rather than guessed at
+        entries.retain(|entry| {
+            OffsetDateTime::parse(&entry.timestamp, &Rfc3339).map_or(true, |ts| ts >= cutoff)
+        });
+    }
+
+    if let Some(max_entries) = limits.max_entries
+        && entries.len() > max_entries
+    {
+        entries.drain(..entries.len() - max_entries);
+    }
+
+    if let Some(max_bytes) = limits.max_bytes {
+        let sizes = entries
+            .iter()
+            .map(|entry| encode(entry).map(|line| line.len() as u64))
+            .collect::<Result<Vec<_>>>()?;
+        let mut total: u64 = sizes.iter().sum();
+        let mut drop = 0;
+        // Always keep the newest entry, even if it alone exceeds the limit
+        while total > max_bytes && drop + 1 < sizes.len() {
+            total -= sizes[drop];
+            drop += 1;
+        }
+        entries.drain(..drop);
+    }
+
+    Ok(entries.len() != before)
+}
+
+/// Collect the snapshot lines containing `needle` (already lowercased)
+fn excerpts(entry: &ArchivedSnapshot, needle: &str) -> Vec<String> {
+    entry
+        .snapshot
+        .lines()
+        .filter(|line| line.to_lowercase().contains(needle))
+        .take(MAX_EXCERPTS)
+        .map(|line| {
+            let line = line.trim();
+            if line.chars().count() > MAX_EXCERPT_CHARS {
+                let cut: String = line.chars().take(MAX_EXCERPT_CHARS).collect();
+                format!("{}...", cut)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect()
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_archive(name: &str) -> Archive {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        Archive::at(
+            std::env::temp_dir()
+                .join(format!("browser-cli-archive-{}-{}", name, nanos))
+                .join(ARCHIVE_FILE),
+        )
+    }
+
+    fn snapshot(title: &str, tree: &str) -> SnapshotData {
+        SnapshotData {
+            snapshot: tree.to_string(),
+            title: title.to_string(),
+            url: format!("https://example.com/{}", title.to_lowercase()),
+        }
+    }
+
+    fn unlimited() -> ArchiveConfig {
+        ArchiveConfig {
+            enabled: true,
+            max_entries: None,
+            max_age_days: None,
+            max_bytes: None,
+        }
+    }
+
+    fn cleanup(archive: &Archive) {
+        if let Some(dir) = archive.path().parent() {
+            let _ = fs::remove_dir_all(dir);
+        }
+    }
+
+    #[test]
+    fn append_assigns_increasing_ids() {
+        let archive = temp_archive("ids");
+        let limits = unlimited();
+        let ts = "2026-01-01T00:00:00Z";
+
+        let first = archive
+            .append("default", ts, &snapshot("Home", "- main"), &limits)
+            .unwrap();
+        let second = archive
+            .append("work", ts, &snapshot("Login", "- form"), &limits)
+            .unwrap();
+
+        assert_eq!((first, second), (1, 2));
+        let entry = archive.get(2).unwrap().expect("entry");
+        assert_eq!(entry.session, "work");
+        assert_eq!(entry.title, "Login");
+        assert!(archive.get(3).unwrap().is_none());
+        cleanup(&archive);
+    }
+
+    #[test]
+    fn search_is_case_insensitive_and_newest_first() {
+        let archive = temp_archive("search");
+        let limits = unlimited();
+        let ts = "2026-01-01T00:00:00Z";
+
+        archive
+            .append(
+                "default",
+                ts,
+                &snapshot("Cart", "- main\n  - button \"Checkout\" [ref=e4]"),
+                &limits,
+            )
+            .unwrap();
+        archive
+            .append("default", ts, &snapshot("Home", "- main"), &limits)
+            .unwrap();
+        archive
+            .append(
+                "work",
+                ts,
+                &snapshot("Order", "- alert \"Checkout failed\""),
+                &limits,
+            )
+            .unwrap();
+
+        let matches = archive.search("checkout", None, 10).unwrap();
+        assert_eq!(matches.iter().map(|m| m.id).collect::<Vec<_>>(), vec![3, 1]);
+        assert_eq!(matches[1].excerpts, vec!["- button \"Checkout\" [ref=e4]"]);
+
+        let matches = archive.search("checkout", Some("default"), 10).unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].id, 1);
+
+        // Title and URL matches count even without a matching line
+        let matches = archive.search("home", None, 10).unwrap();
+        assert_eq!(matches.len(), 1);
+        assert!(matches[0].excerpts.is_empty());
+        cleanup(&archive);
+    }
+
+    #[test]
+    fn append_prunes_by_count() {
+        let archive = temp_archive("count");
+        let limits = ArchiveConfig {
+            max_entries: Some(2),
+            ..unlimited()
+        };
+        let ts = "2026-01-01T00:00:00Z";
+
+        for title in ["A", "B", "C"] {
+            archive
+                .append("default", ts, &snapshot(title, "- main"), &limits)
+                .unwrap();
+        }
+
+        assert!(archive.get(1).unwrap().is_none());
+        assert!(archive.get(2).unwrap().is_some());
+        // IDs keep increasing after pruning
+        let id = archive
+            .append("default", ts, &snapshot("D", "- main"), &limits)
+            .unwrap();
+        assert_eq!(id, 4);
+        cleanup(&archive);
+    }
+
+    #[test]
+    fn concurrent_appends_get_distinct_ids() {
+        let archive = temp_archive("concurrent");
+        let path = archive.path().to_path_buf();
+        let ts = "2026-01-01T00:00:00Z";
+
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    let archive = Archive::at(path);
+                    for _ in 0..10 {
+                        archive
+                            .append("default", ts, &snapshot("Home", "- main"), &unlimited())
+                            .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let mut ids: Vec<u64> = archive.entries().unwrap().iter().map(|e| e.id).collect();
+        ids.sort();
+        assert_eq!(ids, (1..=80).collect::<Vec<_>>());
+        cleanup(&archive);
+    }
+
+    #[test]
+    fn append_reads_the_last_id_past_the_tail_chunk() {
+        let archive = temp_archive("tail");
+        let limits = unlimited();
+        let ts = "2026-01-01T00:00:00Z";
+        let large = "- main\n".repeat(TAIL_CHUNK as usize / 4);
+
+        archive
+            .append("default", ts, &snapshot("Home", "- main"), &limits)
+            .unwrap();
+        archive
+            .append("default", ts, &snapshot("Large", &large), &limits)
+            .unwrap();
+        let id = archive
+            .append("default", ts, &snapshot("Next", "- main"), &limits)
+            .unwrap();
+
+        assert_eq!(id, 3);
+        cleanup(&archive);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn archive_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let archive = temp_archive("private");
+        archive
+            .append(
+                "default",
+                "2026-01-01T00:00:00Z",
+                &snapshot("Home", "- main"),
+                &unlimited(),
+            )
+            .unwrap();
+
+        let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(archive.path()), 0o600);
+        assert_eq!(mode(archive.path().parent().unwrap()), 0o700);
+        cleanup(&archive);
+    }
+
+    #[test]
+    fn prune_drops_old_and_oversized_entries() {
+        let now = OffsetDateTime::parse("2026-01-10T00:00:00Z", &Rfc3339).unwrap();
+        let entry = |id: u64, timestamp: &str| ArchivedSnapshot {
+            id,
+            session: "default".to_string(),
+            timestamp: timestamp.to_string(),
+            title: String::new(),
+            url: String::new(),
+            snapshot: "- main".to_string(),
+        };
+
+        let mut entries = vec![
+            entry(1, "2026-01-01T00:00:00Z"),
+            entry(2, "2026-01-08T00:00:00Z"),
+            entry(3, "2026-01-09T00:00:00Z"),
+        ];
+        let limits = ArchiveConfig {
+            max_age_days: Some(7),
+            ..unlimited()
+        };
+        assert!(prune(&mut entries, &limits, now).unwrap());
+        assert_eq!(entries.iter().map(|e| e.id).collect::<Vec<_>>(), vec![2, 3]);
+
+        let line_len = encode(&entries[1]).unwrap().len() as u64;
+        let limits = ArchiveConfig {
+            max_bytes: Some(line_len),
+            ..unlimited()
+        };
+        assert!(prune(&mut entries, &limits, now).unwrap());
+        assert_eq!(entries.iter().map(|e| e.id).collect::<Vec<_>>(), vec![3]);
+
+        // The newest entry survives even when it alone is over the limit
+        let limits = ArchiveConfig {
+            max_bytes: Some(1),
+            ..unlimited()
+        };
+        assert!(!prune(&mut entries, &limits, now).unwrap());
+        assert_eq!(entries.len(), 1);
+    }
+}
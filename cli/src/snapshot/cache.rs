@@ -0,0 +1,106 @@
+//! Last-snapshot cache
+//!
+//! Keeps the most recent snapshot of each session in the state directory so
+//! locators can be resolved to refs without another round-trip to the page.
+//! Snapshots hold page content, so the cache is readable only by the user.
+
+use crate::types::SnapshotData;
+use crate::utils::dirs::get_state_dir;
+use crate::utils::files::escape_file_name;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Store the latest snapshot for a session
+pub fn store(session: &str, data: &SnapshotData) -> Result<(), String> {
+    store_at(&cache_path(session)?, data)
+}
+
+fn store_at(path: &Path, data: &SnapshotData) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        create_private_dir(parent)
+            .map_err(|e| format!("Failed to create snapshot cache directory: {}", e))?;
+    }
+
+    let json = serde_json::to_vec(data).map_err(|e| format!("Failed to encode snapshot: {}", e))?;
+    write_private(path, &json)
+        .map_err(|e| format!("Failed to write snapshot cache {}: {}", path.display(), e))
+}
+
+/// Load the latest snapshot for a session, if one was cached
+pub fn load(session: &str) -> Option<SnapshotData> {
+    let path = cache_path(session).ok()?;
+    let bytes = fs::read(path).ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+fn cache_path(session: &str) -> Result<PathBuf, String> {
+    Ok(get_state_dir()?
+        .join("snapshots")
+        .join(format!("{}.json", escape_file_name(session))))
+}
+
+#[cfg(unix)]
+fn create_private_dir(dir: &Path) -> Result<(), String> {
+    crate::utils::dirs::ensure_private_dir(dir)
+}
+
+#[cfg(not(unix))]
+fn create_private_dir(dir: &Path) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| e.to_string())
+}
+
+/// Replace `path` with `contents`, readable only by the current user
+///
+/// A cache file left readable by an older version is restricted too.
+fn write_private(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        let mut file = options.open(path)?;
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+        file.write_all(contents)
+    }
+    #[cfg(not(unix))]
+    options.open(path)?.write_all(contents)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn store_is_private() {
+        let dir = std::env::temp_dir().join(format!("browser-cache-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("snapshots").join("default.json");
+        let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        let data = SnapshotData {
+            snapshot: "- heading \"Example\" [ref=e1]".to_string(),
+            title: "Example".to_string(),
+            url: "https://example.com/".to_string(),
+        };
+
+        store_at(&path, &data).unwrap();
+        assert_eq!(mode(&path), 0o600);
+        assert_eq!(mode(path.parent().unwrap()), 0o700);
+
+        // A cache written world-readable by an older version is restricted
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        store_at(&path, &data).unwrap();
+        assert_eq!(mode(&path), 0o600);
+        assert_eq!(
+            serde_json::from_slice::<SnapshotData>(&fs::read(&path).unwrap())
+                .unwrap()
+                .url,
+            data.url
+        );
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,120 @@
+//! Line diff between two snapshots
+//!
+//! Refs are reassigned on every snapshot, so lines are compared with their
+//! `[ref=...]` attribute removed. Changed lines are reported in full, with
+//! the new refs, prefixed by `+ ` or `- `.
+
+/// Largest number of line pairs compared before falling back to replacing the
+/// whole changed region
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// Diff two snapshot texts, returning only the changed lines
+pub fn diff(old: &str, new: &str) -> Vec<String> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let old_keys: Vec<String> = old.iter().map(|line| strip_ref(line)).collect();
+    let new_keys: Vec<String> = new.iter().map(|line| strip_ref(line)).collect();
+
+    // Unchanged head and tail need no table
+    let prefix = old_keys
+        .iter()
+        .zip(&new_keys)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = old_keys[prefix..]
+        .iter()
+        .rev()
+        .zip(new_keys[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let old_mid = prefix..old.len() - suffix;
+    let new_mid = prefix..new.len() - suffix;
+    let (n, m) = (old_mid.len(), new_mid.len());
+
+    let mut out = Vec::new();
+    if n * m > MAX_DIFF_CELLS {
+        out.extend(old[old_mid].iter().map(|line| format!("- {}", line)));
+        out.extend(new[new_mid].iter().map(|line| format!("+ {}", line)));
+        return out;
+    }
+
+    let a = &old_keys[old_mid.clone()];
+    let b = &new_keys[new_mid.clone()];
+
+    // lcs[i][j] is the longest common subsequence of a[i..] and b[j..]
+    let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && a[i] == b[j] {
+            i += 1;
+            j += 1;
+        } else if i < n && (j == m || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            out.push(format!("- {}", old[prefix + i]));
+            i += 1;
+        } else {
+            out.push(format!("+ {}", new[prefix + j]));
+            j += 1;
+        }
+    }
+
+    out
+}
+
+/// Remove a trailing ` [ref=...]` attribute from a snapshot line
+fn strip_ref(line: &str) -> String {
+    match line.rfind(" [ref=") {
+        Some(start) if line.ends_with(']') => line[..start].to_string(),
+        _ => line.to_string(),
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_snapshots_have_no_diff() {
+        let text = "- main\n  - button \"Save\" [ref=e1]";
+        assert!(diff(text, text).is_empty());
+    }
+
+    #[test]
+    fn renumbered_refs_are_not_changes() {
+        let old = "- main\n  - button \"Save\" [ref=e1]";
+        let new = "- main\n  - button \"Save\" [ref=e7]";
+        assert!(diff(old, new).is_empty());
+    }
+
+    #[test]
+    fn reports_added_and_removed_lines() {
+        let old = "- main\n  - button \"Save\" [ref=e1]\n  - link \"Help\" [ref=e2]";
+        let new = "- main\n  - alert \"Saved\"\n  - link \"Help\" [ref=e5]";
+        assert_eq!(
+            diff(old, new),
+            vec!["-   - button \"Save\" [ref=e1]", "+   - alert \"Saved\""]
+        );
+    }
+
+    #[test]
+    fn empty_previous_snapshot_adds_everything() {
+        assert_eq!(
+            diff("", "- main\n  - button [ref=e1]"),
+            vec!["+ - main", "+   - button [ref=e1]"]
+        );
+    }
+}
@@ -0,0 +1,94 @@
+//! HTML renderer
+//!
+//! Produces a standalone page with the tree as nested `<details>` elements,
+//! so large snapshots can be folded open node by node while debugging.
+
+use super::{Snapshot, SnapshotNode};
+
+const STYLE: &str = "body { font-family: ui-monospace, monospace; font-size: 13px; }
+ul.tree, ul.tree ul { list-style: none; padding-left: 1.2em; margin: 0; }
+summary { cursor: pointer; }
+.role { color: #6f42c1; }
+.name { color: #032f62; }
+.ref { color: #e36209; }
+.text { color: #586069; font-style: italic; }";
+
+pub fn render(snapshot: &Snapshot) -> String {
+    let mut lines = vec![
+        "<!DOCTYPE html>".to_string(),
+        "<html lang=\"en\">".to_string(),
+        "<head>".to_string(),
+        "<meta charset=\"utf-8\">".to_string(),
+        format!("<title>Snapshot: {}</title>", escape(&snapshot.title)),
+        "<style>".to_string(),
+        STYLE.to_string(),
+        "</style>".to_string(),
+        "</head>".to_string(),
+        "<body>".to_string(),
+        format!("<h1>{}</h1>", escape(&snapshot.title)),
+        format!("<p><a href=\"{0}\">{0}</a></p>", escape(&snapshot.url)),
+        "<ul class=\"tree\">".to_string(),
+    ];
+
+    for node in &snapshot.nodes {
+        render_node(node, 1, &mut lines);
+    }
+
+    lines.extend([
+        "</ul>".to_string(),
+        "</body>".to_string(),
+        "</html>".to_string(),
+    ]);
+    lines.join("\n")
+}
+
+fn render_node(node: &SnapshotNode, depth: usize, lines: &mut Vec<String>) {
+    let indent = "  ".repeat(depth);
+    let label = label(node);
+
+    if node.children.is_empty() {
+        lines.push(format!("{}<li>{}</li>", indent, label));
+        return;
+    }
+
+    lines.push(format!(
+        "{}<li><details open><summary>{}</summary>",
+        indent, label
+    ));
+    lines.push(format!("{}<ul>", indent));
+    for child in &node.children {
+        render_node(child, depth + 1, lines);
+    }
+    lines.push(format!("{}</ul>", indent));
+    lines.push(format!("{}</details></li>", indent));
+}
+
+fn label(node: &SnapshotNode) -> String {
+    if node.is_text() {
+        return format!(
+            "<span class=\"text\">{}</span>",
+            escape(node.name.as_deref().unwrap_or_default())
+        );
+    }
+
+    let mut label = format!("<span class=\"role\">{}</span>", escape(&node.role));
+    if let Some(name) = &node.name {
+        label.push_str(&format!(
+            " <span class=\"name\">&quot;{}&quot;</span>",
+            escape(name)
+        ));
+    }
+    if let Some(r#ref) = &node.r#ref {
+        label.push_str(&format!(" <span class=\"ref\">[{}]</span>", escape(r#ref)));
+    }
+    label
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
@@ -0,0 +1,161 @@
+//! Markdown renderer
+//!
+//! Flattens the tree into prose for LLM prompts: headings become `#`
+//! lines, links become `[name](ref)`, list items and controls become
+//! bullets, and landmark containers are dropped in favour of their content.
+//! The page title is the top heading, so an `h1` repeating it is dropped.
+
+use super::{ROOT_ROLE, Snapshot, SnapshotNode};
+
+/// Roles that only group content and produce no output of their own
+const CONTAINER_ROLES: &[&str] = &[
+    ROOT_ROLE,
+    "main",
+    "navigation",
+    "complementary",
+    "region",
+    "banner",
+    "contentinfo",
+    "article",
+    "search",
+    "form",
+    "list",
+    "table",
+    "row",
+];
+
+/// A rendered paragraph or bullet line
+struct Block {
+    text: String,
+    bullet: bool,
+}
+
+pub fn render(snapshot: &Snapshot) -> String {
+    let mut blocks = Vec::new();
+
+    if !snapshot.title.is_empty() {
+        push_paragraph(&mut blocks, format!("# {}", snapshot.title));
+    }
+    if !snapshot.url.is_empty() {
+        push_paragraph(&mut blocks, format!("<{}>", snapshot.url));
+    }
+
+    for node in &snapshot.nodes {
+        render_block(node, &mut blocks);
+    }
+
+    // Most pages repeat their title as the first h1
+    if !snapshot.title.is_empty() {
+        let title = format!("# {}", snapshot.title);
+        if let Some(i) = blocks.iter().skip(1).position(|block| block.text == title) {
+            blocks.remove(i + 1);
+        }
+    }
+
+    let mut output = String::new();
+    let mut previous_bullet = None;
+    for block in blocks {
+        match previous_bullet {
+            Some(true) if block.bullet => output.push('\n'),
+            Some(_) => output.push_str("\n\n"),
+            None => {}
+        }
+        output.push_str(&block.text);
+        previous_bullet = Some(block.bullet);
+    }
+    output
+}
+
+fn render_block(node: &SnapshotNode, blocks: &mut Vec<Block>) {
+    if let Some(level) = node.heading_level() {
+        let name = node.name.as_deref().unwrap_or_default();
+        push_paragraph(blocks, format!("{} {}", "#".repeat(level), name));
+    } else if node.role == "listitem" {
+        render_item(node, 0, blocks);
+        return;
+    } else if node.role == "list" {
+        for child in &node.children {
+            render_item(child, 0, blocks);
+        }
+        return;
+    } else if node.role == "link" {
+        push_paragraph(blocks, link(node));
+    } else if node.r#ref.is_some() && node.role != ROOT_ROLE {
+        blocks.push(Block {
+            text: format!("- {}", control(node)),
+            bullet: true,
+        });
+    } else if !CONTAINER_ROLES.contains(&node.role.as_str())
+        && let Some(name) = &node.name
+    {
+        push_paragraph(blocks, name.clone());
+    }
+
+    for child in &node.children {
+        render_block(child, blocks);
+    }
+}
+
+/// Render a node as a bullet at the given list depth
+fn render_item(node: &SnapshotNode, depth: usize, blocks: &mut Vec<Block>) {
+    let indent = "  ".repeat(depth);
+
+    // A list item wrapping a single link renders as the link itself
+    let (text, children): (Option<String>, &[SnapshotNode]) = match node.children.as_slice() {
+        [only] if node.role == "listitem" && only.role == "link" => (Some(link(only)), &[]),
+        children if node.role == "link" => (Some(link(node)), children),
+        children if node.r#ref.is_some() => (Some(control(node)), children),
+        children => (node.name.clone(), children),
+    };
+
+    let Some(text) = text else {
+        // Unnamed groups inside a list are transparent
+        for child in children {
+            render_item(child, depth, blocks);
+        }
+        return;
+    };
+
+    blocks.push(Block {
+        text: format!("{}- {}", indent, text),
+        bullet: true,
+    });
+
+    for child in children {
+        if child.role == "list" {
+            for item in &child.children {
+                render_item(item, depth + 1, blocks);
+            }
+        } else {
+            render_item(child, depth + 1, blocks);
+        }
+    }
+}
+
+fn push_paragraph(blocks: &mut Vec<Block>, text: String) {
+    blocks.push(Block {
+        text,
+        bullet: false,
+    });
+}
+
+fn link(node: &SnapshotNode) -> String {
+    let name = node
+        .name
+        .as_deref()
+        .unwrap_or_default()
+        .replace('[', "\\[")
+        .replace(']', "\\]");
+    match &node.r#ref {
+        Some(r#ref) => format!("[{}]({})", name, r#ref),
+        None => name,
+    }
+}
+
+fn control(node: &SnapshotNode) -> String {
+    let r#ref = node.r#ref.as_deref().unwrap_or_default();
+    match &node.name {
+        Some(name) => format!("{}: {} ({})", node.role, name, r#ref),
+        None => format!("{} ({})", node.role, r#ref),
+    }
+}
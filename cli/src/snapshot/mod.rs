@@ -0,0 +1,354 @@
+//! Snapshot model and export formats
+//!
+//! Parses the accessibility tree text returned by the extension
+//! (`- role "name" [ref=eN]`, two spaces of indent per level) into a node
+//! tree, and renders that tree in the formats accepted by
+//! `snapshot --format`.
+
+pub mod archive;
+pub mod cache;
+pub mod diff;
+mod html;
+mod markdown;
+mod text;
+mod yaml;
+
+use crate::types::SnapshotData;
+
+// =============================================================================
+// Constants
+// =============================================================================
+
+/// Role given to lines that are not `- role ...` entries, such as the
+/// `(empty page)` placeholder the extension emits
+pub const TEXT_ROLE: &str = "#text";
+
+/// Role of the document root emitted by the extension
+pub const ROOT_ROLE: &str = "RootWebArea";
+
+/// Number of spaces per indentation level in the snapshot text
+const INDENT_WIDTH: usize = 2;
+
+// =============================================================================
+// Snapshot Format
+// =============================================================================
+
+/// Export format for `snapshot --format`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum SnapshotFormat {
+    /// Indented accessibility tree (the extension's native layout)
+    #[default]
+    Text,
+    /// Markdown headings, links and bullets for LLM prompts
+    Markdown,
+    /// Playwright-style aria tree
+    Yaml,
+    /// Collapsible HTML outline for debugging
+    Html,
+}
+
+// =============================================================================
+// Snapshot Model
+// =============================================================================
+
+/// A single node of the accessibility tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotNode {
+    pub role: String,
+    pub name: Option<String>,
+    pub r#ref: Option<String>,
+    pub children: Vec<SnapshotNode>,
+}
+
+impl SnapshotNode {
+    /// Heading level for `heading`/`heading1`..`heading6` roles
+    ///
+    /// A bare `heading` gets level 2, the ARIA default.
+    pub fn heading_level(&self) -> Option<usize> {
+        let suffix = self.role.strip_prefix("heading")?;
+        if suffix.is_empty() {
+            return Some(2);
+        }
+        match suffix.parse::<usize>() {
+            Ok(level) if (1..=6).contains(&level) => Some(level),
+            _ => None,
+        }
+    }
+
+    /// Whether this node is a free-text line rather than a tree entry
+    pub fn is_text(&self) -> bool {
+        self.role == TEXT_ROLE
+    }
+}
+
+/// A parsed page snapshot
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    pub title: String,
+    pub url: String,
+    pub nodes: Vec<SnapshotNode>,
+}
+
+impl Snapshot {
+    /// Parse the snapshot text of a response into a node tree
+    pub fn parse(data: &SnapshotData) -> Self {
+        Self {
+            title: data.title.clone(),
+            url: data.url.clone(),
+            nodes: parse_tree(&data.snapshot),
+        }
+    }
+}
+
+// =============================================================================
+// Rendering
+// =============================================================================
+
+/// Render snapshot data in the requested format
+pub fn render(data: &SnapshotData, format: SnapshotFormat) -> String {
+    let snapshot = Snapshot::parse(data);
+    match format {
+        SnapshotFormat::Text => text::render(&snapshot),
+        SnapshotFormat::Markdown => markdown::render(&snapshot),
+        SnapshotFormat::Yaml => yaml::render(&snapshot),
+        SnapshotFormat::Html => html::render(&snapshot),
+    }
+}
+
+// =============================================================================
+// Parsing
+// =============================================================================
+
+/// Parse snapshot text into a list of root nodes
+pub fn parse_tree(text: &str) -> Vec<SnapshotNode> {
+    let mut roots = Vec::new();
+    let mut stack: Vec<(usize, SnapshotNode)> = Vec::new();
+
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let (depth, node) = parse_line(line);
+        while stack.last().is_some_and(|(d, _)| *d >= depth) {
+            if let Some((_, done)) = stack.pop() {
+                attach(&mut stack, &mut roots, done);
+            }
+        }
+        stack.push((depth, node));
+    }
+
+    while let Some((_, done)) = stack.pop() {
+        attach(&mut stack, &mut roots, done);
+    }
+
+    roots
+}
+
+fn attach(stack: &mut [(usize, SnapshotNode)], roots: &mut Vec<SnapshotNode>, node: SnapshotNode) {
+    match stack.last_mut() {
+        Some((_, parent)) => parent.children.push(node),
+        None => roots.push(node),
+    }
+}
+
+/// Parse one line into its depth and node
+fn parse_line(line: &str) -> (usize, SnapshotNode) {
+    let content = line.trim_start_matches(' ');
+    let depth = (line.len() - content.len()) / INDENT_WIDTH;
+
+    let text_node = || SnapshotNode {
+        role: TEXT_ROLE.to_string(),
+        name: Some(content.trim_end().to_string()),
+        r#ref: None,
+        children: Vec::new(),
+    };
+
+    let Some(rest) = content.strip_prefix("- ") else {
+        return (depth, text_node());
+    };
+
+    let role_end = rest.find(' ').unwrap_or(rest.len());
+    let role = &rest[..role_end];
+    if role.is_empty() {
+        return (depth, text_node());
+    }
+    let mut rest = rest[role_end..].trim_start();
+
+    let mut name = None;
+    if let Some(quoted) = rest.strip_prefix('"') {
+        let Some((value, remaining)) = parse_quoted(quoted) else {
+            return (depth, text_node());
+        };
+        name = Some(value);
+        rest = remaining.trim_start();
+    }
+
+    let mut r#ref = None;
+    if let Some(attr) = rest.strip_prefix("[ref=") {
+        let Some(end) = attr.find(']') else {
+            return (depth, text_node());
+        };
+        r#ref = Some(attr[..end].to_string());
+        rest = attr[end + 1..].trim_start();
+    }
+
+    if !rest.is_empty() {
+        return (depth, text_node());
+    }
+
+    (
+        depth,
+        SnapshotNode {
+            role: role.to_string(),
+            name,
+            r#ref,
+            children: Vec::new(),
+        },
+    )
+}
+
+/// Parse the body of a double-quoted name, returning it and the remainder
+///
+/// The extension escapes `"` as `\"` and nothing else.
+fn parse_quoted(input: &str) -> Option<(String, &str)> {
+    let mut value = String::new();
+    let mut chars = input.char_indices();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' if input[i + 1..].starts_with('"') => {
+                chars.next();
+                value.push('"');
+            }
+            '"' => return Some((value, &input[i + 1..])),
+            other => value.push(other),
+        }
+    }
+
+    None
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = include_str!("../../tests/fixtures/snapshot/example.txt");
+
+    fn fixture_data() -> SnapshotData {
+        SnapshotData {
+            snapshot: FIXTURE.trim_end().to_string(),
+            title: "Example Domain".to_string(),
+            url: "https://example.com/".to_string(),
+        }
+    }
+
+    #[test]
+    fn parse_line_reads_role_name_and_ref() {
+        let (depth, node) = parse_line("    - button \"Sign in\" [ref=e7]");
+        assert_eq!(depth, 2);
+        assert_eq!(node.role, "button");
+        assert_eq!(node.name.as_deref(), Some("Sign in"));
+        assert_eq!(node.r#ref.as_deref(), Some("e7"));
+    }
+
+    #[test]
+    fn parse_line_unescapes_quotes_in_name() {
+        let (_, node) = parse_line("- heading2 \"More \\\"info\\\"\"");
+        assert_eq!(node.name.as_deref(), Some("More \"info\""));
+        assert_eq!(node.r#ref, None);
+    }
+
+    #[test]
+    fn parse_line_falls_back_to_text_node() {
+        let (depth, node) = parse_line("(empty page)");
+        assert_eq!(depth, 0);
+        assert!(node.is_text());
+        assert_eq!(node.name.as_deref(), Some("(empty page)"));
+    }
+
+    #[test]
+    fn parse_tree_nests_by_indentation() {
+        let nodes = parse_tree(
+            "- main\n  - list\n    - listitem \"A\"\n  - button [ref=e2]\n- contentinfo",
+        );
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[0].children.len(), 2);
+        assert_eq!(nodes[0].children[0].children[0].name.as_deref(), Some("A"));
+        assert_eq!(nodes[0].children[1].r#ref.as_deref(), Some("e2"));
+        assert_eq!(nodes[1].role, "contentinfo");
+    }
+
+    #[test]
+    fn heading_level_reads_suffix() {
+        let node = |role: &str| SnapshotNode {
+            role: role.to_string(),
+            name: None,
+            r#ref: None,
+            children: Vec::new(),
+        };
+        assert_eq!(node("heading1").heading_level(), Some(1));
+        assert_eq!(node("heading6").heading_level(), Some(6));
+        assert_eq!(node("heading").heading_level(), Some(2));
+        assert_eq!(node("heading9").heading_level(), None);
+        assert_eq!(node("link").heading_level(), None);
+    }
+
+    #[test]
+    fn golden_text() {
+        let output = render(&fixture_data(), SnapshotFormat::Text);
+        assert_eq!(output, FIXTURE.trim_end());
+    }
+
+    #[test]
+    fn golden_markdown() {
+        let output = render(&fixture_data(), SnapshotFormat::Markdown);
+        assert_eq!(
+            output,
+            include_str!("../../tests/fixtures/snapshot/example.md").trim_end()
+        );
+    }
+
+    #[test]
+    fn markdown_keeps_h1_that_differs_from_title() {
+        let data = SnapshotData {
+            title: "Example Domain - Home".to_string(),
+            ..fixture_data()
+        };
+        let output = render(&data, SnapshotFormat::Markdown);
+        assert!(output.starts_with("# Example Domain - Home\n"));
+        assert!(output.contains("\n\n# Example Domain\n\n"));
+    }
+
+    #[test]
+    fn golden_yaml() {
+        let output = render(&fixture_data(), SnapshotFormat::Yaml);
+        assert_eq!(
+            output,
+            include_str!("../../tests/fixtures/snapshot/example.yaml").trim_end()
+        );
+    }
+
+    #[test]
+    fn golden_html() {
+        let output = render(&fixture_data(), SnapshotFormat::Html);
+        assert_eq!(
+            output,
+            include_str!("../../tests/fixtures/snapshot/example.html").trim_end()
+        );
+    }
+
+    #[test]
+    fn text_round_trips_placeholder() {
+        let data = SnapshotData {
+            snapshot: "(empty page)".to_string(),
+            title: String::new(),
+            url: String::new(),
+        };
+        assert_eq!(render(&data, SnapshotFormat::Text), "(empty page)");
+    }
+}
@@ -0,0 +1,35 @@
+//! Text renderer
+//!
+//! Re-emits the tree in the extension's native layout, so a parsed
+//! snapshot renders back to the text it was parsed from.
+
+use super::{INDENT_WIDTH, Snapshot, SnapshotNode};
+
+pub fn render(snapshot: &Snapshot) -> String {
+    let mut lines = Vec::new();
+    for node in &snapshot.nodes {
+        render_node(node, 0, &mut lines);
+    }
+    lines.join("\n")
+}
+
+fn render_node(node: &SnapshotNode, depth: usize, lines: &mut Vec<String>) {
+    let indent = " ".repeat(depth * INDENT_WIDTH);
+
+    if node.is_text() {
+        lines.push(format!("{}{}", indent, node.name.as_deref().unwrap_or("")));
+    } else {
+        let mut line = format!("{}- {}", indent, node.role);
+        if let Some(name) = &node.name {
+            line.push_str(&format!(" \"{}\"", name.replace('"', "\\\"")));
+        }
+        if let Some(r#ref) = &node.r#ref {
+            line.push_str(&format!(" [ref={}]", r#ref));
+        }
+        lines.push(line);
+    }
+
+    for child in &node.children {
+        render_node(child, depth + 1, lines);
+    }
+}
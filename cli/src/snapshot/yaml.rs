@@ -0,0 +1,76 @@
+//! YAML renderer
+//!
+//! Emits a Playwright-style aria tree: one `- role "name" [attr=value]`
+//! sequence entry per node, with a trailing `:` and a nested sequence when
+//! the node has children.
+
+use super::{INDENT_WIDTH, Snapshot, SnapshotNode};
+
+pub fn render(snapshot: &Snapshot) -> String {
+    let mut lines = Vec::new();
+    for node in &snapshot.nodes {
+        render_node(node, 0, &mut lines);
+    }
+    lines.join("\n")
+}
+
+fn render_node(node: &SnapshotNode, depth: usize, lines: &mut Vec<String>) {
+    let indent = " ".repeat(depth * INDENT_WIDTH);
+
+    if node.is_text() {
+        let text = node.name.as_deref().unwrap_or_default();
+        lines.push(format!("{}- text: {}", indent, quote_if_needed(text)));
+        return;
+    }
+
+    let mut key = match node.heading_level() {
+        Some(_) => "heading".to_string(),
+        None => node.role.clone(),
+    };
+    if let Some(name) = &node.name {
+        key.push_str(&format!(
+            " \"{}\"",
+            name.replace('\\', "\\\\").replace('"', "\\\"")
+        ));
+    }
+    if node.role != "heading"
+        && let Some(level) = node.heading_level()
+    {
+        key.push_str(&format!(" [level={}]", level));
+    }
+    if let Some(r#ref) = &node.r#ref {
+        key.push_str(&format!(" [ref={}]", r#ref));
+    }
+
+    if node.children.is_empty() {
+        lines.push(format!("{}- {}", indent, quote_if_needed(&key)));
+    } else {
+        lines.push(format!("{}- {}:", indent, quote_if_needed(&key)));
+        for child in &node.children {
+            render_node(child, depth + 1, lines);
+        }
+    }
+}
+
+/// Single-quote a plain scalar when YAML would otherwise misread it
+fn quote_if_needed(value: &str) -> String {
+    const INDICATORS: &[char] = &[
+        '-', '?', ':', ',', '[', ']', '{', '}', '#', '&', '*', '!', '|', '>', '\'', '"', '%', '@',
+        '`',
+    ];
+
+    let needs_quoting = value.is_empty()
+        || value.starts_with(INDICATORS)
+        || value.starts_with(char::is_whitespace)
+        || value.ends_with(char::is_whitespace)
+        || value.ends_with(':')
+        || value.contains(": ")
+        || value.contains(" #")
+        || value.contains(['\n', '\t']);
+
+    if needs_quoting {
+        format!("'{}'", value.replace('\'', "''"))
+    } else {
+        value.to_string()
+    }
+}
@@ -7,14 +7,45 @@ use crate::error::CliError;
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 
-#[derive(Debug, Clone, Copy, Default, clap::ValueEnum)]
+/// Version of the CLI <-> daemon <-> extension protocol this CLI speaks
+///
+/// The major version changes when messages change incompatibly.
+pub const PROTOCOL_VERSION: &str = "1.1";
+
+/// Whether a component speaking protocol `version` can work with this CLI
+///
+/// Versions are `MAJOR.MINOR`; only the major has to match.
+pub fn protocol_compatible(version: &str) -> bool {
+    let major = |version: &str| version.split('.').next().unwrap_or_default().to_string();
+    major(version) == major(PROTOCOL_VERSION)
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
 pub enum OutputFormat {
     #[default]
     Human,
     Json,
+    /// One compact JSON object per line
+    Ndjson,
+    Yaml,
+    /// Aligned columns
+    Table,
+    /// Rendered with `--template`
+    Template,
     Quiet,
 }
 
+/// When to color human output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ColorChoice {
+    /// Color when writing to a terminal and NO_COLOR is unset
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
 // =============================================================================
 // Command Types
 // =============================================================================
@@ -84,6 +115,10 @@ pub struct Command {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub params: Option<serde_json::Value>,
     pub timestamp: String, // ISO 8601 format
+    /// When the CLI stops waiting for the response (ISO 8601), so the daemon
+    /// can abort instead of finishing work nobody will read
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deadline: Option<String>,
 }
 
 /// Response from daemon back to CLI
@@ -97,6 +132,44 @@ pub struct CommandResponse {
     pub error: Option<String>,
 }
 
+/// Failure report printed on stdout in JSON output mode
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorEnvelope {
+    pub success: bool,
+    pub error: ErrorInfo,
+}
+
+/// Details of a failure
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorInfo {
+    /// Stable error kind, see `CliError::kind`
+    pub kind: String,
+    pub message: String,
+    pub exit_code: i32,
+    /// ID of the daemon command that failed, if one was sent
+    pub command_id: Option<CommandId>,
+}
+
+/// Invocation details added to the output by `--timing`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResponseMeta {
+    /// ID sent in `Command.id`, from `--request-id` or generated
+    pub command_id: CommandId,
+    pub session: String,
+    pub duration_ms: DurationBreakdown,
+}
+
+/// Where the time of an invocation went, in milliseconds
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct DurationBreakdown {
+    /// Connecting to the daemon socket
+    pub connect: u64,
+    /// Waiting for the daemon to run commands
+    pub daemon: u64,
+    /// The whole invocation, including local work
+    pub total: u64,
+}
+
 // =============================================================================
 // IPC Message Types
 // =============================================================================
@@ -109,6 +182,10 @@ pub enum IpcMessageType {
     Response,
     Ping,
     Pong,
+    Status,
+    Shutdown,
+    Cancel,
+    CancelAck,
 }
 
 /// IPC message envelope
@@ -117,6 +194,29 @@ pub struct IpcMessage {
     #[serde(rename = "type")]
     pub message_type: IpcMessageType,
     pub payload: Option<serde_json::Value>,
+    /// Asks the daemon to keep the connection open; echoed if it does
+    #[serde(
+        rename = "keepAlive",
+        default,
+        skip_serializing_if = "std::ops::Not::not"
+    )]
+    pub keep_alive: bool,
+}
+
+/// Payload of a `cancel` message: the command the CLI stopped waiting for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelPayload {
+    pub command_id: CommandId,
+}
+
+/// Payload of the daemon's `cancel_ack` reply
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelAck {
+    pub command_id: CommandId,
+    /// False if the command had already finished or never arrived
+    pub cancelled: bool,
 }
 
 // =============================================================================
@@ -177,3 +277,227 @@ pub struct SnapshotData {
     pub title: String,
     pub url: String,
 }
+
+/// Data returned from an action run with `--then-snapshot` or `--then-diff`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionSnapshotData {
+    /// Response data of the action itself
+    pub action: serde_json::Value,
+    pub snapshot: SnapshotData,
+    /// Lines changed since the previous snapshot, prefixed by `+ ` or `- `
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff: Option<Vec<String>>,
+}
+
+/// A snapshot stored in the archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedSnapshot {
+    pub id: u64,
+    pub session: String,
+    pub timestamp: String,
+    pub title: String,
+    pub url: String,
+    pub snapshot: String,
+}
+
+/// Data returned from history search command
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveSearchData {
+    pub query: String,
+    pub matches: Vec<ArchiveMatch>,
+}
+
+/// An archived snapshot matching a search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveMatch {
+    pub id: u64,
+    pub session: String,
+    pub timestamp: String,
+    pub title: String,
+    pub url: String,
+    /// Snapshot lines containing the query
+    pub excerpts: Vec<String>,
+}
+
+/// Data returned from alias list command
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AliasListData {
+    pub session: String,
+    pub aliases: Vec<AliasInfo>,
+}
+
+/// A named locator usable as `@name`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AliasInfo {
+    pub name: String,
+    pub locator: String,
+    /// Domain the alias is scoped to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub domain: Option<String>,
+    /// URL pattern of the page object defining it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<String>,
+    /// Project file defining it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+}
+
+/// Data returned from config list command
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigListData {
+    /// Config file the settings are read from
+    pub path: String,
+    /// Project file found above the current directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    /// Effective value of every key that is set
+    pub entries: Vec<ConfigEntry>,
+}
+
+/// A config key and its effective value
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigEntry {
+    pub key: String,
+    pub value: serde_json::Value,
+    /// Where the value came from, with `config show --origin`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<String>,
+}
+
+/// Data returned from context list command
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextListData {
+    /// Context in use, if any
+    pub active: Option<String>,
+    pub contexts: Vec<ContextInfo>,
+}
+
+/// A named context and the settings it bundles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextInfo {
+    pub name: String,
+    pub active: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub socket_path: Option<String>,
+}
+
+/// Daemon state returned from daemonctl status and restart
+///
+/// The daemon reports `uptimeMs`; it is printed as `uptime_ms`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonStatusData {
+    pub pid: u32,
+    #[serde(alias = "uptimeMs")]
+    pub uptime_ms: u64,
+    pub version: String,
+    pub socket: String,
+    pub sessions: usize,
+}
+
+/// Versions the daemon reports in reply to a ping
+///
+/// Daemons that predate the version handshake send no payload.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PongData {
+    #[serde(alias = "protocolVersion")]
+    pub protocol_version: String,
+    pub version: String,
+    /// Extensions connected to the daemon
+    #[serde(default)]
+    pub extensions: Vec<ExtensionVersion>,
+}
+
+/// Versions an extension reported when it connected for a session
+///
+/// Extensions that predate the version handshake report neither.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtensionVersion {
+    pub session: String,
+    #[serde(alias = "sessionId")]
+    pub session_id: String,
+    pub version: Option<String>,
+    #[serde(alias = "protocolVersion")]
+    pub protocol_version: Option<String>,
+}
+
+/// Version of one component in `version --all`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComponentVersion {
+    /// `None` if the component predates the version handshake
+    pub version: Option<String>,
+    pub protocol_version: Option<String>,
+}
+
+/// Data returned from version --all
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionData {
+    pub cli: ComponentVersion,
+    /// `None` if no daemon is running
+    pub daemon: Option<ComponentVersion>,
+    pub extensions: Vec<ExtensionVersion>,
+}
+
+/// Outcome of a `doctor` check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// One `doctor` check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DoctorCheck {
+    pub name: String,
+    pub status: CheckStatus,
+    pub message: String,
+    /// What to do about a warning or failure
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remedy: Option<String>,
+}
+
+/// Data returned from doctor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DoctorData {
+    pub checks: Vec<DoctorCheck>,
+}
+
+/// Data returned from daemonctl stop
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonStopData {
+    /// Whether a running daemon was stopped
+    pub stopped: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+    /// Signal sent when the daemon did not shut down on request
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signal: Option<String>,
+}
+
+/// Data returned from daemonctl logs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonLogsData {
+    pub path: String,
+    pub lines: Vec<String>,
+}
+
+/// Data returned from daemon install-service
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceInstallData {
+    /// Unit files written
+    pub units: Vec<String>,
+    /// Commands that enable the units
+    pub next_steps: Vec<String>,
+}
+
+/// Data returned from config path command
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigPathData {
+    pub path: String,
+    pub exists: bool,
+}
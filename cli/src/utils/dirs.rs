@@ -0,0 +1,88 @@
+use crate::utils::files::get_home_dir;
+use std::path::PathBuf;
+
+/// Application directory name under the XDG base directories
+const APP_DIR_NAME: &str = "browser";
+
+/// Get the config directory ($XDG_CONFIG_HOME/browser or ~/.config/browser)
+pub fn get_config_dir() -> Result<PathBuf, String> {
+    xdg_dir("XDG_CONFIG_HOME", &[".config"])
+}
+
+/// Get the data directory ($XDG_DATA_HOME/browser or ~/.local/share/browser)
+pub fn get_data_dir() -> Result<PathBuf, String> {
+    xdg_dir("XDG_DATA_HOME", &[".local", "share"])
+}
+
+/// Get the state directory ($XDG_STATE_HOME/browser or ~/.local/state/browser)
+pub fn get_state_dir() -> Result<PathBuf, String> {
+    xdg_dir("XDG_STATE_HOME", &[".local", "state"])
+}
+
+/// Get the runtime directory for sockets ($XDG_RUNTIME_DIR/browser)
+///
+/// Without `XDG_RUNTIME_DIR`, falls back to a per-user directory in the
+/// system temp directory, which `ensure_private_dir` must check before use
+/// since another user could have created it first.
+#[cfg(unix)]
+pub fn get_runtime_dir() -> PathBuf {
+    if let Ok(dir) = std::env::var("XDG_RUNTIME_DIR")
+        && !dir.is_empty()
+        && std::path::Path::new(&dir).is_absolute()
+    {
+        return PathBuf::from(dir).join(APP_DIR_NAME);
+    }
+
+    // SAFETY: geteuid has no preconditions and cannot fail
+    let uid = unsafe { libc::geteuid() };
+    std::env::temp_dir().join(format!("{}-{}", APP_DIR_NAME, uid))
+}
+
+/// Create `dir` readable only by the current user, or check an existing one
+///
+/// An existing directory must be owned by the current user; group and other
+/// permissions are removed from it.
+#[cfg(unix)]
+pub fn ensure_private_dir(dir: &std::path::Path) -> Result<(), String> {
+    use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+
+    if let Some(parent) = dir.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("failed to create {}: {}", parent.display(), e))?;
+    }
+    match std::fs::DirBuilder::new().mode(0o700).create(dir) {
+        Ok(()) => return Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+        Err(e) => return Err(format!("failed to create {}: {}", dir.display(), e)),
+    }
+
+    let metadata = std::fs::symlink_metadata(dir)
+        .map_err(|e| format!("failed to read {}: {}", dir.display(), e))?;
+    // SAFETY: geteuid has no preconditions and cannot fail
+    let uid = unsafe { libc::geteuid() };
+    if !metadata.is_dir() || metadata.uid() != uid {
+        return Err(format!(
+            "refusing to use {}: it is not a directory owned by uid {}",
+            dir.display(),
+            uid
+        ));
+    }
+    if metadata.mode() & 0o077 != 0 {
+        std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))
+            .map_err(|e| format!("failed to restrict {}: {}", dir.display(), e))?;
+    }
+    Ok(())
+}
+
+/// Resolve an XDG base directory, falling back to a path under the home directory
+fn xdg_dir(env_var: &str, fallback: &[&str]) -> Result<PathBuf, String> {
+    if let Ok(dir) = std::env::var(env_var)
+        && !dir.is_empty()
+    {
+        return Ok(PathBuf::from(dir).join(APP_DIR_NAME));
+    }
+
+    let mut path = PathBuf::from(get_home_dir()?);
+    path.extend(fallback);
+    Ok(path.join(APP_DIR_NAME))
+}
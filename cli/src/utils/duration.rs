@@ -0,0 +1,61 @@
+use std::time::Duration;
+
+/// Parse a human duration such as `500ms`, `2s`, `1.5s` or `1m`
+///
+/// A unit is required so `--timeout 30` is not silently read as
+/// milliseconds. Zero is rejected: it would mean "no timeout" to the socket.
+pub fn parse_duration(input: &str) -> Result<Duration, String> {
+    let input = input.trim();
+    let split = input
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(input.len());
+    let (number, unit) = input.split_at(split);
+
+    let invalid = || {
+        format!(
+            "invalid duration '{}': expected a number and a unit (ms, s, m), e.g. 500ms or 2s",
+            input
+        )
+    };
+    let value: f64 = number.parse().map_err(|_| invalid())?;
+    let millis = match unit.trim() {
+        "ms" => value,
+        "s" => value * 1000.0,
+        "m" => value * 60_000.0,
+        _ => return Err(invalid()),
+    };
+
+    if !millis.is_finite() || millis < 1.0 {
+        return Err(format!(
+            "invalid duration '{}': must be at least 1ms",
+            input
+        ));
+    }
+    Ok(Duration::from_millis(millis.round() as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_units() {
+        assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("2s"), Ok(Duration::from_secs(2)));
+        assert_eq!(parse_duration("1.5s"), Ok(Duration::from_millis(1500)));
+        assert_eq!(parse_duration("1m"), Ok(Duration::from_secs(60)));
+        assert_eq!(parse_duration(" 10 s "), Ok(Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn rejects_missing_units_and_zero() {
+        assert!(
+            parse_duration("30")
+                .unwrap_err()
+                .contains("expected a number and a unit")
+        );
+        assert!(parse_duration("2h").is_err());
+        assert!(parse_duration("ms").is_err());
+        assert!(parse_duration("0s").unwrap_err().contains("at least 1ms"));
+    }
+}
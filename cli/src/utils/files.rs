@@ -92,3 +92,33 @@ pub fn extract_zip(archive_bytes: &[u8], dest_dir: &Path) -> Result<(), String>
 
     Ok(())
 }
+
+/// Make a name safe to use as a file name (session names, domains)
+///
+/// Characters other than ASCII letters, digits, `-`, `_` and `.` are
+/// percent-encoded byte by byte, so distinct names map to distinct files.
+pub fn escape_file_name(name: &str) -> String {
+    let mut escaped = String::with_capacity(name.len());
+    for byte in name.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.') {
+            escaped.push(byte as char);
+        } else {
+            escaped.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_file_name_keeps_distinct_names_distinct() {
+        assert_eq!(escape_file_name("agent-1.main"), "agent-1.main");
+        assert_eq!(escape_file_name("a/b"), "a%2Fb");
+        assert_eq!(escape_file_name("a_b"), "a_b");
+        assert_eq!(escape_file_name("50%"), "50%25");
+        assert_eq!(escape_file_name("é"), "%C3%A9");
+    }
+}
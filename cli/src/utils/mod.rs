@@ -1,3 +1,5 @@
+pub mod dirs;
+pub mod duration;
 pub mod files;
 pub mod plugins;
 pub mod tls_client;
@@ -0,0 +1,136 @@
+//! Auto-starting the daemon from many CLI processes at once, and again
+//! after it crashes
+//!
+//! Runs the `browser` binary with this test as its daemon: when started with
+//! `--socket`, the test binary acts as a stub daemon that records each start
+//! and answers every command. Without a harness, `main` picks the role.
+
+use std::env;
+use std::path::Path;
+use std::time::Duration;
+
+/// Number of CLI processes started at once
+const CLIENTS: usize = 8;
+
+/// Environment variable naming the file the stub daemon records starts in
+const ENV_SPAWN_LOG: &str = "BROWSER_TEST_SPAWN_LOG";
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if let Some(i) = args.iter().position(|arg| arg == "--socket") {
+        #[cfg(unix)]
+        {
+            record_start();
+            // Start slowly, so every client sees no daemon at first
+            stub::serve(Path::new(&args[i + 1]), Duration::from_millis(300));
+        }
+        return;
+    }
+
+    #[cfg(unix)]
+    {
+        print!("test concurrent_clients_start_one_daemon_and_replace_it_after_a_crash ... ");
+        concurrent_clients_start_one_daemon_and_replace_it_after_a_crash();
+        println!("ok");
+    }
+}
+
+/// Append this stub daemon's PID to the spawn log
+#[cfg(unix)]
+fn record_start() {
+    use std::fs::OpenOptions;
+    use std::io::Write;
+
+    if let Ok(path) = env::var(ENV_SPAWN_LOG) {
+        let mut log = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap();
+        writeln!(log, "{}", std::process::id()).unwrap();
+    }
+}
+
+#[cfg(unix)]
+fn concurrent_clients_start_one_daemon_and_replace_it_after_a_crash() {
+    use std::fs;
+    use std::process::Command;
+    use std::thread;
+
+    let root = env::temp_dir().join(format!("browser-startup-test-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(root.join("config/browser")).unwrap();
+    fs::write(
+        root.join("config/browser/config.toml"),
+        format!(
+            "[plugins.daemon]\npath = {:?}\n",
+            env::current_exe().unwrap().to_string_lossy()
+        ),
+    )
+    .unwrap();
+    let spawn_log = root.join("spawns");
+
+    let browser = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_browser"))
+            .args(args)
+            .env("XDG_CONFIG_HOME", root.join("config"))
+            .env("XDG_RUNTIME_DIR", root.join("run"))
+            .env("XDG_STATE_HOME", root.join("state"))
+            .env(ENV_SPAWN_LOG, &spawn_log)
+            .env_remove("BROWSER_SOCKET_PATH")
+            .env_remove("BROWSER_CONTEXT")
+            .current_dir(&root)
+            .output()
+            .unwrap()
+    };
+
+    let outputs: Vec<_> = thread::scope(|scope| {
+        let clients: Vec<_> = (0..CLIENTS)
+            .map(|_| scope.spawn(|| browser(&["back"])))
+            .collect();
+        clients
+            .into_iter()
+            .map(|client| client.join().unwrap())
+            .collect()
+    });
+    for output in outputs {
+        assert!(
+            output.status.success(),
+            "client failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    let starts = fs::read_to_string(&spawn_log).unwrap_or_default();
+    assert_eq!(starts.lines().count(), 1, "daemon started {:?}", starts);
+
+    // A crashed daemon leaves its socket behind; the next command replaces it
+    let pid: libc::pid_t = starts.trim().parse().unwrap();
+    // SAFETY: kill has no memory-safety preconditions
+    unsafe { libc::kill(pid, libc::SIGKILL) };
+    let socket = root.join("run/browser/daemon.sock");
+    for _ in 0..200 {
+        if std::os::unix::net::UnixStream::connect(&socket).is_err() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert!(socket.exists());
+    let output = browser(&["back"]);
+    assert!(
+        output.status.success(),
+        "client failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains("removing stale socket"));
+    let starts = fs::read_to_string(&spawn_log).unwrap();
+    assert_eq!(starts.lines().count(), 2, "daemon started {:?}", starts);
+
+    let output = browser(&["daemonctl", "stop"]);
+    assert!(output.status.success());
+    let _ = fs::remove_dir_all(&root);
+}
+
+#[cfg(unix)]
+#[path = "support/stub_daemon.rs"]
+mod stub;
@@ -0,0 +1,89 @@
+//! A daemon that answers every command with success
+//!
+//! Shared by the integration tests and benchmarks that drive the `browser`
+//! binary. Like the real daemon, it keeps a connection open for the next
+//! message when the client asks it to.
+
+// Each harness that includes this module uses part of it
+#![allow(dead_code)]
+
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+/// Connections accepted so far
+static CONNECTIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// Messages answered so far
+static MESSAGES: AtomicUsize = AtomicUsize::new(0);
+
+/// Connections accepted so far
+pub fn connections() -> usize {
+    CONNECTIONS.load(Ordering::SeqCst)
+}
+
+/// Messages answered so far
+pub fn messages() -> usize {
+    MESSAGES.load(Ordering::SeqCst)
+}
+
+/// Listen on `socket` after `startup_delay` and answer clients until exit
+pub fn serve(socket: &Path, startup_delay: Duration) {
+    thread::sleep(startup_delay);
+    let _ = fs::remove_file(socket);
+    let listener = UnixListener::bind(socket).unwrap();
+    fs::set_permissions(socket, fs::Permissions::from_mode(0o600)).unwrap();
+
+    for stream in listener.incoming().flatten() {
+        CONNECTIONS.fetch_add(1, Ordering::SeqCst);
+        let socket = socket.to_path_buf();
+        thread::spawn(move || answer(stream, socket));
+    }
+}
+
+fn answer(stream: UnixStream, socket: PathBuf) {
+    let mut writer = stream.try_clone().unwrap();
+    let mut reader = BufReader::new(stream);
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).unwrap_or(0) == 0 {
+            return;
+        }
+        MESSAGES.fetch_add(1, Ordering::SeqCst);
+
+        let message: serde_json::Value = serde_json::from_str(&line).unwrap();
+        let mut reply = match message["type"].as_str() {
+            Some("ping") => serde_json::json!({
+                "type": "pong",
+                "payload": {"protocolVersion": "1.1", "version": "0.2.4", "extensions": []}
+            }),
+            Some("shutdown") => serde_json::json!({"type": "shutdown", "payload": null}),
+            _ => serde_json::json!({
+                "type": "response",
+                "payload": {
+                    "id": message["payload"]["id"],
+                    "success": true,
+                    "data": {"executed": true}
+                }
+            }),
+        };
+        let keep_alive = message["keepAlive"] == true;
+        if keep_alive {
+            reply["keepAlive"] = true.into();
+        }
+
+        let _ = writer.write_all(format!("{}\n", reply).as_bytes());
+        if message["type"] == "shutdown" {
+            let _ = fs::remove_file(&socket);
+            std::process::exit(0);
+        }
+        if !keep_alive {
+            return;
+        }
+    }
+}
@@ -39,6 +39,17 @@ function openUrl(url: string): void {
 
 import { validateCommand } from "./validation.js";
 
+/**
+ * Milliseconds left before the command's deadline, or undefined if it has none
+ */
+function timeUntilDeadline(command: Command): number | undefined {
+  if (!command.deadline) {
+    return undefined;
+  }
+  const deadline = Date.parse(command.deadline);
+  return Number.isNaN(deadline) ? undefined : deadline - Date.now();
+}
+
 /**
  * Pending command tracking
  */
@@ -292,6 +303,18 @@ export class CommandRouter {
    */
   private executeCommand(command: Command): Promise<CommandResponse> {
     return new Promise<CommandResponse>((resolve, reject) => {
+      // The CLI has stopped waiting, e.g. while the command was queued
+      const remaining = timeUntilDeadline(command);
+      if (remaining !== undefined && remaining <= 0) {
+        resolve({
+          id: command.id,
+          success: false,
+          error: "Command timed out",
+        });
+        this.processNextInQueue(command.sessionId);
+        return;
+      }
+
       // Mark command as in-flight for session
       this.inFlightCommands.set(command.sessionId, command.id);
 
@@ -312,7 +335,7 @@ export class CommandRouter {
       }
 
       // Set up timeout timer
-      const timeoutId = this.setupCommandTimeout(command.id);
+      const timeoutId = this.setupCommandTimeout(command);
 
       // Store pending command with promise callbacks
       this.pendingCommands.set(command.id, {
@@ -418,11 +441,12 @@ export class CommandRouter {
   /**
    * Set up timeout for a pending command
    */
-  private setupCommandTimeout(commandId: CommandId): NodeJS.Timeout {
-    // Create timeout timer using heartbeat timeout from config or default
-    const timeout = this.config.heartbeatTimeout || DEFAULT_COMMAND_TIMEOUT;
+  private setupCommandTimeout(command: Command): NodeJS.Timeout {
+    // Give up at the CLI's deadline, else after the heartbeat timeout from config or default
+    const timeout =
+      timeUntilDeadline(command) ?? (this.config.heartbeatTimeout || DEFAULT_COMMAND_TIMEOUT);
     return setTimeout(() => {
-      this.handleCommandTimeout(commandId);
+      this.handleCommandTimeout(command.id);
     }, timeout);
   }
 
@@ -549,6 +573,42 @@ export class CommandRouter {
   // Cleanup
   // ===========================================================================
 
+  /**
+   * Cancel one command the CLI stopped waiting for
+   *
+   * A queued command is dropped; for one in flight the session moves on to
+   * its next command, and whatever the extension sends back is discarded.
+   * Returns false if the command is neither queued nor in flight.
+   */
+  cancel(commandId: CommandId): boolean {
+    const cancelled: CommandResponse = {
+      id: commandId,
+      success: false,
+      error: "Command cancelled",
+    };
+
+    const pending = this.pendingCommands.get(commandId);
+    if (pending) {
+      clearTimeout(pending.timeoutId);
+      this.pendingCommands.delete(commandId);
+      this.inFlightCommands.delete(pending.command.sessionId);
+      pending.resolve(cancelled);
+      this.processNextInQueue(pending.command.sessionId);
+      return true;
+    }
+
+    for (const queue of this.commandQueues.values()) {
+      const index = queue.findIndex((queued) => queued.command.id === commandId);
+      if (index !== -1) {
+        const [queued] = queue.splice(index, 1);
+        queued.resolve(cancelled);
+        return true;
+      }
+    }
+
+    return false;
+  }
+
   /**
    * Cancel all pending commands and clear queues
    */
@@ -1,7 +1,7 @@
 
 import type { WebSocket } from "ws";
 import type { DaemonConfig, SessionId, Command, CommandResponse } from "./types.js";
-import { DEFAULT_CONFIG } from "./types.js";
+import { DAEMON_VERSION, DEFAULT_CONFIG, PROTOCOL_VERSION } from "./types.js";
 import { IpcServer } from "./ipc-server.js";
 import { WsServer } from "./ws-server.js";
 import { SessionManager } from "./session-manager.js";
@@ -22,6 +22,7 @@ export class BrowserDaemon {
     private commandRouter: CommandRouter;
     private isRunning: boolean = false;
     private shutdownPromise: Promise<void> | null = null;
+    private startedAt: number = 0;
 
     constructor(config: Partial<DaemonConfig> = {}) {
         this.config = { ...DEFAULT_CONFIG, ...config };
@@ -59,6 +60,7 @@ export class BrowserDaemon {
         setupSignalHandlers(this);
 
         this.isRunning = true;
+        this.startedAt = Date.now();
         console.log("browser-daemon started successfully");
     }
 
@@ -124,6 +126,32 @@ export class BrowserDaemon {
 
     private setupEventHandlers(): void {
         this.ipcServer.onCommand((command) => this.handleCliCommand(command));
+        this.ipcServer.onPing(() => ({
+            protocolVersion: PROTOCOL_VERSION,
+            version: DAEMON_VERSION,
+            extensions: this.wsServer.getExtensionVersions(),
+        }));
+        this.ipcServer.onStatus(() => ({
+            pid: process.pid,
+            uptimeMs: Date.now() - this.startedAt,
+            version: DAEMON_VERSION,
+            socket: this.config.ipcSocketPath,
+            sessions: this.sessionManager.listSessions().length,
+        }));
+        this.ipcServer.onShutdown(() => {
+            console.log("Received shutdown request");
+            this.stop().then(() => {
+                process.exit(0);
+            }).catch((err) => {
+                console.error("Error during shutdown:", err);
+                process.exit(1);
+            });
+        });
+        this.ipcServer.onCancel((commandId) => {
+            const cancelled = this.commandRouter.cancel(commandId);
+            console.log(`Cancel requested for command ${commandId}: ${cancelled ? "cancelled" : "not pending"}`);
+            return cancelled;
+        });
         this.wsServer.setEventHandlers({
             onExtensionConnected: (sessionId, ws) => this.handleExtensionConnected(sessionId, ws),
             onExtensionDisconnected: (sessionId) => this.handleExtensionDisconnected(sessionId),
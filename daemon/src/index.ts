@@ -2,10 +2,7 @@ import { BrowserDaemon } from "./daemon.js";
 import { fileURLToPath } from "node:url";
 import { createRequire } from "node:module";
 import { Command } from "commander";
-import { DaemonConfig, DEFAULT_CONFIG } from "./types.js";
-
-
-const DAEMON_VERSION = "0.2.4"
+import { DaemonConfig, DAEMON_VERSION, DEFAULT_CONFIG } from "./types.js";
 
 export function loadConfig(argv: string[]): DaemonConfig {
   const program = new Command();
@@ -22,6 +19,9 @@ Environment Variables:
   BROWSER_SOCKET_PATH   IPC socket path
   BROWSER_WS_PORT       WebSocket server port
   BROWSER_PATH          Browser executable path
+  BROWSER_DAEMON_DEBUG  Set to 1 to log each IPC message's type and command ID
+  LISTEN_FDS, LISTEN_PID  Set by systemd socket activation; the daemon then
+                        listens on the passed socket (fd 3) instead of --socket
 
 Examples:
   browser-daemon                Start with default settings
@@ -3,15 +3,22 @@
  *
  * Exposes a local Unix socket (or named pipe on Windows) for CLI commands.
  * Each CLI invocation connects, sends one command, receives one response, and disconnects.
+ * Pings and commands marked keepAlive leave the connection open for the next message.
  */
 
 import { createServer, type Server, type Socket } from "node:net";
-import { existsSync, unlinkSync } from "node:fs";
+import { chmodSync, existsSync, lstatSync, mkdirSync, unlinkSync } from "node:fs";
+import { dirname } from "node:path";
+import { defaultSocketPath } from "./types.js";
 import type {
   IpcMessage,
+  CancelPayload,
   Command,
+  CommandId,
   CommandResponse,
   DaemonConfig,
+  DaemonStatus,
+  PongPayload,
 } from "./types.js";
 
 /**
@@ -20,11 +27,91 @@ import type {
  */
 const MESSAGE_DELIMITER = "\n";
 
+/**
+ * First file descriptor passed by systemd socket activation (SD_LISTEN_FDS_START)
+ */
+const LISTEN_FDS_START = 3;
+
+/**
+ * The listening socket passed by the service manager, if the daemon was
+ * socket-activated: LISTEN_FDS names how many were passed, and LISTEN_PID
+ * must be this process, so variables inherited from elsewhere are ignored
+ */
+function activatedSocketFd(): number | null {
+  const count = parseInt(process.env.LISTEN_FDS ?? "", 10);
+  const pid = parseInt(process.env.LISTEN_PID ?? "", 10);
+  if (!(count >= 1) || pid !== process.pid) {
+    return null;
+  }
+  return LISTEN_FDS_START;
+}
+
+/**
+ * Create the directory for the socket
+ *
+ * The default directory is made private to the current user and must be
+ * owned by them, since without XDG_RUNTIME_DIR it lives in the shared temp
+ * directory; a configured socket's directory is only created if missing.
+ */
+function prepareSocketDir(socketPath: string): void {
+  const dir = dirname(socketPath);
+  if (process.platform === "win32" || socketPath !== defaultSocketPath()) {
+    mkdirSync(dir, { recursive: true });
+    return;
+  }
+
+  mkdirSync(dir, { recursive: true, mode: 0o700 });
+  const stats = lstatSync(dir);
+  const uid = process.geteuid?.();
+  if (!stats.isDirectory() || stats.uid !== uid) {
+    throw new Error(`refusing to use ${dir}: it is not a directory owned by uid ${uid}`);
+  }
+  if ((stats.mode & 0o077) !== 0) {
+    chmodSync(dir, 0o700);
+  }
+}
+
+/**
+ * Summarize a message for the debug log
+ *
+ * Command parameters carry typed text, such as passwords, and the daemon's
+ * output may be written to a file, so only the message and command types and
+ * the command ID are included.
+ */
+function describeMessage(message: IpcMessage): string {
+  if (message?.type !== "command") {
+    return String(message?.type);
+  }
+  const command = message.payload as Partial<Command> | undefined;
+  return `command ${command?.type} (id ${command?.id})`;
+}
+
 /**
  * Callback type for handling incoming commands from CLI
  */
 export type CommandHandler = (command: Command) => Promise<CommandResponse>;
 
+/**
+ * Callback type for reporting versions in reply to `ping` requests
+ */
+export type PingHandler = () => PongPayload;
+
+/**
+ * Callback type for reporting daemon state to `status` requests
+ */
+export type StatusHandler = () => DaemonStatus;
+
+/**
+ * Callback type for stopping the daemon on a `shutdown` request
+ */
+export type ShutdownHandler = () => void;
+
+/**
+ * Callback type for cancelling a command on a `cancel` request; returns
+ * whether the command was still queued or in flight
+ */
+export type CancelHandler = (commandId: CommandId) => boolean;
+
 
 /**
  * IPC Server class
@@ -33,7 +120,13 @@ export type CommandHandler = (command: Command) => Promise<CommandResponse>;
 export class IpcServer {
   private server: Server | null = null;
   private commandHandler: CommandHandler | null = null;
+  private pingHandler: PingHandler | null = null;
+  private statusHandler: StatusHandler | null = null;
+  private shutdownHandler: ShutdownHandler | null = null;
+  private cancelHandler: CancelHandler | null = null;
   private activeConnections: Set<Socket> = new Set();
+  /** Whether the socket was passed in by the service manager */
+  private activated = false;
 
   constructor(private config: DaemonConfig) {}
 
@@ -45,9 +138,15 @@ export class IpcServer {
    * Start the IPC server and begin listening for CLI connections
    */
   async start(): Promise<void> {
-    // Remove existing socket file if it exists
-    if (existsSync(this.config.ipcSocketPath)) {
-      unlinkSync(this.config.ipcSocketPath);
+    this.activated = activatedSocketFd() !== null;
+
+    // Remove existing socket file if it exists; an activated socket is the
+    // service manager's, which also set its permissions
+    if (!this.activated) {
+      prepareSocketDir(this.config.ipcSocketPath);
+      if (existsSync(this.config.ipcSocketPath)) {
+        unlinkSync(this.config.ipcSocketPath);
+      }
     }
 
     return new Promise((resolve, reject) => {
@@ -58,8 +157,18 @@ export class IpcServer {
         reject(err);
       });
 
+      const fd = activatedSocketFd();
+      if (fd !== null) {
+        this.server.listen({ fd }, () => resolve());
+        return;
+      }
+
       // Bind to config.ipcSocketPath and start listening
       this.server.listen(this.config.ipcSocketPath, () => {
+        // Only the owner may connect; the CLI refuses sockets others can write to
+        if (process.platform !== "win32") {
+          chmodSync(this.config.ipcSocketPath, 0o600);
+        }
         resolve();
       });
     });
@@ -83,8 +192,8 @@ export class IpcServer {
 
       // Stop accepting new connections and close the server
       this.server.close(() => {
-        // Clean up socket file
-        if (existsSync(this.config.ipcSocketPath)) {
+        // Clean up socket file, unless the service manager owns it
+        if (!this.activated && existsSync(this.config.ipcSocketPath)) {
           unlinkSync(this.config.ipcSocketPath);
         }
         this.server = null;
@@ -109,6 +218,34 @@ export class IpcServer {
     this.commandHandler = handler;
   }
 
+  /**
+   * Register the handler that reports versions for `ping` requests
+   */
+  onPing(handler: PingHandler): void {
+    this.pingHandler = handler;
+  }
+
+  /**
+   * Register the handler that reports daemon state for `status` requests
+   */
+  onStatus(handler: StatusHandler): void {
+    this.statusHandler = handler;
+  }
+
+  /**
+   * Register the handler that stops the daemon on `shutdown` requests
+   */
+  onShutdown(handler: ShutdownHandler): void {
+    this.shutdownHandler = handler;
+  }
+
+  /**
+   * Register the handler that cancels commands on `cancel` requests
+   */
+  onCancel(handler: CancelHandler): void {
+    this.cancelHandler = handler;
+  }
+
   // ===========================================================================
   // Connection Handling
   // ===========================================================================
@@ -123,7 +260,7 @@ export class IpcServer {
     let buffer = "";
 
     // Handle incoming data chunks
-    socket.on("data", async (chunk: Buffer) => {
+    socket.on("data", (chunk: Buffer) => {
       buffer += chunk.toString("utf8");
 
       // Check for complete messages (delimited by newline)
@@ -135,7 +272,9 @@ export class IpcServer {
         // Parse and handle the complete message
         const message = this.parseMessage(Buffer.from(rawMessage, "utf8"));
         if (message) {
-          await this.handleMessage(socket, message);
+          // Not awaited: commands on one connection run concurrently, and
+          // clients match each response to its command by ID
+          void this.handleMessage(socket, message);
         } else {
           // Send error response for invalid message format
           this.sendResponse(socket, {
@@ -166,9 +305,10 @@ export class IpcServer {
     socket: Socket,
     message: IpcMessage
   ): Promise<void> {
-    // DEBUG: Log received message
-    console.log("[IPC DEBUG] Received message:", JSON.stringify(message, null, 2));
-    
+    if (process.env.BROWSER_DAEMON_DEBUG === "1") {
+      console.log("[IPC DEBUG] Received message:", describeMessage(message));
+    }
+
     // Validate message structure
     if (!message || typeof message.type !== "string") {
       this.sendResponse(socket, {
@@ -185,13 +325,76 @@ export class IpcServer {
         // Respond with pong
         const pongMessage: IpcMessage = {
           type: "pong",
+          payload: this.pingHandler ? this.pingHandler() : null,
+        };
+        this.reply(socket, pongMessage, message.keepAlive === true);
+        break;
+
+      case "status":
+        if (!this.statusHandler) {
+          this.sendResponse(socket, {
+            id: "unknown",
+            success: false,
+            error: "No status handler registered",
+          });
+          return;
+        }
+        const statusMessage: IpcMessage = {
+          type: "status",
+          payload: this.statusHandler(),
+        };
+        socket.write(this.serializeMessage(statusMessage), () => {
+          socket.end();
+        });
+        break;
+
+      case "shutdown":
+        if (!this.shutdownHandler) {
+          this.sendResponse(socket, {
+            id: "unknown",
+            success: false,
+            error: "No shutdown handler registered",
+          });
+          return;
+        }
+        // Acknowledge before stopping, which closes every connection
+        const shutdownHandler = this.shutdownHandler;
+        const ackMessage: IpcMessage = {
+          type: "shutdown",
           payload: null,
         };
-        socket.write(this.serializeMessage(pongMessage), () => {
+        socket.write(this.serializeMessage(ackMessage), () => {
           socket.end();
+          shutdownHandler();
         });
         break;
 
+      case "cancel":
+        // May arrive on the command's own connection or on a new one
+        if (!this.cancelHandler) {
+          this.sendResponse(socket, {
+            id: "unknown",
+            success: false,
+            error: "No cancel handler registered",
+          });
+          return;
+        }
+        const commandId = (message.payload as CancelPayload | null)?.commandId;
+        if (typeof commandId !== "string") {
+          this.sendResponse(socket, {
+            id: "unknown",
+            success: false,
+            error: "Cancel payload requires a commandId",
+          });
+          return;
+        }
+        const cancelAck: IpcMessage = {
+          type: "cancel_ack",
+          payload: { commandId, cancelled: this.cancelHandler(commandId) },
+        };
+        this.reply(socket, cancelAck, message.keepAlive === true);
+        break;
+
       case "command":
         // Invoke command handler
         if (!this.commandHandler) {
@@ -212,17 +415,18 @@ export class IpcServer {
           return;
         }
 
+        const keepAlive = message.keepAlive === true;
         try {
           const command = message.payload as Command;
           const response = await this.commandHandler(command);
-          this.sendResponse(socket, response);
+          this.sendResponse(socket, response, keepAlive);
         } catch (err) {
           this.sendResponse(socket, {
             id: (message.payload as Command)?.id ?? "unknown",
             success: false,
             error:
               err instanceof Error ? err.message : "Unknown error occurred",
-          });
+          }, keepAlive);
         }
         break;
 
@@ -277,18 +481,32 @@ export class IpcServer {
   /**
    * Send a response back to the CLI
    */
-  private sendResponse(socket: Socket, response: CommandResponse): void {
+  private sendResponse(
+    socket: Socket,
+    response: CommandResponse,
+    keepAlive: boolean = false
+  ): void {
     // Wrap response in IpcMessage envelope
     const message: IpcMessage = {
       type: "response",
       payload: response,
     };
 
-    // Serialize message
-    const data = this.serializeMessage(message);
+    this.reply(socket, message, keepAlive);
+  }
+
+  /**
+   * Send a reply, then close the connection unless the client asked to keep it
+   */
+  private reply(socket: Socket, message: IpcMessage, keepAlive: boolean): void {
+    if (keepAlive) {
+      // Tells the client it may send its next message on this connection
+      socket.write(this.serializeMessage({ ...message, keepAlive: true }));
+      return;
+    }
 
     // Write to socket and close after write completes
-    socket.write(data, () => {
+    socket.write(this.serializeMessage(message), () => {
       socket.end();
     });
   }
@@ -6,6 +6,8 @@
 
 import type { WebSocket } from "ws";
 import type { ChildProcess } from "node:child_process";
+import { tmpdir } from "node:os";
+import { isAbsolute, join } from "node:path";
 
 // =============================================================================
 // Session Types
@@ -111,6 +113,8 @@ export interface Command {
   type: CommandType;
   params?: Record<string, unknown>;
   timestamp: Date;
+  /** ISO 8601 time after which the CLI no longer waits for the response */
+  deadline?: string;
 }
 
 /**
@@ -150,14 +154,67 @@ export interface CommandResponse {
 /**
  * Message types for IPC communication
  */
-export type IpcMessageType = "command" | "response" | "ping" | "pong" | "get_endpoint" | "endpoint" | "register_extension" | "registration";
+export type IpcMessageType = "command" | "response" | "ping" | "pong" | "status" | "shutdown" | "cancel" | "cancel_ack" | "get_endpoint" | "endpoint" | "register_extension" | "registration";
 
 /**
  * IPC message envelope
  */
 export interface IpcMessage {
   type: IpcMessageType;
-  payload: Command | CommandResponse | null;
+  payload: Command | CommandResponse | DaemonStatus | PongPayload | CancelPayload | CancelAck | null;
+  /** Set by clients to keep the connection open; echoed when it is kept */
+  keepAlive?: boolean;
+}
+
+/**
+ * Payload of a "cancel" message: the command the CLI stopped waiting for
+ */
+export interface CancelPayload {
+  commandId: CommandId;
+}
+
+/**
+ * Payload of the "cancel_ack" reply to a "cancel" message
+ */
+export interface CancelAck {
+  commandId: CommandId;
+  /** False if the command had already finished or was never received */
+  cancelled: boolean;
+}
+
+/**
+ * Daemon state reported in reply to a "status" message
+ */
+export interface DaemonStatus {
+  pid: number;
+  uptimeMs: number;
+  version: string;
+  socket: string;
+  sessions: number;
+}
+
+/**
+ * Versions reported in reply to a "ping" message
+ *
+ * Clients compare the protocol major version with their own and warn or
+ * refuse when it differs.
+ */
+export interface PongPayload {
+  protocolVersion: string;
+  version: string;
+  extensions: ExtensionVersion[];
+}
+
+/**
+ * Versions an extension reported when it registered for a session
+ *
+ * Extensions that predate the handshake report neither.
+ */
+export interface ExtensionVersion {
+  session: string;
+  sessionId: SessionId;
+  version: string | null;
+  protocolVersion: string | null;
 }
 
 // =============================================================================
@@ -195,6 +252,10 @@ export interface WsMessage {
 export interface ExtensionRegistration {
   windowId: number;
   cachedSessionId: SessionId | undefined;
+  /** Extension build version, from its manifest */
+  version?: string;
+  /** Protocol version the extension speaks */
+  protocolVersion?: string;
 }
 
 // =============================================================================
@@ -243,11 +304,39 @@ export interface DaemonConfig {
   defaultBrowserPath?: string;
 }
 
+/**
+ * Daemon version, reported by --version and in status and ping replies
+ */
+export const DAEMON_VERSION = "0.2.4";
+
+/**
+ * Version of the CLI <-> daemon <-> extension protocol
+ *
+ * The major version changes when messages change incompatibly.
+ */
+export const PROTOCOL_VERSION = "1.1";
+
+/**
+ * Default IPC socket path, the same one the CLI connects to by default:
+ * $XDG_RUNTIME_DIR/browser/daemon.sock, or without XDG_RUNTIME_DIR a
+ * per-user directory in the temp directory. Named pipe on Windows.
+ */
+export function defaultSocketPath(): string {
+  if (process.platform === "win32") {
+    return "\\\\.\\pipe\\browser-daemon";
+  }
+  const runtimeDir = process.env.XDG_RUNTIME_DIR;
+  if (runtimeDir && isAbsolute(runtimeDir)) {
+    return join(runtimeDir, "browser", "daemon.sock");
+  }
+  return join(tmpdir(), `browser-${process.geteuid?.() ?? 0}`, "daemon.sock");
+}
+
 /**
  * Default daemon configuration
  */
 export const DEFAULT_CONFIG: DaemonConfig = {
-  ipcSocketPath: "/tmp/browser-daemon.sock",
+  ipcSocketPath: defaultSocketPath(),
   wsPort: 9222,
   heartbeatInterval: 30000,
   heartbeatTimeout: 10000,
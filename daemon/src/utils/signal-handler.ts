@@ -4,7 +4,9 @@ export function setupSignalHandlers(daemon: BrowserDaemon): void {
 
     process.on("SIGTERM", () => {
         console.log("Received SIGTERM signal");
-        daemon.stop().catch((err) => {
+        daemon.stop().then(() => {
+            process.exit(0);
+        }).catch((err) => {
             console.error("Error during shutdown:", err);
             process.exit(1);
         });
@@ -11,6 +11,7 @@ import type {
   ExtensionCommand,
   ExtensionResponse,
   ExtensionRegistration,
+  ExtensionVersion,
   SessionId,
   DaemonConfig,
 } from "./types.js";
@@ -44,6 +45,7 @@ export class WsServer {
   private connectionToSession: Map<WebSocket, SessionId> = new Map();
   private eventHandlers: WsServerEventHandlers | null = null;
   private heartbeatStates: Map<SessionId, HeartbeatState> = new Map();
+  private registrations: Map<SessionId, ExtensionRegistration> = new Map();
   private sessionManager: SessionManager | null = null;
 
   constructor(private config: DaemonConfig) { }
@@ -246,6 +248,7 @@ export class WsServer {
     // Store new connection
     this.connections.set(sessionId, ws);
     this.connectionToSession.set(ws, sessionId);
+    this.registrations.set(sessionId, registration);
 
     // Update session state
     sessionManager.setExtensionConnection(sessionId, ws);
@@ -291,6 +294,7 @@ export class WsServer {
 
     // Remove from connections map
     this.connections.delete(sessionId);
+    this.registrations.delete(sessionId);
     if (ws) {
       this.connectionToSession.delete(ws);
     }
@@ -357,6 +361,32 @@ export class WsServer {
       this.heartbeatStates.delete(oldSessionId);
       this.heartbeatStates.set(newSessionId, heartbeatState);
     }
+
+    // Move registration
+    const registration = this.registrations.get(oldSessionId);
+    if (registration) {
+      this.registrations.delete(oldSessionId);
+      this.registrations.set(newSessionId, registration);
+    }
+  }
+
+  /**
+   * Versions reported by each connected extension
+   */
+  getExtensionVersions(): ExtensionVersion[] {
+    const versions: ExtensionVersion[] = [];
+    for (const [sessionId, registration] of this.registrations) {
+      if (!this.isConnected(sessionId)) {
+        continue;
+      }
+      versions.push({
+        session: this.sessionManager?.getSession(sessionId)?.name ?? sessionId,
+        sessionId,
+        version: registration.version ?? null,
+        protocolVersion: registration.protocolVersion ?? null,
+      });
+    }
+    return versions;
   }
 
   // ===========================================================================
@@ -1,3 +1,4 @@
+import { PROTOCOL_VERSION } from '../shared/types';
 import type { AgentCommand, AgentResponse, ExtensionConfig } from '../shared/types';
 import { getCachedSessionId, setCachedSessionId } from './storage';
 
@@ -163,6 +164,8 @@ export function createWebSocketManager(
             type: 'register',
             windowId,
             cachedSessionId,
+            version: chrome.runtime.getManifest().version,
+            protocolVersion: PROTOCOL_VERSION,
           }));
           // Daemon assigns unique session ID - ONE WINDOW = ONE SESSION
           startHeartbeat();
@@ -9,6 +9,16 @@ import { isElementVisible } from './utils';
 // Counter for generating unique refs
 let findRefCounter = 0;
 
+/**
+ * Match text the way the CLI matches snapshot names: exactly when `exact`,
+ * otherwise as a case-insensitive substring
+ */
+function textMatches(content: string | null, value: string, exact?: boolean): boolean {
+    const candidate = content?.trim() ?? '';
+    if (exact) return candidate === value;
+    return candidate.toLowerCase().includes(value.toLowerCase());
+}
+
 /**
  * Find element based on params
  * @param params - Find parameters
@@ -16,7 +26,7 @@ let findRefCounter = 0;
  * @returns The ref of the found element
  */
 export async function findElement(params: FindParams, registry: RefRegistry): Promise<string | null> {
-    const { locator, value, text } = params;
+    const { locator, value, text, exact } = params;
 
     let elements: Element[] = [];
 
@@ -28,13 +38,13 @@ export async function findElement(params: FindParams, registry: RefRegistry): Pr
 
         case 'text':
             elements = Array.from(document.querySelectorAll('*')).filter(el =>
-                el.textContent?.trim().includes(value) && el.children.length === 0
+                el.children.length === 0 && textMatches(el.textContent, value, exact)
             );
             break;
 
         case 'label':
             elements = Array.from(document.querySelectorAll('label')).filter(l =>
-                l.textContent?.trim().includes(value)
+                textMatches(l.textContent, value, exact)
             ).map(l => {
                 if (l.htmlFor) return document.getElementById(l.htmlFor);
                 return l.querySelector('input, select, textarea');
@@ -266,7 +266,7 @@ export async function executeAction(request: ContentRequest): Promise<ContentRes
         const value = request.params?.value;
         if (!locator || !value) return { success: false, error: 'Missing locator or value' };
         const ref = await findElement(
-          { locator, value, text: request.params?.text },
+          { locator, value, text: request.params?.text, exact: request.params?.exact },
           currentRegistry
         );
         // Return array format per protocol spec: [{ ref: "...", nodeId: "..." }]
@@ -29,6 +29,7 @@ export interface ContentRequest {
     dst?: string;
     files?: string[];
     locator?: string;
+    exact?: boolean;
     action?: string;
     x?: number;
     y?: number;
@@ -110,6 +110,8 @@ export interface FindParams {
   value: string;
   action?: string;
   text?: string;
+  /** Match `text` and `label` values exactly instead of as a case-insensitive substring */
+  exact?: boolean;
 }
 
 export interface MouseParams {
@@ -228,6 +230,13 @@ export interface ExtensionConfig {
 
 declare const process: { env: { WS_URL: string } };
 
+/**
+ * Version of the CLI <-> daemon <-> extension protocol, sent when registering
+ *
+ * The major version changes when messages change incompatibly.
+ */
+export const PROTOCOL_VERSION = '1.1';
+
 export const DEFAULT_CONFIG: ExtensionConfig = {
   websocketUrl: process.env.WS_URL || 'ws://localhost:9222',
   reconnectInterval: 3000,
@@ -12,6 +12,7 @@ import { getInfo } from '../src/content/actions/get';
 import { checkState } from '../src/content/actions/is';
 import { scroll } from '../src/content/actions/scroll';
 import { wait } from '../src/content/actions/wait';
+import { executeAction } from '../src/content/executor';
 import type { RefRegistry } from '../src/shared/types';
 
 function createRegistry(): RefRegistry {
@@ -286,4 +287,21 @@ describe('action handlers', () => {
       expect(duration).toBeGreaterThanOrEqual(90); // Allow some jitter
     });
   });
+
+  describe('find', () => {
+    // jsdom has no layout, so only fixed elements count as visible
+    const find = async (value: string, exact?: boolean) => {
+      const response = await executeAction({ action: 'find', params: { locator: 'text', value, exact } });
+      const { result } = response.data as { result: { ref: string }[] };
+      return result.length;
+    };
+
+    it('matches quoted text exactly and plain text as a substring', async () => {
+      document.body.innerHTML = '<button style="position: fixed">Save draft</button>';
+
+      expect(await find('save')).toBe(1);
+      expect(await find('Save', true)).toBe(0);
+      expect(await find('Save draft', true)).toBe(1);
+    });
+  });
 });